@@ -0,0 +1,56 @@
+//! Compares the default build with the `embedded` profile: run it once with each,
+//!
+//! ```text
+//! cargo bench --bench profile
+//! cargo bench --bench profile --features embedded
+//! ```
+//!
+//! and compare the lines it prints. It solves the same boards with the same solvers in both, and
+//! reports the time they took and the size of the benchmark executable, which links in the solver.
+
+use std::env;
+use std::fs;
+use std::time::Instant;
+
+use tiles::dataset::Sampling;
+use tiles::instances::InstanceSet;
+use tiles::solver::{Algorithm, Solver};
+
+const BOARDS: usize = 200;
+const SEED: u64 = 7;
+const ROUNDS: usize = 5;
+
+fn main() {
+    let profile = if cfg!(feature = "embedded") { "embedded" } else { "default" };
+    let boards = InstanceSet::generate(Sampling::Uniform, SEED, BOARDS).boards;
+
+    for algorithm in [Algorithm::AStar, Algorithm::GreedyBestFirst] {
+        let solver = Solver::new(algorithm);
+        let mut steps = 0;
+        let mut expanded = 0;
+        //the fastest of a few rounds, the others being slowed down by the rest of the machine
+        let mut fastest = None;
+        for _ in 0..ROUNDS {
+            let start = Instant::now();
+            steps = 0;
+            expanded = 0;
+            for board in &boards {
+                let result = solver.run(*board);
+                steps += result.plan.map_or(0, |plan| plan.steps());
+                expanded += result.statistics.expanded as usize;
+            }
+            let elapsed = start.elapsed();
+            fastest = Some(fastest.map_or(elapsed, |fastest: std::time::Duration| fastest.min(elapsed)));
+        }
+
+        let fastest = fastest.unwrap();
+        println!("{} {}: {} boards in {:.1} ms ({:.0} expansions/s), {} steps, {} expanded",
+                 profile, algorithm, BOARDS, fastest.as_secs_f64() * 1000.0, expanded as f64 / fastest.as_secs_f64(), steps, expanded);
+    }
+
+    let executable = env::current_exe().and_then(fs::metadata).map(|metadata| metadata.len());
+    match executable {
+        Ok(size) => println!("{} executable: {} bytes", profile, size),
+        Err(e) => println!("{} executable: unknown size ({})", profile, e),
+    }
+}
@@ -0,0 +1,165 @@
+use crate::board::{Board, Tile, HIDDEN};
+use crate::plan::Plan;
+use crate::search::{self, Limits, SearchBuffers, State};
+use crate::BoardState;
+
+/// Tiles grouped into classes that are placed one class after the other. While placing a class,
+/// the search runs on abstract boards where the tiles of the later classes all look the same,
+/// which leaves far fewer boards to tell apart than the concrete puzzle has.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Abstraction {
+    classes: Vec<Vec<Tile>>,
+}
+
+impl Abstraction {
+    pub fn new(classes: Vec<Vec<Tile>>) -> Abstraction {
+        assert!(!classes.is_empty(), "An abstraction needs at least one class of tiles.");
+        assert!(classes.iter().flatten().all(|tile| *tile > 0), "The classes can only contain numbered tiles.");
+
+        Abstraction { classes }
+    }
+
+    /// One class for each tile of the goal in reading order, except for the tiles of the last two
+    /// rows that are placed together, since the last row cannot be completed without moving the
+    /// tiles of the row above. Placing a whole row at once takes millions of boards on a 5x5 board.
+    pub fn tile_by_tile<const R: usize, const C: usize>() -> Abstraction {
+        let goal = Board::<R, C>::goal();
+        let mut classes: Vec<Vec<Tile>> = goal.tiles().iter().copied()
+            .take(C * R.saturating_sub(2))
+            .map(|tile| vec![tile])
+            .collect();
+        classes.push(goal.tiles().iter().copied().skip(C * R.saturating_sub(2)).filter(|tile| *tile != 0).collect());
+
+        Abstraction::new(classes)
+    }
+
+    pub fn classes(&self) -> &[Vec<Tile>] {
+        &self.classes
+    }
+
+    /// The board as seen while placing the classes up to `class` (inclusive): only the tiles of
+    /// those classes keep their number. Placing the last class looks at the concrete board, so that
+    /// tiles left out of every class are placed with it.
+    pub fn abstract_board<const R: usize, const C: usize>(&self, board: &Board<R, C>, class: usize) -> Board<R, C> {
+        if class + 1 >= self.classes.len() {
+            return *board;
+        }
+
+        let placed = &self.classes[..=class];
+        let mut tiles: [[Tile; C]; R] = (*board).into();
+        for tile in tiles.as_flattened_mut() {
+            if *tile != 0 && !placed.iter().any(|tiles| tiles.contains(tile)) {
+                *tile = HIDDEN;
+            }
+        }
+
+        Board::from_rows(tiles)
+    }
+}
+
+/// Solves the board in two levels: for each class of the abstraction, an A* search on the abstract
+/// boards finds a skeleton plan that puts the tiles of that class in place, which is then refined
+/// into concrete boards by replaying its moves from the board reached so far.
+/// Every abstract search expands at most `budget` boards, and the plan is not found if any of them
+/// gives up. The plan is usually longer than the optimal one, but it scales to 5x5 boards.
+pub fn two_level_search<const R: usize, const C: usize>(board: Board<R, C>, abstraction: &Abstraction, budget: i32) -> Option<Plan<R, C>> {
+    if !board.is_solvable() {
+        return None;
+    }
+
+    let mut buffers = SearchBuffers::new();
+    let mut boards = vec![board];
+    for class in 0..abstraction.classes().len() {
+        let current = *boards.last().unwrap();
+        let initial_state = BoardState::manhattan(abstraction.abstract_board(&current, class));
+        let limits = Limits { max_expanded: Some(budget), ..Limits::none() };
+        //the hidden tiles are left out of the manhattan distance, so it is zero once the class is placed
+        let result = search::a_star_search_buffered(&initial_state, |state| state.h() == 0, limits, &mut buffers);
+
+        let skeleton = result.plan_actions?;
+        let mut concrete = current;
+        for next_move in skeleton {
+            //the abstract board has the blank in the same place, so the move is always possible
+            concrete = concrete.apply_move(next_move).unwrap();
+            boards.push(concrete);
+        }
+    }
+
+    Some(Plan::new(boards).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    use crate::board::GOAL;
+
+    use super::*;
+
+    /// A board reached with a random walk from the goal
+    fn walk<const R: usize, const C: usize>(steps: usize) -> Board<R, C> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut board = Board::<R, C>::goal();
+        for _ in 0..steps {
+            board = *board.successors().choose(&mut rng).unwrap();
+        }
+        board
+    }
+
+    #[test]
+    fn test_tile_by_tile() {
+        let abstraction = Abstraction::tile_by_tile::<4, 4>();
+        assert_eq!(abstraction.classes(), &[
+            vec![1], vec![2], vec![3], vec![4], vec![5], vec![6], vec![7], vec![8],
+            vec![9, 10, 11, 12, 13, 14, 15],
+        ]);
+
+        let abstraction = Abstraction::tile_by_tile::<2, 3>();
+        assert_eq!(abstraction.classes(), &[vec![1, 2, 3, 4, 5]]);
+    }
+
+    #[test]
+    fn test_abstract_board() {
+        let abstraction = Abstraction::new(vec![vec![1, 2, 3], vec![4, 5, 6, 7, 8]]);
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+
+        assert_eq!(abstraction.abstract_board(&board, 0), Board::from_rows([[HIDDEN, HIDDEN, HIDDEN], [2, HIDDEN, HIDDEN], [3, 0, 1]]));
+        assert_eq!(abstraction.abstract_board(&board, 1), board);
+    }
+
+    #[test]
+    fn test_solves_eight_puzzle() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let plan = two_level_search(board, &Abstraction::tile_by_tile::<3, 3>(), 100_000).unwrap();
+
+        assert_eq!(*plan.start(), board);
+        assert_eq!(*plan.end(), GOAL);
+        assert!(plan.steps() >= 31);
+    }
+
+    #[test]
+    fn test_solves_large_boards() {
+        let board = walk::<4, 4>(1000);
+        let plan = two_level_search(board, &Abstraction::tile_by_tile::<4, 4>(), 100_000).unwrap();
+        assert!(plan.end().is_goal());
+
+        let board = walk::<5, 5>(1000);
+        let plan = two_level_search(board, &Abstraction::tile_by_tile::<5, 5>(), 100_000).unwrap();
+        assert_eq!(*plan.start(), board);
+        assert!(plan.end().is_goal());
+    }
+
+    #[test]
+    fn test_budget_exceeded() {
+        let board = walk::<5, 5>(1000);
+        assert!(two_level_search(board, &Abstraction::tile_by_tile::<5, 5>(), 10).is_none());
+    }
+
+    #[test]
+    fn test_unsolvable_board() {
+        let board = Board::new([1, 2, 3, 4, 5, 6, 8, 7, 0]);
+        assert!(two_level_search(board, &Abstraction::tile_by_tile::<3, 3>(), 100_000).is_none());
+    }
+}
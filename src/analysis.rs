@@ -0,0 +1,340 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::board::{Board, Tile};
+use crate::trace::Trace;
+
+/// How often each position held the blank, and each tile, among the boards expanded by a search
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ExpansionHeatmap {
+    expanded: u32,
+    blank: [u32; 9],
+    tiles: [[u32; 9]; 9],
+}
+
+impl ExpansionHeatmap {
+    pub fn new(trace: &Trace) -> ExpansionHeatmap {
+        let mut blank = [0; 9];
+        let mut tiles = [[0; 9]; 9];
+        for expansion in trace.expansions() {
+            for (pos, tile) in expansion.board.tiles().iter().copied().enumerate() {
+                tiles[tile as usize][pos] += 1;
+                if tile == 0 {
+                    blank[pos] += 1;
+                }
+            }
+        }
+
+        ExpansionHeatmap { expanded: trace.len() as u32, blank, tiles }
+    }
+
+    /// The number of expanded boards the heatmap counts
+    pub fn expanded(&self) -> u32 {
+        self.expanded
+    }
+
+    /// Expansions with the blank at each position, in row major order
+    pub fn blank(&self) -> &[u32; 9] {
+        &self.blank
+    }
+
+    /// Expansions with the tile at each position, in row major order
+    pub fn tile(&self, tile: Tile) -> &[u32; 9] {
+        &self.tiles[tile as usize]
+    }
+
+    /// The share of the expansions with the blank at each position
+    pub fn blank_fractions(&self) -> [f64; 9] {
+        let mut fractions = [0.0; 9];
+        if self.expanded > 0 {
+            for (fraction, count) in fractions.iter_mut().zip(self.blank.iter()) {
+                *fraction = *count as f64 / self.expanded as f64;
+            }
+        }
+
+        fractions
+    }
+}
+
+impl Display for ExpansionHeatmap {
+    /// The blank positions as a grid of percentages of the expansions
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let fractions = self.blank_fractions();
+        for row in fractions.chunks(3) {
+            let cells: Vec<String> = row.iter().map(|fraction| format!("{:5.1}%", fraction * 100.0)).collect();
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+
+        write!(f, "({} expansions)", self.expanded)
+    }
+}
+
+/// The boards expanded by one search and not the other, identified by their permutation rank, to see
+/// where a better heuristic saved expansions
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TraceDiff {
+    first_expanded: usize,
+    second_expanded: usize,
+    common: usize,
+    only_first: Vec<(u32, i32)>,
+    only_second: Vec<(u32, i32)>,
+}
+
+impl TraceDiff {
+    pub fn new(first: &Trace, second: &Trace) -> TraceDiff {
+        let first_ranks = distinct_ranks(first);
+        let second_ranks = distinct_ranks(second);
+        let first_set: HashSet<u32> = first_ranks.iter().map(|(rank, _)| *rank).collect();
+        let second_set: HashSet<u32> = second_ranks.iter().map(|(rank, _)| *rank).collect();
+
+        TraceDiff {
+            first_expanded: first_ranks.len(),
+            second_expanded: second_ranks.len(),
+            common: first_set.intersection(&second_set).count(),
+            only_first: first_ranks.into_iter().filter(|(rank, _)| !second_set.contains(rank)).collect(),
+            only_second: second_ranks.into_iter().filter(|(rank, _)| !first_set.contains(rank)).collect(),
+        }
+    }
+
+    /// The number of distinct boards expanded by the first search
+    pub fn first_expanded(&self) -> usize {
+        self.first_expanded
+    }
+
+    /// The number of distinct boards expanded by the second search
+    pub fn second_expanded(&self) -> usize {
+        self.second_expanded
+    }
+
+    /// The number of boards expanded by both searches
+    pub fn common(&self) -> usize {
+        self.common
+    }
+
+    /// The rank and heuristic value of the boards only the first search expanded, in its order
+    pub fn only_first(&self) -> &[(u32, i32)] {
+        &self.only_first
+    }
+
+    /// The rank and heuristic value of the boards only the second search expanded, in its order
+    pub fn only_second(&self) -> &[(u32, i32)] {
+        &self.only_second
+    }
+
+    /// The share of the boards expanded by either search that both expanded (Jaccard index)
+    pub fn overlap(&self) -> f64 {
+        let union = self.first_expanded + self.second_expanded - self.common;
+        if union == 0 {
+            1.0
+        } else {
+            self.common as f64 / union as f64
+        }
+    }
+}
+
+impl Display for TraceDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Expanded by the first search: {}", self.first_expanded)?;
+        writeln!(f, "Expanded by the second search: {}", self.second_expanded)?;
+        writeln!(f, "Expanded by both: {}", self.common)?;
+        writeln!(f, "Only by the first: {}", self.only_first.len())?;
+        writeln!(f, "Only by the second: {}", self.only_second.len())?;
+        write!(f, "Overlap: {:.1}%", self.overlap() * 100.0)
+    }
+}
+
+/// The rank and heuristic value of the expanded boards, in order, leaving out the boards expanded
+/// again
+fn distinct_ranks(trace: &Trace) -> Vec<(u32, i32)> {
+    let mut seen = HashSet::new();
+    trace.expansions().iter()
+        .map(|expansion| (expansion.board.rank(), expansion.h))
+        .filter(|(rank, _)| seen.insert(*rank))
+        .collect()
+}
+
+/// The heuristic values along random walks from a board, with how long the walks stayed on plateaus
+/// (consecutive boards with the same value) and in local minima (consecutive boards none of whose
+/// successors has a lower value, where enforced hill climbing has to fall back on breadth first
+/// search to find a way out)
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SurfaceSample {
+    walks: Vec<Vec<i32>>,
+    plateaus: BTreeMap<usize, u32>,
+    local_minima: BTreeMap<usize, u32>,
+}
+
+impl SurfaceSample {
+    /// Takes `walks` random walks of `length` moves from the board, which never undo the move they
+    /// just made, and records the heuristic value of every board they go through
+    pub fn new<const R: usize, const C: usize, H: Fn(&Board<R, C>) -> i32>(board: Board<R, C>, heuristic: H, walks: usize, length: usize, seed: u64) -> SurfaceSample {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut sample = SurfaceSample { walks: Vec::with_capacity(walks), plateaus: BTreeMap::new(), local_minima: BTreeMap::new() };
+
+        for _ in 0..walks {
+            let mut values = Vec::with_capacity(length + 1);
+            let mut stalled = Vec::with_capacity(length + 1);
+            let mut previous = None;
+            let mut current = board;
+            for step in 0..=length {
+                let h = heuristic(&current);
+                let successors = current.successors();
+                values.push(h);
+                //the goal is the minimum the searches are after, rather than somewhere they stall
+                stalled.push(!current.is_goal() && successors.iter().all(|successor| heuristic(successor) >= h));
+
+                if step < length {
+                    let forward: Vec<&Board<R, C>> = successors.iter()
+                        .filter(|successor| Some(**successor) != previous)
+                        .collect();
+                    //on a board with a single row or column the walk can be at a dead end, and
+                    //then the only way on is back
+                    let next = match forward.choose(&mut rng) {
+                        Some(next) => **next,
+                        None => *successors.choose(&mut rng).unwrap(),
+                    };
+                    previous = Some(current);
+                    current = next;
+                }
+            }
+
+            for (_, size) in runs(&values).into_iter().filter(|(_, size)| *size > 1) {
+                *sample.plateaus.entry(size).or_insert(0) += 1;
+            }
+            for (_, size) in runs(&stalled).into_iter().filter(|(stalled, _)| *stalled) {
+                *sample.local_minima.entry(size).or_insert(0) += 1;
+            }
+            sample.walks.push(values);
+        }
+
+        sample
+    }
+
+    /// The heuristic values of the boards of each walk, starting with the initial board
+    pub fn walks(&self) -> &[Vec<i32>] {
+        &self.walks
+    }
+
+    /// How many plateaus of each size (in boards) the walks went through
+    pub fn plateaus(&self) -> &BTreeMap<usize, u32> {
+        &self.plateaus
+    }
+
+    /// How many local minima of each size (in boards) the walks went through
+    pub fn local_minima(&self) -> &BTreeMap<usize, u32> {
+        &self.local_minima
+    }
+}
+
+/// The runs of equal consecutive values, with their length
+fn runs<T: Copy + PartialEq>(values: &[T]) -> Vec<(T, usize)> {
+    let mut runs: Vec<(T, usize)> = Vec::new();
+    for value in values {
+        match runs.last_mut() {
+            Some((last, size)) if last == value => *size += 1,
+            _ => runs.push((*value, 1)),
+        }
+    }
+
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::GOAL;
+    use crate::heuristic::HeuristicKind;
+    use crate::solver::{Algorithm, Solver};
+
+    use super::*;
+
+    #[test]
+    fn test_heatmap() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let (_, trace) = Solver::new(Algorithm::AStar).run_traced(board);
+        let heatmap = ExpansionHeatmap::new(&trace);
+
+        assert_eq!(heatmap.expanded(), trace.len() as u32);
+        assert_eq!(heatmap.blank().iter().sum::<u32>(), heatmap.expanded());
+        assert_eq!(heatmap.blank(), heatmap.tile(0));
+        for tile in 1..9 {
+            assert_eq!(heatmap.tile(tile).iter().sum::<u32>(), heatmap.expanded());
+        }
+        assert!((heatmap.blank_fractions().iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_empty_heatmap() {
+        let heatmap = ExpansionHeatmap::new(&Trace::new());
+        assert_eq!(heatmap.blank_fractions(), [0.0; 9]);
+        assert_eq!(heatmap.to_string().lines().count(), 4);
+    }
+
+    #[test]
+    fn test_trace_diff() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let (_, manhattan) = Solver::new(Algorithm::AStar).run_traced(board);
+        let (_, displaced) = Solver::new(Algorithm::AStar).with_heuristic(HeuristicKind::DisplacedTiles).run_traced(board);
+        let diff = TraceDiff::new(&displaced, &manhattan);
+
+        //the better informed heuristic expands fewer boards
+        assert!(diff.only_first().len() > diff.only_second().len());
+        assert_eq!(diff.first_expanded(), diff.common() + diff.only_first().len());
+        assert_eq!(diff.second_expanded(), diff.common() + diff.only_second().len());
+        assert!(diff.overlap() > 0.0 && diff.overlap() < 1.0);
+        assert_eq!(diff.only_second()[0].1, Board::unrank(diff.only_second()[0].0).manhattan_dist());
+
+        let same = TraceDiff::new(&manhattan, &manhattan);
+        assert_eq!(same.overlap(), 1.0);
+        assert!(same.only_first().is_empty() && same.only_second().is_empty());
+        assert_eq!(TraceDiff::new(&Trace::new(), &Trace::new()).to_string().lines().last(), Some("Overlap: 100.0%"));
+    }
+
+    #[test]
+    fn test_runs() {
+        assert_eq!(runs(&[3, 3, 2, 2, 2, 3]), vec![(3, 2), (2, 3), (3, 1)]);
+        assert!(runs::<i32>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_surface_sample() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let sample = SurfaceSample::new(board, |board: &Board| board.manhattan_dist(), 10, 50, 0);
+
+        assert_eq!(sample.walks().len(), 10);
+        for walk in sample.walks() {
+            assert_eq!(walk.len(), 51);
+            assert_eq!(walk[0], board.manhattan_dist());
+            //every move changes the manhattan distance of a single tile by one
+            assert!(walk.windows(2).all(|step| (step[0] - step[1]).abs() == 1));
+        }
+        assert!(sample.plateaus().is_empty());
+        assert!(!sample.local_minima().is_empty());
+        assert_eq!(sample, SurfaceSample::new(board, |board: &Board| board.manhattan_dist(), 10, 50, 0));
+    }
+
+    #[test]
+    fn test_surface_sample_single_row() {
+        //the blank reaches the end of the row and has to turn back
+        let board = Board::<1, 3>::goal();
+        let sample = SurfaceSample::new(board, |board: &Board<1, 3>| board.manhattan_dist(), 3, 10, 0);
+        for walk in sample.walks() {
+            assert_eq!(walk.len(), 11);
+            assert!(walk.iter().all(|h| (0..=2).contains(h)));
+        }
+    }
+
+    #[test]
+    fn test_displaced_tiles_plateaus() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let sample = SurfaceSample::new(board, |board: &Board| board.displaced_tiles(), 10, 50, 0);
+        assert!(!sample.plateaus().is_empty());
+
+        let sample = SurfaceSample::new(GOAL, |board: &Board| board.displaced_tiles(), 1, 0, 0);
+        assert_eq!(sample.walks(), &[vec![0]]);
+        assert!(sample.local_minima().is_empty());
+    }
+}
@@ -0,0 +1,488 @@
+//! Anytime weighted A*: a quick plan from a heavy weight first, then cheaper ones from lighter
+//! weights as time allows, with the weights decided by a `WeightSchedule`: either restarting the
+//! search with every weight, or repairing the previous search with ARA*. A single search can also
+//! carry on past its first plan, with the weight unchanged.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::mem::size_of;
+use std::time::Duration;
+
+use crate::board::Board;
+use crate::clock::Instant;
+use crate::ensemble::{weighted_a_star, Incumbent, PRIORITY_SCALE};
+use crate::plan::Plan;
+use crate::search::{effective_branching_factor, Limits, Statistics};
+
+/// How the weight of the heuristic evolves, from one search to the next and during each search.
+/// Weights below 1 are raised to 1.
+pub trait WeightSchedule {
+    /// The weight of the first search
+    fn initial(&self) -> f64;
+
+    /// The weight of the search after one with `weight` ended, `elapsed` after the first one
+    /// started, or None to stop searching
+    fn next(&mut self, weight: f64, elapsed: Duration) -> Option<f64>;
+
+    /// The weight a search started with `weight` continues with, `elapsed` after the first search
+    /// started. The weight stays the same during a search unless the schedule says otherwise.
+    fn during(&self, weight: f64, _elapsed: Duration) -> f64 {
+        weight
+    }
+}
+
+/// A single search with the same weight throughout
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Fixed(pub f64);
+
+impl WeightSchedule for Fixed {
+    fn initial(&self) -> f64 {
+        self.0
+    }
+
+    fn next(&mut self, _weight: f64, _elapsed: Duration) -> Option<f64> {
+        None
+    }
+}
+
+/// The weight lowered by `step` after every search, down to `last`, which is searched once
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Stepwise {
+    pub first: f64,
+    pub step: f64,
+    pub last: f64,
+}
+
+impl WeightSchedule for Stepwise {
+    fn initial(&self) -> f64 {
+        self.first
+    }
+
+    fn next(&mut self, weight: f64, _elapsed: Duration) -> Option<f64> {
+        (weight > self.last).then(|| (weight - self.step).max(self.last))
+    }
+}
+
+/// The excess of the weight over 1 halved every `half_life`, also during the searches, until it is
+/// below `tolerance`, when a last search runs with a weight of 1
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimeDecay {
+    pub first: f64,
+    pub half_life: Duration,
+    pub tolerance: f64,
+}
+
+impl TimeDecay {
+    fn weight(&self, elapsed: Duration) -> f64 {
+        let weight = 1.0 + (self.first - 1.0) * 0.5f64.powf(elapsed.as_secs_f64() / self.half_life.as_secs_f64());
+        if weight - 1.0 < self.tolerance { 1.0 } else { weight }
+    }
+}
+
+impl WeightSchedule for TimeDecay {
+    fn initial(&self) -> f64 {
+        self.first
+    }
+
+    fn next(&mut self, weight: f64, elapsed: Duration) -> Option<f64> {
+        (weight > 1.0).then(|| self.weight(elapsed))
+    }
+
+    fn during(&self, _weight: f64, elapsed: Duration) -> f64 {
+        self.weight(elapsed)
+    }
+}
+
+/// A plan cheaper than the ones found before it
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Improvement {
+    /// The weight the search started with
+    pub weight: f64,
+    pub steps: usize,
+    /// When the plan was found, after the first search started
+    pub elapsed: Duration,
+}
+
+/// The best plan found by the anytime search, and the ones it improved on
+#[derive(Debug, Clone)]
+pub struct AnytimeResult<const R: usize = 3, const C: usize = R> {
+    pub plan: Option<Plan<R, C>>,
+    /// The plans found, from the first to the cheapest
+    pub improvements: Vec<Improvement>,
+    /// The weights the searches started with, in order
+    pub weights: Vec<f64>,
+    /// Whether a search went through every board that could lead to a cheaper plan, which proves
+    /// the plan optimal (or that there is none) when the heuristic is admissible
+    pub exhausted: bool,
+}
+
+/// Restarting weighted A*: runs weighted A* (f = g + weight × h) with the weights of the schedule,
+/// one search after the other until the deadline, the schedule stops, or a search proves its plan
+/// optimal. Every search prunes the boards that cannot lead to a plan cheaper than the best one
+/// found so far. With the `embedded` feature, which has no clock, the deadline has no effect.
+pub fn anytime_weighted_a_star<const R: usize, const C: usize, H, S>(board: Board<R, C>, heuristic: H, schedule: &mut S, deadline: Duration) -> AnytimeResult<R, C>
+    where H: Fn(&Board<R, C>) -> i32,
+          S: WeightSchedule + ?Sized,
+{
+    let start = Instant::now();
+    let incumbent = Incumbent::new();
+    let mut statistics = Statistics::default();
+    let mut improvements = Vec::new();
+    let mut weights = Vec::new();
+    let mut exhausted = false;
+
+    let mut weight = schedule.initial().max(1.0);
+    while cfg!(feature = "embedded") || start.elapsed() < deadline {
+        weights.push(weight);
+        let limits = Limits { max_duration: Some(deadline - start.elapsed()), ..Limits::none() };
+        let before = incumbent.cost();
+        let during = |weight: f64| schedule.during(weight, start.elapsed()).max(1.0);
+        let (run, proved) = weighted_a_star(board, &heuristic, || during(weight), &incumbent, &limits, |_, _| {});
+        statistics = statistics.combine(&run);
+
+        if incumbent.cost() < before {
+            improvements.push(Improvement { weight, steps: incumbent.cost() as usize, elapsed: start.elapsed() });
+        }
+        if proved {
+            exhausted = true;
+            break;
+        }
+
+        match schedule.next(weight, start.elapsed()) {
+            Some(next) => weight = next.max(1.0),
+            None => break,
+        }
+    }
+
+    statistics.duration = start.elapsed();
+    AnytimeResult { plan: incumbent.into_plan(statistics), improvements, weights, exhausted }
+}
+
+/// Continuing weighted A*: a single weighted A* (f = g + weight × h) that does not stop at the
+/// first plan, but keeps searching with its cost as a bound, skipping the boards that cannot lead
+/// to a cheaper plan. Every cheaper plan is passed to `on_improvement` as soon as it is found,
+/// until the queue runs out, which proves the last plan optimal when the heuristic is admissible,
+/// or the limits are reached.
+pub fn continued_weighted_a_star<const R: usize, const C: usize, H, F>(board: Board<R, C>, heuristic: H, weight: f64, limits: &Limits, mut on_improvement: F) -> AnytimeResult<R, C>
+    where H: Fn(&Board<R, C>) -> i32,
+          F: FnMut(&Plan<R, C>, &Improvement),
+{
+    let weight = weight.max(1.0);
+    let incumbent = Incumbent::new();
+    let mut improvements = Vec::new();
+    let (statistics, exhausted) = weighted_a_star(board, &heuristic, || weight, &incumbent, limits, |boards, statistics| {
+        let statistics = Statistics { branching_factor: effective_branching_factor(statistics.created as usize, boards.len() - 1), ..statistics.clone() };
+        let found = Plan::from_search(boards.to_vec(), statistics);
+        let improvement = Improvement { weight, steps: found.steps(), elapsed: found.statistics().unwrap().duration };
+        on_improvement(&found, &improvement);
+        improvements.push(improvement);
+    });
+
+    AnytimeResult { plan: incumbent.into_plan(statistics), improvements, weights: vec![weight], exhausted }
+}
+
+struct AraNode<const R: usize, const C: usize> {
+    board: Board<R, C>,
+    parent: Option<usize>,
+    g: u32,
+    h: u32,
+    closed: bool,
+    //whether the node is in the list of closed nodes whose g decreased
+    inconsistent: bool,
+}
+
+/// Anytime Repairing A* (Likhachev, Gordon and Thrun): a single weighted A* whose weight is lowered
+/// after each plan it finds, reusing the boards already searched rather than starting over.
+/// The boards whose cost decreased after they were expanded are set aside, and queued again with
+/// the new weight, so that every search only repairs the previous one.
+/// Only `initial` and `next` of the schedule are used, and a weight above the previous one is
+/// lowered to it. Every cheaper plan is passed to `on_improvement` as soon as it is found.
+/// With the `embedded` feature, which has no clock, the deadline has no effect.
+pub fn ara_star<const R: usize, const C: usize, H, S, F>(board: Board<R, C>, heuristic: H, schedule: &mut S, deadline: Duration, mut on_improvement: F) -> AnytimeResult<R, C>
+    where H: Fn(&Board<R, C>) -> i32,
+          S: WeightSchedule + ?Sized,
+          F: FnMut(&Plan<R, C>, &Improvement),
+{
+    let start = Instant::now();
+    let limits = Limits { max_duration: Some(deadline), ..Limits::none() };
+    let mut statistics = Statistics::initial();
+    let priority = |weight: f64, node: &AraNode<R, C>| ((node.g as f64 + weight * node.h as f64) * PRIORITY_SCALE) as u64;
+
+    let h = heuristic(&board).max(0) as u32;
+    let mut nodes = vec![AraNode { board, parent: None, g: 0, h, closed: false, inconsistent: false }];
+    let mut indices = HashMap::new();
+    indices.insert(board, 0);
+    let mut goal = board.is_goal().then_some(0);
+
+    let mut weight = schedule.initial().max(1.0);
+    //ties are broken in favour of the deepest node; the g of the entry tells whether it is stale
+    let mut open = BinaryHeap::new();
+    open.push(Reverse((priority(weight, &nodes[0]), Reverse(nodes[0].g), 0)));
+    let mut inconsistent = Vec::new();
+
+    let mut plan: Option<Plan<R, C>> = None;
+    let mut improvements = Vec::new();
+    let mut weights = Vec::new();
+    let mut exhausted = false;
+    loop {
+        weights.push(weight);
+
+        //expand until no queued board has a lower priority than the goal, whose h is 0
+        let mut stopped = false;
+        while let Some(&Reverse((min, Reverse(g), index))) = open.peek() {
+            if goal.is_some_and(|goal| priority(weight, &nodes[goal]) <= min) {
+                break;
+            }
+            if limits.reached(&statistics, start) {
+                stopped = true;
+                break;
+            }
+
+            open.pop();
+            if nodes[index].closed || nodes[index].g != g {
+                continue;
+            }
+
+            nodes[index].closed = true;
+            statistics.expanded += 1;
+            for successor in nodes[index].board.successors() {
+                let g = g + 1;
+                let next = match indices.get(&successor) {
+                    Some(&next) if nodes[next].g <= g => continue,
+                    Some(&next) => {
+                        nodes[next].g = g;
+                        nodes[next].parent = Some(index);
+                        next
+                    }
+                    None => {
+                        statistics.count_created(1);
+                        let h = heuristic(&successor).max(0) as u32;
+                        indices.insert(successor, nodes.len());
+                        nodes.push(AraNode { board: successor, parent: Some(index), g, h, closed: false, inconsistent: false });
+                        nodes.len() - 1
+                    }
+                };
+
+                if successor.is_goal() {
+                    goal = Some(next);
+                }
+                if !nodes[next].closed {
+                    statistics.count_queued(1);
+                    open.push(Reverse((priority(weight, &nodes[next]), Reverse(g), next)));
+                } else if !nodes[next].inconsistent {
+                    nodes[next].inconsistent = true;
+                    inconsistent.push(next);
+                }
+            }
+        }
+
+        if let Some(goal) = goal.filter(|goal| plan.as_ref().is_none_or(|plan| nodes[*goal].g < plan.cost())) {
+            statistics.duration = start.elapsed();
+            statistics.set_seen(nodes.len());
+            statistics.branching_factor = effective_branching_factor(statistics.created as usize, nodes[goal].g as usize);
+            let found = Plan::from_search(ara_path(&nodes, goal), statistics.clone());
+            let improvement = Improvement { weight, steps: found.steps(), elapsed: statistics.duration };
+            on_improvement(&found, &improvement);
+            improvements.push(improvement);
+            plan = Some(found);
+        }
+
+        //with a weight of 1 the search is plain A*, whose plan is optimal
+        if !stopped && (weight <= 1.0 || open.is_empty() && inconsistent.is_empty()) {
+            exhausted = true;
+        }
+        if stopped || exhausted {
+            break;
+        }
+        match schedule.next(weight, start.elapsed()) {
+            Some(next) => weight = next.max(1.0).min(weight),
+            None => break,
+        }
+
+        //queue the inconsistent nodes with the open ones under the new weight, and reopen every node,
+        //dropping those that cannot lead to a plan cheaper than the current one
+        let bound = plan.as_ref().map_or(u32::MAX, |plan| plan.cost());
+        let mut queued: Vec<usize> = open.into_iter()
+            .map(|Reverse((_, _, index))| index)
+            .filter(|index| !nodes[*index].closed)
+            .chain(inconsistent.drain(..))
+            .collect();
+        queued.sort_unstable();
+        queued.dedup();
+        for node in nodes.iter_mut() {
+            node.closed = false;
+            node.inconsistent = false;
+        }
+
+        open = BinaryHeap::new();
+        for index in queued {
+            let node = &nodes[index];
+            if node.g + node.h < bound {
+                open.push(Reverse((priority(weight, node), Reverse(node.g), index)));
+            } else {
+                statistics.count_purged(1);
+            }
+        }
+    }
+
+    statistics.duration = start.elapsed();
+    statistics.set_seen(nodes.len());
+    statistics.memory = nodes.len() * size_of::<AraNode<R, C>>() + indices.len() * size_of::<(Board<R, C>, usize)>();
+    let plan = plan.map(|plan| {
+        let branching_factor = effective_branching_factor(statistics.created as usize, plan.steps());
+        Plan::from_search(plan.into_boards(), Statistics { branching_factor, ..statistics })
+    });
+    AnytimeResult { plan, improvements, weights, exhausted }
+}
+
+fn ara_path<const R: usize, const C: usize>(nodes: &[AraNode<R, C>], mut index: usize) -> Vec<Board<R, C>> {
+    let mut boards = vec![nodes[index].board];
+    while let Some(parent) = nodes[index].parent {
+        boards.push(nodes[parent].board);
+        index = parent;
+    }
+
+    boards.reverse();
+    boards
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::manhattan_heuristic;
+
+    use super::*;
+
+    #[test]
+    fn test_stepwise() {
+        let mut schedule = Stepwise { first: 3.0, step: 0.75, last: 1.0 };
+        let mut weights = vec![schedule.initial()];
+        while let Some(next) = schedule.next(*weights.last().unwrap(), Duration::ZERO) {
+            weights.push(next);
+        }
+
+        assert_eq!(weights, vec![3.0, 2.25, 1.5, 1.0]);
+        assert_eq!(Fixed(2.0).next(2.0, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn test_time_decay() {
+        let mut schedule = TimeDecay { first: 5.0, half_life: Duration::from_secs(1), tolerance: 0.1 };
+        assert_eq!(schedule.during(5.0, Duration::ZERO), 5.0);
+        assert_eq!(schedule.during(5.0, Duration::from_secs(2)), 2.0);
+        assert_eq!(schedule.next(5.0, Duration::from_secs(1)), Some(3.0));
+        //close enough to 1 for a last search, after which there is none
+        assert_eq!(schedule.next(2.0, Duration::from_secs(6)), Some(1.0));
+        assert_eq!(schedule.next(1.0, Duration::from_secs(7)), None);
+    }
+
+    #[test]
+    fn test_anytime_improves_to_optimal() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let mut schedule = Stepwise { first: 5.0, step: 2.0, last: 1.0 };
+        let result = anytime_weighted_a_star(board, manhattan_heuristic, &mut schedule, Duration::from_secs(60));
+
+        assert!(result.exhausted);
+        let plan = result.plan.unwrap();
+        assert_eq!(plan.steps(), 31);
+        assert_eq!(*plan.start(), board);
+        assert!(plan.end().is_goal());
+
+        assert_eq!(result.weights[0], 5.0);
+        assert_eq!(result.improvements.last().unwrap().steps, 31);
+        assert!(result.improvements.windows(2).all(|pair| pair[1].steps < pair[0].steps));
+    }
+
+    #[test]
+    fn test_schedule_stops() {
+        let board = Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]);
+        let result = anytime_weighted_a_star(board, manhattan_heuristic, &mut Fixed(3.0), Duration::from_secs(60));
+
+        assert_eq!(result.weights, vec![3.0]);
+        assert!(result.plan.unwrap().end().is_goal());
+    }
+
+    #[test]
+    fn test_time_decay_search() {
+        let board = Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]);
+        let mut schedule = TimeDecay { first: 3.0, half_life: Duration::from_millis(1), tolerance: 0.01 };
+        let result = anytime_weighted_a_star(board, manhattan_heuristic, &mut schedule, Duration::from_secs(60));
+
+        assert!(result.exhausted);
+        assert_eq!(result.plan.unwrap().steps(), 31);
+    }
+
+    #[test]
+    fn test_continued_weighted_a_star() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let mut reported = Vec::new();
+        let result = continued_weighted_a_star(board, manhattan_heuristic, 3.0, &Limits::none(), |plan, improvement| {
+            assert_eq!(plan.steps(), improvement.steps);
+            assert_eq!(*plan.start(), board);
+            assert!(plan.end().is_goal());
+            reported.push(*improvement);
+        });
+
+        assert!(result.exhausted);
+        assert_eq!(result.plan.unwrap().steps(), 31);
+        assert_eq!(reported, result.improvements);
+        assert_eq!(result.weights, vec![3.0]);
+        assert!(reported.windows(2).all(|pair| pair[1].steps < pair[0].steps));
+    }
+
+    #[test]
+    fn test_continued_weighted_a_star_budget() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let limits = Limits { max_expanded: Some(10), ..Limits::none() };
+        let result = continued_weighted_a_star(board, manhattan_heuristic, 5.0, &limits, |_, _| {});
+
+        assert!(!result.exhausted);
+        assert!(result.plan.is_none());
+        assert!(result.improvements.is_empty());
+    }
+
+    #[test]
+    fn test_ara_star() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let mut schedule = Stepwise { first: 5.0, step: 1.0, last: 1.0 };
+        let mut reported = Vec::new();
+        let result = ara_star(board, manhattan_heuristic, &mut schedule, Duration::from_secs(60), |plan, improvement| {
+            assert_eq!(plan.steps(), improvement.steps);
+            assert!(plan.end().is_goal());
+            reported.push(*improvement);
+        });
+
+        assert!(result.exhausted);
+        let plan = result.plan.unwrap();
+        assert_eq!(plan.steps(), 31);
+        assert_eq!(*plan.start(), board);
+        assert_eq!(reported, result.improvements);
+        assert_eq!(reported[0].weight, 5.0);
+        assert!(reported.windows(2).all(|pair| pair[1].steps < pair[0].steps));
+        assert!(result.weights.windows(2).all(|pair| pair[1] < pair[0]));
+    }
+
+    #[test]
+    fn test_ara_star_optimal() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let board = Board::<3>::random(&mut rng);
+            let mut schedule = Stepwise { first: 3.0, step: 0.5, last: 1.0 };
+            let result = ara_star(board, manhattan_heuristic, &mut schedule, Duration::from_secs(60), |_, _| {});
+
+            assert!(result.exhausted);
+            assert_eq!(result.plan.unwrap().steps(), crate::a_star_search(board).unwrap().steps(), "{}", board);
+        }
+    }
+
+    #[test]
+    fn test_ara_star_unsolvable() {
+        let board = Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]);
+        let result = ara_star(board, manhattan_heuristic, &mut Stepwise { first: 2.0, step: 1.0, last: 1.0 }, Duration::from_secs(60), |_, _| panic!("There is no plan."));
+
+        assert!(result.exhausted);
+        assert!(result.plan.is_none());
+    }
+}
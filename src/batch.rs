@@ -0,0 +1,315 @@
+//! Experimental: A* with the heuristic evaluated in batches by an external backend (e.g. a GPU
+//! compute shader), which completes the batches asynchronously while the search keeps expanding.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::mem::size_of;
+
+use crate::board::Board;
+use crate::clock::Instant;
+use crate::pdb::DisjointPatternDatabases;
+use crate::plan::Plan;
+use crate::search::{effective_branching_factor, Statistics};
+
+/// Backend evaluating the heuristic of many boards at a time.
+/// Batches may complete in any order, and the search never blocks on a batch while it has other
+/// nodes to expand.
+pub trait BatchEvaluator {
+    /// Handle of a submitted batch
+    type Ticket;
+
+    /// Starts evaluating the boards
+    fn submit(&mut self, boards: &[Board]) -> Self::Ticket;
+
+    /// The heuristic values, in the order of the submitted boards, if the batch has completed.
+    /// Once the values are returned the ticket is not polled again.
+    fn poll(&mut self, ticket: &Self::Ticket) -> Option<Vec<i32>>;
+
+    /// Blocks until the batch completes, and returns its values
+    fn wait(&mut self, ticket: Self::Ticket) -> Vec<i32>;
+}
+
+/// Evaluates every batch on the CPU as soon as it is submitted
+pub struct ImmediateEvaluator<H: Fn(&Board) -> i32> {
+    heuristic: H,
+}
+
+impl<H: Fn(&Board) -> i32> ImmediateEvaluator<H> {
+    pub fn new(heuristic: H) -> ImmediateEvaluator<H> {
+        ImmediateEvaluator { heuristic }
+    }
+}
+
+impl<H: Fn(&Board) -> i32> BatchEvaluator for ImmediateEvaluator<H> {
+    type Ticket = Vec<i32>;
+
+    fn submit(&mut self, boards: &[Board]) -> Vec<i32> {
+        boards.iter().map(|board| (self.heuristic)(board)).collect()
+    }
+
+    fn poll(&mut self, ticket: &Vec<i32>) -> Option<Vec<i32>> {
+        Some(ticket.clone())
+    }
+
+    fn wait(&mut self, ticket: Vec<i32>) -> Vec<i32> {
+        ticket
+    }
+}
+
+/// Looks up every batch in the pattern databases as soon as it is submitted, prefetching the
+/// entries of the whole batch before reading them
+impl BatchEvaluator for DisjointPatternDatabases {
+    type Ticket = Vec<i32>;
+
+    fn submit(&mut self, boards: &[Board]) -> Vec<i32> {
+        self.h_batch(boards)
+    }
+
+    fn poll(&mut self, ticket: &Vec<i32>) -> Option<Vec<i32>> {
+        Some(ticket.clone())
+    }
+
+    fn wait(&mut self, ticket: Vec<i32>) -> Vec<i32> {
+        ticket
+    }
+}
+
+struct Node {
+    board: Board,
+    parent: Option<usize>,
+    g: u32,
+}
+
+/// A* search whose heuristic values come from the evaluator, in batches of up to `batch_size`
+/// generated nodes. Nodes only join the open list once their batch completes, so they can be
+/// expanded out of order; a node reached again with a lower cost is re-expanded, and the goal is
+/// only accepted once no batches are outstanding. With an admissible heuristic the plan is optimal.
+pub fn batched_a_star<E: BatchEvaluator>(board: Board, evaluator: &mut E, batch_size: usize) -> Option<Plan> {
+    assert!(batch_size > 0, "Batches need to contain at least one board.");
+    BatchedSearch::new(evaluator, batch_size).run(board)
+}
+
+struct BatchedSearch<'a, E: BatchEvaluator> {
+    evaluator: &'a mut E,
+    batch_size: usize,
+    nodes: Vec<Node>,
+    best_g: HashMap<Board, u32>,
+    open: BinaryHeap<Reverse<(u32, Reverse<u32>, usize)>>,
+    pending: Vec<usize>,
+    outstanding: VecDeque<(E::Ticket, Vec<usize>)>,
+    statistics: Statistics,
+}
+
+impl<'a, E: BatchEvaluator> BatchedSearch<'a, E> {
+    fn new(evaluator: &'a mut E, batch_size: usize) -> BatchedSearch<'a, E> {
+        BatchedSearch {
+            evaluator,
+            batch_size,
+            nodes: Vec::new(),
+            best_g: HashMap::new(),
+            open: BinaryHeap::new(),
+            pending: Vec::new(),
+            outstanding: VecDeque::new(),
+            statistics: Statistics { created: 0, queued: 0, purged: 0, vetoed: 0, expanded: 0, seen: 0, memory: 0, duration: Default::default(), branching_factor: None },
+        }
+    }
+
+    fn run(mut self, board: Board) -> Option<Plan> {
+        let start_time = Instant::now();
+        self.generate(board, None, 0);
+        self.submit();
+
+        loop {
+            self.collect_completed();
+
+            let Reverse((f, Reverse(g), index)) = match self.open.pop() {
+                Some(entry) => entry,
+                None if self.has_unevaluated() => {
+                    self.collect_oldest();
+                    continue;
+                }
+                None => break,
+            };
+
+            let board = self.nodes[index].board;
+            //skip nodes that were reached again with a lower cost
+            if g > self.best_g[&board] {
+                continue;
+            }
+
+            if board.is_goal() {
+                if self.has_unevaluated() {
+                    //a node still being evaluated might lead to a cheaper plan
+                    self.open.push(Reverse((f, Reverse(g), index)));
+                    while self.has_unevaluated() {
+                        self.collect_oldest();
+                    }
+                    continue;
+                }
+
+                self.statistics.set_seen(self.best_g.len());
+                self.statistics.memory = self.nodes.len() * size_of::<Node>() + self.best_g.len() * size_of::<(Board, u32)>();
+                let path = self.path(index);
+                self.statistics.branching_factor = effective_branching_factor(self.statistics.created as usize, path.len() - 1);
+                self.statistics.duration = start_time.elapsed();
+                return Some(Plan::from_search(path, self.statistics));
+            }
+
+            self.statistics.expanded += 1;
+            for successor in board.successors() {
+                self.generate(successor, Some(index), g + 1);
+            }
+            if self.pending.len() >= self.batch_size {
+                self.submit();
+            }
+        }
+
+        None
+    }
+
+    fn generate(&mut self, board: Board, parent: Option<usize>, g: u32) {
+        if self.best_g.get(&board).is_some_and(|best| *best <= g) {
+            return;
+        }
+
+        self.statistics.count_created(1);
+        self.best_g.insert(board, g);
+        self.pending.push(self.nodes.len());
+        self.nodes.push(Node { board, parent, g });
+    }
+
+    fn has_unevaluated(&self) -> bool {
+        !self.pending.is_empty() || !self.outstanding.is_empty()
+    }
+
+    fn submit(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let indices: Vec<usize> = self.pending.drain(..).collect();
+        let boards: Vec<Board> = indices.iter().map(|index| self.nodes[*index].board).collect();
+        let ticket = self.evaluator.submit(&boards);
+        self.outstanding.push_back((ticket, indices));
+    }
+
+    fn collect_completed(&mut self) {
+        let mut remaining = VecDeque::with_capacity(self.outstanding.len());
+        while let Some((ticket, indices)) = self.outstanding.pop_front() {
+            match self.evaluator.poll(&ticket) {
+                Some(values) => self.queue(&indices, &values),
+                None => remaining.push_back((ticket, indices)),
+            }
+        }
+
+        self.outstanding = remaining;
+    }
+
+    /// Blocks on the oldest batch, submitting the pending nodes first if nothing is outstanding
+    fn collect_oldest(&mut self) {
+        if self.outstanding.is_empty() {
+            self.submit();
+        }
+
+        if let Some((ticket, indices)) = self.outstanding.pop_front() {
+            let values = self.evaluator.wait(ticket);
+            self.queue(&indices, &values);
+        }
+    }
+
+    fn queue(&mut self, indices: &[usize], values: &[i32]) {
+        assert_eq!(indices.len(), values.len(), "The evaluator returned the wrong number of values.");
+
+        for (index, h) in indices.iter().zip(values.iter()) {
+            let g = self.nodes[*index].g;
+            self.statistics.count_queued(1);
+            self.open.push(Reverse((g + (*h).max(0) as u32, Reverse(g), *index)));
+        }
+    }
+
+    fn path(&self, mut index: usize) -> Vec<Board> {
+        let mut boards = vec![self.nodes[index].board];
+        while let Some(parent) = self.nodes[index].parent {
+            boards.push(self.nodes[parent].board);
+            index = parent;
+        }
+
+        boards.reverse();
+        boards
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pdb::Compression;
+
+    use super::*;
+
+    /// Completes every batch only after it has been polled a few times, like a slow device
+    struct DelayedEvaluator {
+        polls: HashMap<usize, u32>,
+        batches: Vec<Vec<i32>>,
+    }
+
+    impl BatchEvaluator for DelayedEvaluator {
+        type Ticket = usize;
+
+        fn submit(&mut self, boards: &[Board]) -> usize {
+            self.batches.push(boards.iter().map(|board| board.manhattan_dist()).collect());
+            self.batches.len() - 1
+        }
+
+        fn poll(&mut self, ticket: &usize) -> Option<Vec<i32>> {
+            let polls = self.polls.entry(*ticket).or_insert(0);
+            *polls += 1;
+            if *polls > 3 {
+                Some(self.batches[*ticket].clone())
+            } else {
+                None
+            }
+        }
+
+        fn wait(&mut self, ticket: usize) -> Vec<i32> {
+            self.batches[ticket].clone()
+        }
+    }
+
+    #[test]
+    fn test_immediate_matches_a_star() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let mut evaluator = ImmediateEvaluator::new(Board::manhattan_dist);
+
+        let plan = batched_a_star(board, &mut evaluator, 64).unwrap();
+        assert_eq!(plan.steps(), 31);
+        assert_eq!(*plan.start(), board);
+    }
+
+    #[test]
+    fn test_delayed_batches_stay_optimal() {
+        for tiles in [[8, 6, 7, 2, 5, 4, 3, 0, 1], [6, 4, 7, 8, 5, 0, 3, 2, 1], [1, 2, 3, 0, 4, 6, 7, 5, 8]].iter() {
+            let board = Board::new(*tiles);
+            let mut evaluator = DelayedEvaluator { polls: HashMap::new(), batches: Vec::new() };
+
+            let plan = batched_a_star(board, &mut evaluator, 16).unwrap();
+            assert_eq!(plan.steps(), crate::a_star_search(board).unwrap().steps());
+            assert!(Plan::new(plan.boards().to_vec()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_pattern_databases() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let mut databases = DisjointPatternDatabases::build(&[vec![1, 2, 3, 4], vec![5, 6, 7, 8]], Compression::None);
+
+        let plan = batched_a_star(board, &mut databases, 64).unwrap();
+        assert_eq!(plan.steps(), 31);
+        assert!(Plan::new(plan.boards().to_vec()).is_ok());
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let board = Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]);
+        let mut evaluator = ImmediateEvaluator::new(Board::manhattan_dist);
+        assert!(batched_a_star(board, &mut evaluator, 1024).is_none());
+    }
+}
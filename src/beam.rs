@@ -0,0 +1,181 @@
+//! Beam search: breadth first search that only keeps the most promising boards of every layer,
+//! which bounds its memory but gives up on finding the shortest plans, or any plan at all.
+
+use std::collections::HashSet;
+use std::mem::size_of;
+
+use crate::board::Board;
+use crate::clock::Instant;
+use crate::plan::Plan;
+use crate::search::{effective_branching_factor, Statistics};
+
+/// How many boards a beam search keeps in every layer
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BeamWidth {
+    /// This many boards
+    Fixed(usize),
+    /// As many boards as fit in this many bytes. The width of every layer is the memory left,
+    /// shared out over the moves the heuristic expects are still needed, and the size of a board is
+    /// measured again after every layer, so that later layers make up for a wrong estimate.
+    Memory(usize),
+}
+
+impl BeamWidth {
+    /// The budget in megabytes, as users think of it
+    pub fn megabytes(megabytes: usize) -> BeamWidth {
+        BeamWidth::Memory(megabytes << 20)
+    }
+}
+
+/// What a beam search found
+#[derive(Debug, Clone)]
+pub struct BeamResult<const R: usize = 3, const C: usize = R> {
+    pub plan: Option<Plan<R, C>>,
+    pub statistics: Statistics,
+    /// The number of boards kept in each layer after the initial board
+    pub widths: Vec<usize>,
+    /// Whether the search stopped because it ran out of memory rather than boards
+    pub out_of_memory: bool,
+}
+
+struct Node<const R: usize, const C: usize> {
+    board: Board<R, C>,
+    parent: usize,
+}
+
+/// Beam search ordered by the heuristic, which keeps the boards of every layer with the lowest
+/// values. Every board of a layer is as many moves away from the start, so this is also the order
+/// of A*.
+pub fn beam_search<const R: usize, const C: usize, H>(board: Board<R, C>, heuristic: H, width: BeamWidth) -> BeamResult<R, C>
+    where H: Fn(&Board<R, C>) -> i32
+{
+    let start = Instant::now();
+    let mut statistics = Statistics::initial();
+    let mut nodes = vec![Node { board, parent: 0 }];
+    let mut seen = HashSet::new();
+    seen.insert(board);
+
+    //a first guess of the bytes per board, the node and the entry in the seen boards
+    let mut per_board = size_of::<Node<R, C>>() + size_of::<Board<R, C>>() + 1;
+    let mut layer = 0..1;
+    let mut best_h = heuristic(&board);
+    let mut widths = Vec::new();
+    let mut goal = board.is_goal().then_some(0);
+    let mut out_of_memory = false;
+
+    while goal.is_none() && !layer.is_empty() {
+        let beam = match width {
+            BeamWidth::Fixed(width) => width,
+            BeamWidth::Memory(budget) => {
+                let left = budget.saturating_sub(memory(&nodes, &seen));
+                if left < per_board {
+                    out_of_memory = true;
+                    break;
+                }
+                (left / per_board / best_h.max(1) as usize).max(1)
+            }
+        };
+
+        let mut candidates = Vec::new();
+        for index in layer.clone() {
+            statistics.expanded += 1;
+            for successor in nodes[index].board.successors() {
+                statistics.count_created(1);
+                if seen.insert(successor) {
+                    candidates.push((heuristic(&successor), successor, index));
+                }
+            }
+        }
+
+        //the stable sort keeps the order of generation between boards of the same value
+        candidates.sort_by_key(|(h, _, _)| *h);
+        for (_, board, _) in candidates.iter().skip(beam) {
+            seen.remove(board);
+        }
+        candidates.truncate(beam);
+
+        let next = nodes.len()..nodes.len() + candidates.len();
+        for (h, board, parent) in candidates {
+            if board.is_goal() && goal.is_none() {
+                goal = Some(nodes.len());
+            }
+            best_h = best_h.min(h);
+            nodes.push(Node { board, parent });
+        }
+
+        statistics.count_queued(next.len() as i32);
+        widths.push(next.len());
+        per_board = memory(&nodes, &seen) / nodes.len();
+        layer = next;
+    }
+
+    statistics.set_seen(seen.len());
+    statistics.memory = memory(&nodes, &seen);
+    statistics.duration = start.elapsed();
+
+    let plan = goal.map(|mut index| {
+        let mut boards = vec![nodes[index].board];
+        while index != 0 {
+            index = nodes[index].parent;
+            boards.push(nodes[index].board);
+        }
+        boards.reverse();
+
+        statistics.branching_factor = effective_branching_factor(statistics.created as usize, boards.len() - 1);
+        Plan::from_search(boards, statistics.clone())
+    });
+
+    BeamResult { plan, statistics, widths, out_of_memory }
+}
+
+//the storage held by the nodes and the seen boards, each entry of which also takes a control byte
+fn memory<const R: usize, const C: usize>(nodes: &Vec<Node<R, C>>, seen: &HashSet<Board<R, C>>) -> usize {
+    nodes.capacity() * size_of::<Node<R, C>>() + seen.capacity() * (size_of::<Board<R, C>>() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::GOAL;
+    use crate::manhattan_heuristic;
+
+    use super::*;
+
+    #[test]
+    fn test_fixed_width() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let result = beam_search(board, manhattan_heuristic, BeamWidth::Fixed(100));
+
+        let plan = result.plan.unwrap();
+        assert!(plan.end().is_goal());
+        assert!(plan.steps() >= 31);
+        assert!(result.widths.iter().all(|width| *width <= 100));
+        assert_eq!(result.widths.len(), plan.steps());
+
+        assert_eq!(beam_search(GOAL, manhattan_heuristic, BeamWidth::Fixed(1)).plan.unwrap().steps(), 0);
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        let board: Board<4> = "5 1 3 4 / 9 2 7 8 / 13 6 10 12 / 14 0 11 15".parse().unwrap();
+        let budget = 1 << 20;
+        let result = beam_search(board, manhattan_heuristic, BeamWidth::Memory(budget));
+
+        let plan = result.plan.unwrap();
+        assert!(plan.end().is_goal());
+        assert!(!result.out_of_memory);
+        assert!(result.statistics.memory <= budget);
+        //the width follows the memory left and the moves expected, rather than staying the same
+        assert_eq!(result.widths.len(), plan.steps());
+        assert!(result.widths.windows(2).any(|pair| pair[0] != pair[1]));
+        assert_eq!(BeamWidth::megabytes(1), BeamWidth::Memory(budget));
+    }
+
+    #[test]
+    fn test_out_of_memory() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let result = beam_search(board, manhattan_heuristic, BeamWidth::Memory(1000));
+
+        assert!(result.plan.is_none());
+        assert!(result.out_of_memory);
+    }
+}
@@ -1,94 +1,607 @@
-use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
 use std::fmt::{Display, Formatter, Result};
+use std::str::FromStr;
 
-pub const GOAL: Board = Board { tiles: [1, 2, 3, 4, 5, 6, 7, 8, 0], zero: 0 };
+use rand::seq::SliceRandom;
+use rand::Rng;
 
-lazy_static! {
-    static ref GOAL_MAP: HashMap<i8, usize> = {
-        let mut goal_positions = HashMap::with_capacity(GOAL.tiles.len());
-        for (index, tile) in GOAL.tiles.iter().enumerate() {
-            goal_positions.insert(*tile, index);
+use display::{BoardDisplay, DisplayOptions};
+
+/// Alternative storage of the tiles, kept interchangeable with `Board`.
+/// Internal, public only so that it can be benchmarked.
+#[doc(hidden)]
+pub mod repr;
+pub mod costed;
+pub mod torus;
+pub mod display;
+
+/// The number of a tile, 0 standing for the blank. Boards have at most `Tile::MAX` positions, up
+/// to 15x15, so that `HIDDEN` is never the number of a tile.
+pub type Tile = u8;
+
+/// The number standing for the tiles that are hidden, such as those the abstract boards do not
+/// tell apart, which the heuristics leave out like the blank
+pub const HIDDEN: Tile = Tile::MAX;
+
+pub const GOAL: Board = Board::goal();
+
+/// The goal used by many textbooks, with the blank in the top left corner
+pub const BLANK_FIRST_GOAL: Board = Board { tiles: [[0, 1, 2], [3, 4, 5], [6, 7, 8]], zero: 0 };
+
+/// Reasons why a sequence of tiles is not a valid board
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BoardError {
+    /// The board needs exactly one entry per position
+    WrongSize { expected: usize, found: usize },
+    /// The tile is outside the range from 0 to the number of positions minus one
+    InvalidTile(Tile),
+    /// The tile appears more than once
+    DuplicateTile(Tile),
+}
+
+impl Display for BoardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            BoardError::WrongSize { expected, found } => write!(f, "Expecting {} tiles, found {}.", expected, found),
+            BoardError::InvalidTile(tile) => write!(f, "Invalid tile {}, expecting tiles from 0 up to the number of positions minus one.", tile),
+            BoardError::DuplicateTile(tile) => write!(f, "Tile {} appears more than once.", tile),
+        }
+    }
+}
+
+impl Error for BoardError {}
+
+/// Reasons why a text is not a valid board
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseBoardError {
+    /// The text between the separators is not a number
+    InvalidNumber(String),
+    /// The row, counting from 0, does not have one tile per column
+    WrongRowSize { row: usize, expected: usize, found: usize },
+    /// The numbers do not make up a valid board
+    Board(BoardError),
+}
+
+impl Display for ParseBoardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            ParseBoardError::InvalidNumber(text) => write!(f, "Invalid tile: {}", text),
+            ParseBoardError::WrongRowSize { row, expected, found } => write!(f, "Expecting {} tiles in row {}, found {}.", expected, row, found),
+            ParseBoardError::Board(error) => write!(f, "{}", error),
         }
+    }
+}
+
+impl Error for ParseBoardError {}
+
+impl From<BoardError> for ParseBoardError {
+    fn from(error: BoardError) -> ParseBoardError {
+        ParseBoardError::Board(error)
+    }
+}
+
+/// Reasons why a sequence of moves cannot be replayed on a board
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MoveError {
+    /// The move at this index of the sequence has no tile to slide, as the blank is on that edge
+    Blocked { index: usize, next_move: Move },
+}
 
-        goal_positions
-    };
+impl Display for MoveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            MoveError::Blocked { index, next_move } => write!(f, "Move {} ({:?}) has no tile to slide into the blank.", index, next_move),
+        }
+    }
 }
 
+impl Error for MoveError {}
+
+/// The direction in which a tile slides into the blank space
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Move {
+    /// The move that undoes this one
+    pub fn inverse(self) -> Move {
+        match self {
+            Move::Up => Move::Down,
+            Move::Down => Move::Up,
+            Move::Left => Move::Right,
+            Move::Right => Move::Left,
+        }
+    }
+}
+
+/// The order in which the moves are tried when generating the successors of a board.
+/// Searches break ties by generation order, so the order changes the number of nodes they visit.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MoveOrder {
+    /// Left, up, down, right, as generated by `Board::successors`
+    Default,
+    /// Right, down, up, left
+    Reversed,
+    /// The same order for every board
+    Fixed([Move; 4]),
+    /// A pseudo-random order for every board, which is always the same for the same seed and board
+    Random(u64),
+}
+
+const DEFAULT_ORDER: [Move; 4] = [Move::Left, Move::Up, Move::Down, Move::Right];
+
+impl MoveOrder {
+    /// The moves in the order in which they are tried on the board
+    pub fn moves<const R: usize, const C: usize>(&self, board: &Board<R, C>) -> [Move; 4] {
+        match self {
+            MoveOrder::Default => DEFAULT_ORDER,
+            MoveOrder::Reversed => [Move::Right, Move::Down, Move::Up, Move::Left],
+            MoveOrder::Fixed(moves) => *moves,
+            MoveOrder::Random(seed) => {
+                let packed = board.tiles.as_flattened().iter().fold(0u64, |packed, tile| (packed << 4) | *tile as u64);
+                let mut random = split_mix(seed ^ packed);
+
+                //Fisher-Yates shuffle, drawing each index from the mixed bits
+                let mut moves = DEFAULT_ORDER;
+                for i in (1..moves.len()).rev() {
+                    moves.swap(i, (random % (i as u64 + 1)) as usize);
+                    random /= i as u64 + 1;
+                }
+                moves
+            }
+        }
+    }
+}
+
+fn split_mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+
+/// A sliding puzzle of R rows by C columns, square unless the columns are given, and the
+/// 8-puzzle by default. The tiles are numbered from 1 to R×C-1, with 0 standing for the blank.
 #[derive(Debug, Copy, Clone, Hash, Eq)]
-pub struct Board {
-    tiles: [i8; 9],
+pub struct Board<const R: usize = 3, const C: usize = R> {
+    tiles: [[Tile; C]; R],
     zero: usize,
 }
 
 impl Board {
-    pub fn new(tiles: [i8; 9]) -> Board {
-        Board { tiles, zero: find_zero(tiles) }
+    /// The number of arrangements of the tiles of the 8-puzzle (9!), solvable or not, each of which
+    /// has its own rank below this
+    pub const RANKS: u32 = 362_880;
+
+    /// The board with the tiles in reading order.
+    /// Panics if they are not a valid board, which `try_new` reports instead.
+    pub fn new(tiles: [Tile; 9]) -> Board {
+        Board::try_new(tiles).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    pub fn is_goal(&self) -> bool {
-        *self == GOAL
+    /// The board with the tiles in reading order, if every tile from 0 to 8 appears exactly once
+    pub fn try_new(tiles: [Tile; 9]) -> std::result::Result<Board, BoardError> {
+        Board::try_from(&tiles[..])
+    }
+
+    /// The index of the arrangement of the tiles among all of them in lexicographic order, from 0 to
+    /// `RANKS - 1`, computed from its Lehmer code: each tile counts the smaller tiles after it,
+    /// weighted by the factorial of the positions left. Tables of every board can be indexed by it.
+    pub fn rank(&self) -> u32 {
+        let tiles = self.tiles.as_flattened();
+        let mut rank = 0;
+        for (index, tile) in tiles.iter().enumerate() {
+            let smaller_after = tiles[index + 1..].iter().filter(|other| *other < tile).count() as u32;
+            rank = rank * (tiles.len() - index) as u32 + smaller_after;
+        }
+
+        rank
     }
 
-    fn swap(&self, pos1: usize, pos2: usize) -> Board {
-        assert!(pos1 < self.tiles.len());
-        assert!(pos2 < self.tiles.len());
+    /// The board with the given rank, the inverse of `rank`
+    pub fn unrank(mut rank: u32) -> Board {
+        assert!(rank < Board::RANKS, "Invalid rank {}, expecting less than {}.", rank, Board::RANKS);
 
-        if pos1 == pos2 {
-            Board::new(self.tiles)
+        let mut remaining: Vec<Tile> = (0..9).collect();
+        let mut tiles = [0; 9];
+        for (index, tile) in tiles.iter_mut().enumerate() {
+            let block: u32 = (1..9 - index as u32).product();
+            *tile = remaining.remove((rank / block) as usize);
+            rank %= block;
+        }
+
+        Board::new(tiles)
+    }
+}
+
+impl<const R: usize, const C: usize> Board<R, C> {
+    /// The goal configuration of this size, with the tiles in order and the blank last
+    pub const fn goal() -> Board<R, C> {
+        //evaluated when the size is known, so that a board too large does not compile
+        const { assert!(R * C <= Tile::MAX as usize, "The boards can have at most 255 positions.") };
+        let mut tiles = [[0; C]; R];
+        let mut index = 0;
+        while index < R * C - 1 {
+            tiles[index / C][index % C] = (index + 1) as Tile;
+            index += 1;
+        }
+
+        Board { tiles, zero: R * C - 1 }
+    }
+
+    /// A solvable board picked uniformly at random.
+    /// The tiles are shuffled, and an unsolvable arrangement has its first two numbered tiles swapped, which
+    /// flips the parity and pairs every unsolvable board with exactly one solvable board.
+    /// On boards of a single row or column only the blank can be placed at random, since the tiles
+    /// keep the order of the goal.
+    pub fn random<G: Rng + ?Sized>(rng: &mut G) -> Board<R, C> {
+        let mut tiles = Board::<R, C>::goal().tiles;
+        let values = tiles.as_flattened_mut();
+        if R == 1 || C == 1 {
+            let blank = rng.gen_range(0..values.len());
+            values[blank..].rotate_right(1);
+            return Board::from_slice(values);
+        }
+
+        values.shuffle(rng);
+
+        let board = Board::from_slice(values);
+        if board.is_solvable() {
+            return board;
+        }
+
+        let mut positions = (0..values.len()).filter(|pos| values[*pos] != 0);
+        let first = positions.next().unwrap();
+        let second = positions.next().unwrap();
+        values.swap(first, second);
+        Board::from_slice(values)
+    }
+
+    /// The board reached from the goal with a random walk of this many moves, never undoing the
+    /// previous move. The optimal plan is at most `steps` long, and usually close to it for short
+    /// walks, which makes this a rough way to pick the difficulty of a board.
+    pub fn scrambled<G: Rng + ?Sized>(steps: usize, rng: &mut G) -> Board<R, C> {
+        let mut board = Board::goal();
+        let mut previous: Option<Move> = None;
+        for _ in 0..steps {
+            let successors = board.successors_with_moves();
+            let forward: Vec<(Move, Board<R, C>)> = successors.iter().copied()
+                .filter(|(next_move, _)| previous != Some(next_move.inverse()))
+                .collect();
+            //a board with a single row or column can leave undoing the move as the only one
+            let (next_move, next) = match forward.choose(rng) {
+                Some(successor) => *successor,
+                None => *successors.choose(rng).unwrap(),
+            };
+            board = next;
+            previous = Some(next_move);
+        }
+
+        board
+    }
+
+    //the values are expected to be a valid board of this size
+    fn from_slice(values: &[Tile]) -> Board<R, C> {
+        let mut tiles = [[0; C]; R];
+        tiles.as_flattened_mut().copy_from_slice(values);
+
+        Board { tiles, zero: find_zero(values) }
+    }
+
+    //the rows are expected to hold the blank, but may hide tiles or repeat them, as abstractions do
+    pub(crate) fn from_rows(rows: [[Tile; C]; R]) -> Board<R, C> {
+        Board { tiles: rows, zero: find_zero(rows.as_flattened()) }
+    }
+
+    /// The number of rows
+    pub fn height(&self) -> usize {
+        R
+    }
+
+    /// The number of columns
+    pub fn width(&self) -> usize {
+        C
+    }
+
+    /// The tiles row by row, with 0 standing for the blank
+    pub fn tiles(&self) -> &[Tile] {
+        self.tiles.as_flattened()
+    }
+
+    /// Iterates over the rows from top to bottom, each with its tiles from left to right
+    pub fn rows(&self) -> impl Iterator<Item = &[Tile]> + '_ {
+        self.tiles.iter().map(|row| row.as_slice())
+    }
+
+    /// Displays the board with the given options rather than those of `Display`
+    pub fn display_with(&self, options: DisplayOptions) -> BoardDisplay<'_, R, C> {
+        BoardDisplay { board: self, options }
+    }
+
+    /// The tile at the given row and column, if they are within the board
+    pub fn get(&self, row: usize, col: usize) -> Option<Tile> {
+        if row < R && col < C {
+            Some(self.tiles[row][col])
         } else {
-            let mut swapped = self.tiles;
-            let temp = swapped[pos1];
-            swapped[pos1] = swapped[pos2];
-            swapped[pos2] = temp;
+            None
+        }
+    }
+
+    /// The row and column of the given tile, if it is on the board
+    pub fn position_of(&self, tile: Tile) -> Option<(usize, usize)> {
+        self.tiles().iter().copied()
+            .position(|t| t == tile)
+            .map(|index| (index / C, index % C))
+    }
 
-            Board::new(swapped)
+    /// Iterates over the tiles of a row, from left to right, if the row is within the board
+    pub fn row(&self, row: usize) -> Option<impl Iterator<Item = Tile> + '_> {
+        self.tiles.get(row).map(|tiles| tiles.iter().copied())
+    }
+
+    /// Iterates over the tiles of a column, from top to bottom, if the column is within the board
+    pub fn column(&self, col: usize) -> Option<impl Iterator<Item = Tile> + '_> {
+        if col >= C {
+            return None;
         }
+
+        Some(self.tiles.iter().map(move |row| row[col]))
+    }
+
+    pub fn is_goal(&self) -> bool {
+        *self == Board::goal()
+    }
+
+    /// Whether the goal can be reached from this board.
+    /// Every move keeps the parity of the inversions between the tiles (ignoring the blank), so only
+    /// boards with an even number of inversions, like the goal, can be solved. On boards of a single
+    /// row or column the tiles can never pass each other, so they have to be in order already.
+    pub fn is_solvable(&self) -> bool {
+        self.is_solvable_to(&Board::goal())
+    }
+
+    /// Whether the given goal can be reached from this board, which is when both have the same
+    /// parity of inversions. `GOAL` and `BLANK_FIRST_GOAL` both have none, so any board can either
+    /// reach both or neither of them.
+    /// On boards of even width a vertical move flips the parity of the inversions, so the row of
+    /// the blank is added to them.
+    /// On boards of a single row or column the blank only slides along the tiles, which keep their
+    /// order, so the goal is reached exactly when both have the same tiles in the same order.
+    pub fn is_solvable_to(&self, goal: &Board<R, C>) -> bool {
+        if R == 1 || C == 1 {
+            let numbered = |board: &Board<R, C>| board.tiles.as_flattened().iter().copied().filter(|tile| *tile != 0).collect::<Vec<Tile>>();
+            return numbered(self) == numbered(goal);
+        }
+
+        self.parity() == goal.parity()
+    }
+
+    /// The parity that every move keeps, 0 or 1: that of the inversions, plus the row of the blank
+    /// on boards of even width. Two boards of at least two rows and two columns can reach each other
+    /// exactly when their parities match.
+    pub fn parity(&self) -> usize {
+        let blank_row = if C % 2 == 1 { 0 } else { self.zero / C };
+        (self.inversions() + blank_row) % 2
+    }
+
+    /// The number of pairs of tiles, ignoring the blank, where the larger one comes first when
+    /// reading the board row by row
+    pub fn inversions(&self) -> usize {
+        let tiles = self.tiles.as_flattened();
+        let mut inversions = 0;
+        for (index, tile) in tiles.iter().enumerate() {
+            inversions += tiles[index + 1..].iter()
+                .filter(|other| **other != 0 && *tile != 0 && *other < tile)
+                .count();
+        }
+
+        inversions
+    }
+
+    /// Whether the blank is in the position it occupies in the goal
+    pub fn is_blank_home(&self) -> bool {
+        self.zero == R * C - 1
+    }
+
+    fn swap(&self, pos1: usize, pos2: usize) -> Board<R, C> {
+        assert!(pos1 < R * C);
+        assert!(pos2 < R * C);
+
+        let mut swapped = self.tiles;
+        swapped.as_flattened_mut().swap(pos1, pos2);
+
+        Board::from_rows(swapped)
     }
 
     /// Returns the successors of the current board configuration.
-    pub fn successors(&self) -> Vec<Board> {
+    pub fn successors(&self) -> Vec<Board<R, C>> {
         let mut successors = Vec::with_capacity(self.successor_count());
+        let col = self.zero % C;
 
         //left
-        if self.zero % 3 != 2 {
+        if col != C - 1 {
             successors.push(self.swap(self.zero, self.zero + 1));
         }
 
         //up
-        if self.zero <= 5 {
-            successors.push(self.swap(self.zero, self.zero + 3));
+        if self.zero < C * (R - 1) {
+            successors.push(self.swap(self.zero, self.zero + C));
         }
 
         //down
-        if self.zero >= 3 {
-            successors.push(self.swap(self.zero, self.zero - 3));
+        if self.zero >= C {
+            successors.push(self.swap(self.zero, self.zero - C));
         }
 
         //right
-        if self.zero % 3 != 0 {
+        if col != 0 {
             successors.push(self.swap(self.zero, self.zero - 1));
         }
 
         successors
     }
 
+    /// The successors generated in the given order of moves
+    pub fn successors_in(&self, order: MoveOrder) -> Vec<Board<R, C>> {
+        if order == MoveOrder::Default {
+            return self.successors();
+        }
+
+        order.moves(self).iter()
+            .filter_map(|next_move| self.moved_tile(*next_move))
+            .map(|pos| self.swap(self.zero, pos))
+            .collect()
+    }
+
+    /// The successors together with the move that reaches each one, in the order of `successors`
+    pub fn successors_with_moves(&self) -> Vec<(Move, Board<R, C>)> {
+        self.successors_with_moves_in(MoveOrder::Default)
+    }
+
+    /// The successors with their moves, generated in the given order of moves
+    pub fn successors_with_moves_in(&self, order: MoveOrder) -> Vec<(Move, Board<R, C>)> {
+        order.moves(self).iter()
+            .filter_map(|next_move| self.apply_move(*next_move).map(|successor| (*next_move, successor)))
+            .collect()
+    }
+
+    /// The board after the move, if there is a tile to slide in that direction
+    pub fn apply_move(&self, next_move: Move) -> Option<Board<R, C>> {
+        self.moved_tile(next_move).map(|pos| self.swap(self.zero, pos))
+    }
+
+    /// Applies the move to this board rather than to a copy, unless there is no tile to slide, in
+    /// which case the board is left as it is and false is returned
+    pub fn apply_move_in_place(&mut self, next_move: Move) -> bool {
+        match self.moved_tile(next_move) {
+            Some(pos) => {
+                self.tiles.as_flattened_mut().swap(self.zero, pos);
+                self.zero = pos;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The moves that have a tile to slide, in the default order
+    pub fn possible_moves(&self) -> impl Iterator<Item = Move> + '_ {
+        DEFAULT_ORDER.iter().copied().filter(move |next_move| self.moved_tile(*next_move).is_some())
+    }
+
+    /// The board after all the moves, one after the other, such as those of a plan
+    pub fn apply_moves(&self, moves: &[Move]) -> std::result::Result<Board<R, C>, MoveError> {
+        moves.iter().enumerate().try_fold(*self, |board, (index, next_move)| {
+            board.apply_move(*next_move).ok_or(MoveError::Blocked { index, next_move: *next_move })
+        })
+    }
+
+    /// The position of the tile that would slide into the blank with the move, if there is one
+    fn moved_tile(&self, next_move: Move) -> Option<usize> {
+        let col = self.zero % C;
+        match next_move {
+            Move::Left if col != C - 1 => Some(self.zero + 1),
+            Move::Up if self.zero < C * (R - 1) => Some(self.zero + C),
+            Move::Down if self.zero >= C => Some(self.zero - C),
+            Move::Right if col != 0 => Some(self.zero - 1),
+            _ => None,
+        }
+    }
+
+    /// Returns the move that turns this board into the next one, if they are a single move apart
+    pub fn move_to(&self, next: &Board<R, C>) -> Option<Move> {
+        if !self.successors().contains(next) {
+            return None;
+        }
+
+        let offset = next.zero as isize - self.zero as isize;
+        if offset == C as isize {
+            Some(Move::Up)
+        } else if offset == -(C as isize) {
+            Some(Move::Down)
+        } else if offset == 1 {
+            Some(Move::Left)
+        } else {
+            Some(Move::Right)
+        }
+    }
+
     /// Returns how many successors this board configuration should have
-    /// Position 4 has 4 places to move, odd positions have 3 places, and the rest have 2
+    /// The blank has 4 places to move, minus one for every edge of the board it is on
     fn successor_count(&self) -> usize {
-        match self.zero {
-            4 => 4,
-            n if (n % 2 == 1) => 3,
-            _ => 2
-        }
+        let (row, col) = (self.zero / C, self.zero % C);
+        let edges = [row == 0, row == R - 1, col == 0, col == C - 1];
+
+        4 - edges.iter().filter(|edge| **edge).count()
     }
 
-    /// Calculates the manhattan distance from GOAL
+    /// Calculates the manhattan distance from the goal
     pub fn manhattan_dist(&self) -> i32 {
         let mut distance = 0;
-        for (index, tile) in self.tiles.iter().enumerate() {
-            if *tile > 0 {
-                let goal_tile_pos = GOAL_MAP.get(&tile).unwrap();
-                distance += manhattan_dist_positions(index, *goal_tile_pos);
+        for (index, tile) in self.tiles().iter().copied().enumerate() {
+            if is_numbered(tile) {
+                //in the goal, tile t is in position t - 1
+                distance += position_dist::<R, C>(index, tile as usize - 1);
+            }
+        }
+
+        distance
+    }
+
+    /// The manhattan distance of this board, from that of the board it was reached from with a
+    /// single move, by only adjusting the distance of the tile that moved instead of adding up the
+    /// distances of every tile
+    pub fn manhattan_dist_from(&self, previous: &Board<R, C>, previous_dist: i32) -> i32 {
+        //the tile slid from the new position of the blank into its old position
+        let tile = self.tiles.as_flattened()[previous.zero];
+        manhattan_dist_after_move::<R, C>(tile, self.zero, previous.zero, previous_dist)
+    }
+
+    /// The board mirrored along its main diagonal, with the tiles renumbered so that the goal mirrors
+    /// onto itself, which keeps the distance to the goal (and the manhattan distance) the same.
+    /// Only square boards whose goal has the blank on the diagonal have such a mirror image.
+    pub fn reflected_to(&self, goal: &Board<R, C>) -> Option<Board<R, C>> {
+        if R != C || goal.zero / C != goal.zero % C {
+            return None;
+        }
+
+        let transpose = |pos: usize| (pos % C) * C + pos / C;
+        let goal_positions = goal.positions();
+        let goal_tiles = goal.tiles.as_flattened();
+        let mut tiles = [[0; C]; R];
+        for (pos, tile) in self.tiles().iter().copied().enumerate() {
+            tiles.as_flattened_mut()[transpose(pos)] = goal_tiles[transpose(goal_positions[tile as usize])];
+        }
+
+        Some(Board::from_rows(tiles))
+    }
+
+    /// The lexicographically smallest of the board and its mirror image towards the goal, which is
+    /// the same for both, so searches can treat them as a single board
+    pub fn canonical_to(&self, goal: &Board<R, C>) -> Board<R, C> {
+        match self.reflected_to(goal) {
+            Some(reflected) if reflected.tiles < self.tiles => reflected,
+            _ => *self,
+        }
+    }
+
+    /// The canonical form of the board towards the default goal
+    pub fn canonical(&self) -> Board<R, C> {
+        self.canonical_to(&Board::goal())
+    }
+
+    /// Calculates the manhattan distance from an arbitrary goal configuration
+    pub fn manhattan_dist_to(&self, goal: &Board<R, C>) -> i32 {
+        let goal_positions = goal.positions();
+        let mut distance = 0;
+        for (index, tile) in self.tiles().iter().copied().enumerate() {
+            if is_numbered(tile) {
+                distance += position_dist::<R, C>(index, goal_positions[tile as usize]);
             }
         }
 
@@ -97,64 +610,249 @@ impl Board {
 
     pub fn displaced_tiles(&self) -> i32 {
         let mut displaced = 0;
-        for (index, tile) in self.tiles.iter().enumerate() {
-            if *tile > 0 {
-                let goal_tile_pos = GOAL_MAP.get(&tile).unwrap();
-                if *goal_tile_pos != index {
-                    displaced += 1;
-                }
+        for (index, tile) in self.tiles().iter().copied().enumerate() {
+            if is_numbered(tile) && tile as usize != index + 1 {
+                displaced += 1;
+            }
+        }
+
+        displaced
+    }
+
+    /// Counts the tiles that are not in the position they occupy in the given goal configuration
+    pub fn displaced_tiles_to(&self, goal: &Board<R, C>) -> i32 {
+        let mut displaced = 0;
+        for (tile, goal_tile) in self.tiles().iter().copied().zip(goal.tiles().iter().copied()) {
+            if is_numbered(tile) && goal_tile != tile {
+                displaced += 1;
             }
         }
 
         displaced
     }
+
+    /// Returns the dual board, which treats the board as a permutation of the goal and inverts it.
+    /// Tile t in position p of the board becomes the tile found at goal position p, placed in the
+    /// goal position of t. When the blank is in its goal position, the dual board is exactly as far
+    /// from the goal as the original one, so any admissible heuristic can be evaluated on either.
+    pub fn inverse_permutation(&self) -> Board<R, C> {
+        let goal = Board::<R, C>::goal();
+        let goal_positions = goal.positions();
+        let goal_tiles = goal.tiles.as_flattened();
+        let mut inverse = [[0; C]; R];
+        for (index, tile) in self.tiles().iter().copied().enumerate() {
+            let position = goal_positions[tile as usize];
+            inverse[position / C][position % C] = goal_tiles[index];
+        }
+
+        Board::from_rows(inverse)
+    }
+
+    /// Returns the position of every tile, indexed by the tile number
+    pub(crate) fn positions(&self) -> Vec<usize> {
+        let mut positions = vec![0; R * C];
+        for (index, tile) in self.tiles().iter().copied().enumerate() {
+            positions[tile as usize] = index;
+        }
+
+        positions
+    }
 }
 
-impl PartialEq for Board {
+impl<const R: usize, const C: usize> PartialEq for Board<R, C> {
     fn eq(&self, other: &Self) -> bool {
         self.tiles == other.tiles
     }
 }
 
-impl Display for Board {
+impl<const R: usize, const C: usize> Display for Board<R, C> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        let mut board_str = String::new();
-        for (index, tile) in self.tiles.iter().enumerate() {
-            board_str.push_str(&tile.to_string());
-            if index % 3 == 2 {
-                board_str.push_str("\r\n");
-            } else {
-                board_str.push_str(" ");
+        write!(f, "{}", self.display_with(DisplayOptions::default()))
+    }
+}
+
+impl<const R: usize, const C: usize> TryFrom<[[Tile; C]; R]> for Board<R, C> {
+    type Error = BoardError;
+
+    fn try_from(rows: [[Tile; C]; R]) -> std::result::Result<Board<R, C>, BoardError> {
+        Board::try_from(rows.as_flattened())
+    }
+}
+
+impl<const R: usize, const C: usize> TryFrom<&[Tile]> for Board<R, C> {
+    type Error = BoardError;
+
+    fn try_from(values: &[Tile]) -> std::result::Result<Board<R, C>, BoardError> {
+        if values.len() != R * C {
+            return Err(BoardError::WrongSize { expected: R * C, found: values.len() });
+        }
+
+        let mut present = vec![false; R * C];
+        for tile in values.iter() {
+            if *tile as usize >= R * C {
+                return Err(BoardError::InvalidTile(*tile));
             }
-        };
+            if present[*tile as usize] {
+                return Err(BoardError::DuplicateTile(*tile));
+            }
+
+            present[*tile as usize] = true;
+        }
+
+        Ok(Board::from_slice(values))
+    }
+}
+
+impl<const R: usize, const C: usize> TryFrom<Vec<Tile>> for Board<R, C> {
+    type Error = BoardError;
 
-        write!(f, "{}", board_str)
+    fn try_from(values: Vec<Tile>) -> std::result::Result<Board<R, C>, BoardError> {
+        Board::try_from(values.as_slice())
     }
 }
 
-fn find_zero(tiles: [i8; 9]) -> usize {
+/// Writes the tiles row by row as a flat sequence, such as `[8,6,7,2,5,4,3,0,1]` in JSON
+#[cfg(feature = "serde")]
+impl<const R: usize, const C: usize> serde::Serialize for Board<R, C> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.tiles())
+    }
+}
+
+/// Reads the tiles written by `Serialize`, rejecting sequences that are not a valid board of this size
+#[cfg(feature = "serde")]
+impl<'de, const R: usize, const C: usize> serde::Deserialize<'de> for Board<R, C> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Board<R, C>, D::Error> {
+        let tiles = <Vec<Tile> as serde::Deserialize>::deserialize(deserializer)?;
+        Board::try_from(tiles).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Reads the tiles row by row, separated by whitespace or commas, such as "1 2 3 4 5 6 7 8 0" or
+/// "1,2,3,4,5,6,7,8,0". The rows can also be separated by slashes, as in "1 2 3 / 4 5 6 / 7 8 0",
+/// in which case every row needs one tile per column.
+impl<const R: usize, const C: usize> FromStr for Board<R, C> {
+    type Err = ParseBoardError;
+
+    fn from_str(text: &str) -> std::result::Result<Board<R, C>, ParseBoardError> {
+        let rows: Vec<&str> = text.split('/').collect();
+        let mut tiles = Vec::with_capacity(R * C);
+        for (index, row) in rows.iter().enumerate() {
+            let mut row_tiles = row.split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|tile| !tile.is_empty())
+                .map(|tile| tile.parse::<Tile>().map_err(|_| ParseBoardError::InvalidNumber(tile.to_string())))
+                .collect::<std::result::Result<Vec<Tile>, ParseBoardError>>()?;
+
+            if rows.len() > 1 && row_tiles.len() != C {
+                return Err(ParseBoardError::WrongRowSize { row: index, expected: C, found: row_tiles.len() });
+            }
+            tiles.append(&mut row_tiles);
+        }
+
+        Ok(Board::try_from(tiles)?)
+    }
+}
+
+impl<const R: usize, const C: usize> From<Board<R, C>> for [[Tile; C]; R] {
+    fn from(board: Board<R, C>) -> [[Tile; C]; R] {
+        board.tiles
+    }
+}
+
+impl From<Board> for [Tile; 9] {
+    fn from(board: Board) -> [Tile; 9] {
+        let mut tiles = [0; 9];
+        tiles.copy_from_slice(board.tiles.as_flattened());
+
+        tiles
+    }
+}
+
+impl<const R: usize, const C: usize> From<Board<R, C>> for Vec<Tile> {
+    fn from(board: Board<R, C>) -> Vec<Tile> {
+        board.tiles().to_vec()
+    }
+}
+
+/// Whether the tile is a numbered one, rather than the blank or a hidden tile
+pub(crate) fn is_numbered(tile: Tile) -> bool {
+    tile != 0 && tile != HIDDEN
+}
+
+fn find_zero(tiles: &[Tile]) -> usize {
     //we should always find 0, so panic if not
     tiles.iter().position(|&tile| tile == 0).unwrap()
 }
 
-fn manhattan_dist_positions(pos1: usize, pos2: usize) -> i32 {
+/// The manhattan distance between two positions of the 8-puzzle
+#[cfg(test)]
+pub(crate) fn manhattan_dist_positions(pos1: usize, pos2: usize) -> i32 {
+    DISTANCES_3X3[pos1][pos2]
+}
+
+/// The manhattan distance after a move slid the tile from `from` to `to`, from the distance before
+/// it. This is `Board::manhattan_dist_from` for searches that keep their boards in another form.
+pub(crate) fn manhattan_dist_after_move<const R: usize, const C: usize>(tile: Tile, from: usize, to: usize, previous_dist: i32) -> i32 {
+    if !is_numbered(tile) {
+        return previous_dist;
+    }
+
+    let home = tile as usize - 1;
+    previous_dist - position_dist::<R, C>(from, home) + position_dist::<R, C>(to, home)
+}
+
+//the distances between every two positions of the 8 and 15-puzzles, which the heuristics look up
+//rather than work out from the coordinates
+const DISTANCES_3X3: [[i32; 9]; 9] = distance_table(3);
+const DISTANCES_4X4: [[i32; 16]; 16] = distance_table(4);
+
+const fn distance_table<const N: usize>(width: usize) -> [[i32; N]; N] {
+    let mut table = [[0; N]; N];
+    let mut pos1 = 0;
+    while pos1 < N {
+        let mut pos2 = 0;
+        while pos2 < N {
+            table[pos1][pos2] = ((pos1 % width) as i32 - (pos2 % width) as i32).abs() + ((pos1 / width) as i32 - (pos2 / width) as i32).abs();
+            pos2 += 1;
+        }
+        pos1 += 1;
+    }
+
+    table
+}
+
+/// The manhattan distance between two positions of a board of the given size, looked up for the
+/// sizes that have a table
+pub(crate) fn position_dist<const R: usize, const C: usize>(pos1: usize, pos2: usize) -> i32 {
+    match (R, C) {
+        (3, 3) => DISTANCES_3X3[pos1][pos2],
+        (4, 4) => DISTANCES_4X4[pos1][pos2],
+        _ => grid_dist(pos1, pos2, C),
+    }
+}
+
+fn grid_dist(pos1: usize, pos2: usize, width: usize) -> i32 {
     if pos1 == pos2 {
         0
     } else {
-        let (x_pos1, y_pos1) = to_coordinates(pos1);
-        let (x_pos2, y_pos2) = to_coordinates(pos2);
+        let (x_pos1, y_pos1) = to_coordinates(pos1, width);
+        let (x_pos2, y_pos2) = to_coordinates(pos2, width);
 
         (x_pos2 - x_pos1).abs() + (y_pos2 - y_pos1).abs()
     }
 }
 
-fn to_coordinates(pos: usize) -> (i32, i32) {
-    ((pos % 3) as i32, (pos / 3) as i32)
+fn to_coordinates(pos: usize, width: usize) -> (i32, i32) {
+    ((pos % width) as i32, (pos / width) as i32)
 }
 
-
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
     use super::*;
 
     #[test]
@@ -162,6 +860,11 @@ mod tests {
         assert_eq!(Board::new([1, 2, 3, 4, 5, 6, 7, 8, 0]), GOAL);
     }
 
+    #[test]
+    fn test_goal_zero() {
+        assert_eq!(Board::new([1, 2, 3, 4, 5, 6, 7, 8, 0]).zero, GOAL.zero);
+    }
+
     #[test]
     fn test_inequality() {
         assert_ne!(Board::new([1, 0, 2, 3, 4, 5, 6, 7, 8]), GOAL);
@@ -177,11 +880,207 @@ mod tests {
         assert_eq!(Board::new([1, 0, 2, 3, 4, 5, 6, 7, 8]).is_goal(), false);
     }
 
+    #[test]
+    fn test_successors_in_order() {
+        let board = Board::new([1, 2, 3, 4, 0, 5, 6, 7, 8]);
+        let successors = board.successors();
+
+        assert_eq!(board.successors_in(MoveOrder::Default), successors);
+        assert_eq!(board.successors_in(MoveOrder::Fixed(DEFAULT_ORDER)), successors);
+        assert_eq!(board.successors_in(MoveOrder::Reversed), successors.iter().rev().copied().collect::<Vec<Board>>());
+
+        //moves that are not possible from the board are skipped
+        let corner = GOAL.successors_in(MoveOrder::Fixed([Move::Left, Move::Down, Move::Up, Move::Right]));
+        assert_eq!(corner, vec![Board::new([1, 2, 3, 4, 5, 0, 7, 8, 6]), Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8])]);
+    }
+
+    #[test]
+    fn test_random_order() {
+        let board = Board::new([1, 2, 3, 4, 0, 5, 6, 7, 8]);
+        let random = board.successors_in(MoveOrder::Random(7));
+
+        assert_eq!(random, board.successors_in(MoveOrder::Random(7)));
+        let mut sorted = random.clone();
+        sorted.sort_by_key(|successor| successor.zero);
+        let mut expected = board.successors();
+        expected.sort_by_key(|successor| successor.zero);
+        assert_eq!(sorted, expected);
+
+        //different seeds give different orders for at least some boards
+        let orders: Vec<[Move; 4]> = (0..10).map(|seed| MoveOrder::Random(seed).moves(&board)).collect();
+        assert!(orders.iter().any(|order| *order != orders[0]));
+    }
+
+    #[test]
+    fn test_is_solvable() {
+        assert!(GOAL.is_solvable());
+        assert!(Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]).is_solvable());
+        assert!(!Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]).is_solvable());
+        assert!(!Board::new([1, 2, 3, 4, 5, 6, 8, 7, 0]).is_solvable());
+    }
+
+    #[test]
+    fn test_inversions_and_parity() {
+        assert_eq!(GOAL.inversions(), 0);
+        assert_eq!(Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]).inversions(), 24);
+        assert_eq!(Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]).parity(), 1);
+
+        //on even widths the row of the blank counts too, so a vertical move keeps the parity
+        let goal = Board::<4>::goal();
+        let moved: Board<4> = "1 2 3 4 / 5 6 7 8 / 9 10 11 0 / 13 14 15 12".parse().unwrap();
+        assert_eq!(goal.parity(), 1);
+        assert_eq!(moved.inversions(), 3);
+        assert_eq!(moved.parity(), goal.parity());
+    }
+
+    #[test]
+    fn test_is_solvable_to_blank_first() {
+        assert!(BLANK_FIRST_GOAL.is_solvable());
+        assert!(GOAL.is_solvable_to(&BLANK_FIRST_GOAL));
+        assert!(!Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]).is_solvable_to(&BLANK_FIRST_GOAL));
+        assert_eq!(BLANK_FIRST_GOAL.zero, find_zero(BLANK_FIRST_GOAL.tiles.as_flattened()));
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let boards: HashSet<Board> = (0..1000).map(|_| Board::random(&mut rng)).collect();
+        assert!(boards.iter().all(|board| board.is_solvable()));
+        assert!(boards.len() > 990);
+
+        for _ in 0..100 {
+            let board = Board::<4>::random(&mut rng);
+            assert!(board.is_solvable());
+            assert_eq!(board.zero, find_zero(board.tiles.as_flattened()));
+        }
+    }
+
+    #[test]
+    fn test_apply_move_in_place() {
+        let mut board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let moves: Vec<Move> = board.possible_moves().collect();
+        assert_eq!(moves, vec![Move::Left, Move::Down, Move::Right]);
+
+        for next_move in moves {
+            let expected = board.apply_move(next_move).unwrap();
+            let mut moved = board;
+            assert!(moved.apply_move_in_place(next_move));
+            assert_eq!(moved, expected);
+            assert_eq!(moved.zero, expected.zero);
+        }
+
+        assert!(!board.apply_move_in_place(Move::Up));
+        assert_eq!(board, Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]));
+    }
+
+    #[test]
+    fn test_scrambled() {
+        let mut rng = StdRng::seed_from_u64(5);
+        assert_eq!(Board::<3>::scrambled(0, &mut rng), GOAL);
+
+        for steps in 1..12 {
+            let board = Board::<3>::scrambled(steps, &mut rng);
+            let cost = crate::a_star_search(board).unwrap().steps();
+            assert!(cost <= steps);
+            assert_eq!(cost % 2, steps % 2);
+        }
+
+        //without undoing moves, two steps never lead back to the goal
+        for _ in 0..20 {
+            assert_ne!(Board::<4>::scrambled(2, &mut rng), Board::<4>::goal());
+        }
+
+        //the blank turns back at the ends of a single row
+        let board = Board::<1, 3>::scrambled(7, &mut rng);
+        assert_eq!(board.tiles().iter().position(|tile| *tile == 0), Some(1));
+    }
+
+    #[test]
+    fn test_tiles() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        assert_eq!(board.tiles(), &[8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        assert_eq!(board.rows().collect::<Vec<&[Tile]>>(), vec![&[8, 6, 7], &[2, 5, 4], &[3, 0, 1]]);
+    }
+
+    #[test]
+    fn test_get() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        assert_eq!(board.get(0, 0), Some(8));
+        assert_eq!(board.get(1, 2), Some(4));
+        assert_eq!(board.get(2, 1), Some(0));
+        assert_eq!(board.get(3, 0), None);
+        assert_eq!(board.get(0, 3), None);
+    }
+
+    #[test]
+    fn test_position_of() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        assert_eq!(board.position_of(8), Some((0, 0)));
+        assert_eq!(board.position_of(4), Some((1, 2)));
+        assert_eq!(board.position_of(0), Some((2, 1)));
+        assert_eq!(board.position_of(9), None);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        assert_eq!(board.row(1).unwrap().collect::<Vec<Tile>>(), vec![2, 5, 4]);
+        assert_eq!(board.column(2).unwrap().collect::<Vec<Tile>>(), vec![7, 4, 1]);
+        assert!(board.row(3).is_none());
+        assert!(board.column(3).is_none());
+    }
+
+    #[test]
+    fn test_from_rows() {
+        let board = Board::try_from([[8, 6, 7], [2, 5, 4], [3, 0, 1]]).unwrap();
+        assert_eq!(board, Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]));
+        assert_eq!(board.zero, 7);
+
+        let rows: [[Tile; 3]; 3] = board.into();
+        assert_eq!(rows, [[8, 6, 7], [2, 5, 4], [3, 0, 1]]);
+
+        assert_eq!(Board::try_from([[8, 6, 7], [2, 5, 4], [3, 1, 1]]), Err(BoardError::DuplicateTile(1)));
+        //without a blank, one of the other tiles is repeated or out of range
+        assert_eq!(Board::try_from([[1, 2, 3], [4, 5, 6], [7, 8, 8]]), Err(BoardError::DuplicateTile(8)));
+        assert_eq!(Board::try_from([[1, 2, 3], [4, 5, 6], [7, 8, 9]]), Err(BoardError::InvalidTile(9)));
+    }
+
+    #[test]
+    fn test_try_from_vec() {
+        let board = Board::try_from(vec![8, 6, 7, 2, 5, 4, 3, 0, 1]).unwrap();
+        assert_eq!(board, Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]));
+
+        let tiles: Vec<Tile> = board.into();
+        assert_eq!(tiles, vec![8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let tiles: [Tile; 9] = board.into();
+        assert_eq!(tiles, [8, 6, 7, 2, 5, 4, 3, 0, 1]);
+    }
+
+    #[test]
+    fn test_try_from_invalid() {
+        assert_eq!(Board::<3>::try_from(vec![1, 2, 3]), Err(BoardError::WrongSize { expected: 9, found: 3 }));
+        assert_eq!(Board::<3>::try_from(vec![8, 6, 7, 2, 5, 4, 3, 9, 1]), Err(BoardError::InvalidTile(9)));
+        assert_eq!(Board::<3>::try_from(vec![8, 6, 7, 2, 5, 4, 3, 1, 1]), Err(BoardError::DuplicateTile(1)));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        assert_eq!(serde_json::to_string(&board).unwrap(), "[8,6,7,2,5,4,3,0,1]");
+        assert_eq!(serde_json::from_str::<Board>("[8,6,7,2,5,4,3,0,1]").unwrap(), board);
+        assert_eq!(serde_json::from_str::<Board<2, 3>>("[1,2,3,4,5,0]").unwrap(), Board::<2, 3>::goal());
+
+        let error = serde_json::from_str::<Board>("[1,1,2,3,4,5,6,7,0]").unwrap_err();
+        assert_eq!(error.to_string(), "Tile 1 appears more than once.");
+        assert!(serde_json::from_str::<Board<4>>("[8,6,7,2,5,4,3,0,1]").is_err());
+    }
+
     #[test]
     fn test_zero_pos() {
-        assert_eq!(find_zero([0, 1, 2, 3, 4, 5, 6, 7, 8]), 0);
-        assert_eq!(find_zero([1, 0, 2, 3, 4, 5, 6, 7, 8]), 1);
-        assert_eq!(find_zero([1, 8, 2, 3, 4, 5, 6, 7, 0]), 8);
+        assert_eq!(find_zero(&[0, 1, 2, 3, 4, 5, 6, 7, 8]), 0);
+        assert_eq!(find_zero(&[1, 0, 2, 3, 4, 5, 6, 7, 8]), 1);
+        assert_eq!(find_zero(&[1, 8, 2, 3, 4, 5, 6, 7, 0]), 8);
     }
 
     #[test]
@@ -202,6 +1101,21 @@ mod tests {
         Board::new([9, 1, 2, 3, 4, 5, 6, 7, 8]);
     }
 
+    #[test]
+    #[should_panic(expected = "Tile 1 appears more than once.")]
+    fn test_duplicate_board_initialisation() {
+        Board::new([1, 1, 2, 3, 4, 5, 6, 7, 0]);
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(Board::try_new([8, 6, 7, 2, 5, 4, 3, 0, 1]), Ok(Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1])));
+        assert_eq!(Board::try_new([9, 1, 2, 3, 4, 5, 6, 7, 8]), Err(BoardError::InvalidTile(9)));
+        assert_eq!(Board::try_new([1, 1, 2, 3, 4, 5, 6, 7, 8]), Err(BoardError::DuplicateTile(1)));
+        //without a blank, some other tile has to be missing or repeated
+        assert_eq!(Board::try_new([1, 2, 3, 4, 5, 6, 7, 8, 8]), Err(BoardError::DuplicateTile(8)));
+    }
+
     #[test]
     fn test_swap() {
         let board = Board::new([0, 1, 2, 3, 4, 5, 6, 7, 8]);
@@ -289,6 +1203,67 @@ mod tests {
         assert!(successors.contains(&Board::new([1, 2, 3, 4, 5, 0, 7, 8, 6])));
     }
 
+    #[test]
+    fn test_move_to() {
+        let board = Board::new([1, 2, 3, 4, 0, 6, 7, 5, 8]);
+        assert_eq!(board.move_to(&Board::new([1, 2, 3, 4, 6, 0, 7, 5, 8])), Some(Move::Left));
+        assert_eq!(board.move_to(&Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8])), Some(Move::Up));
+        assert_eq!(board.move_to(&Board::new([1, 0, 3, 4, 2, 6, 7, 5, 8])), Some(Move::Down));
+        assert_eq!(board.move_to(&Board::new([1, 2, 3, 0, 4, 6, 7, 5, 8])), Some(Move::Right));
+        assert_eq!(board.move_to(&board), None);
+        assert_eq!(board.move_to(&GOAL), None);
+    }
+
+    #[test]
+    fn test_successors_with_moves() {
+        let board = Board::new([1, 2, 3, 4, 0, 6, 7, 5, 8]);
+        let labelled = board.successors_with_moves();
+
+        assert_eq!(labelled.iter().map(|(_, successor)| *successor).collect::<Vec<Board>>(), board.successors());
+        for (next_move, successor) in labelled.iter() {
+            assert_eq!(board.move_to(successor), Some(*next_move));
+        }
+        assert_eq!(GOAL.successors_with_moves_in(MoveOrder::Reversed), vec![
+            (Move::Right, Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8])),
+            (Move::Down, Board::new([1, 2, 3, 4, 5, 0, 7, 8, 6])),
+        ]);
+    }
+
+    #[test]
+    fn test_apply_moves() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let plan = crate::a_star_search(board).unwrap();
+
+        assert_eq!(board.apply_moves(plan.moves()), Ok(GOAL));
+        assert_eq!(GOAL.apply_moves(&plan.undo_moves()), Ok(board));
+        assert_eq!(board.apply_moves(&[]), Ok(board));
+
+        assert_eq!(GOAL.apply_move(Move::Up), None);
+        assert_eq!(GOAL.apply_move(Move::Right), Some(Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8])));
+        assert_eq!(GOAL.apply_moves(&[Move::Right, Move::Right, Move::Right]), Err(MoveError::Blocked { index: 2, next_move: Move::Right }));
+    }
+
+    #[test]
+    fn test_move_inverse() {
+        let board = Board::new([1, 2, 3, 4, 0, 6, 7, 5, 8]);
+        for successor in board.successors() {
+            assert_eq!(successor.move_to(&board), board.move_to(&successor).map(Move::inverse));
+        }
+    }
+
+    #[test]
+    fn test_distance_tables() {
+        for pos1 in 0..16 {
+            for pos2 in 0..16 {
+                if pos1 < 9 && pos2 < 9 {
+                    assert_eq!(position_dist::<3, 3>(pos1, pos2), grid_dist(pos1, pos2, 3));
+                }
+                assert_eq!(position_dist::<4, 4>(pos1, pos2), grid_dist(pos1, pos2, 4));
+                assert_eq!(position_dist::<4, 5>(pos1, pos2), grid_dist(pos1, pos2, 5));
+            }
+        }
+    }
+
     #[test]
     fn test_manhattan_dist_positions() {
         //all positions from 0
@@ -342,6 +1317,75 @@ mod tests {
         assert_eq!(4, Board::new([1, 2, 3, 7, 4, 6, 5, 8, 0]).manhattan_dist());
     }
 
+    #[test]
+    fn test_manhattan_dist_from() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut board = Board::<4>::goal();
+        let mut dist = 0;
+        for _ in 0..1000 {
+            let next = *board.successors().choose(&mut rng).unwrap();
+            dist = next.manhattan_dist_from(&board, dist);
+            assert_eq!(dist, next.manhattan_dist(), "{}", next);
+            board = next;
+        }
+
+        //hidden tiles are left out like the blank
+        let hidden = Board::from_rows([[HIDDEN, HIDDEN, HIDDEN], [2, HIDDEN, HIDDEN], [3, 0, 1]]);
+        let next = hidden.apply_move(Move::Right).unwrap();
+        assert_eq!(next.manhattan_dist_from(&hidden, hidden.manhattan_dist()), next.manhattan_dist());
+    }
+
+    #[test]
+    fn test_reflected() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let reflected = board.reflected_to(&GOAL).unwrap();
+        //tile 2 is where 4 belongs, so it mirrors onto where 2 belongs and becomes 4
+        assert_eq!(reflected, Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]));
+        assert_eq!(reflected.reflected_to(&GOAL), Some(board));
+        assert_eq!(reflected.manhattan_dist(), board.manhattan_dist());
+        assert_eq!(GOAL.reflected_to(&GOAL), Some(GOAL));
+        assert_eq!(BLANK_FIRST_GOAL.reflected_to(&BLANK_FIRST_GOAL), Some(BLANK_FIRST_GOAL));
+
+        //the blank of the goal is off the diagonal, or the board is not square
+        assert_eq!(board.reflected_to(&Board::new([1, 2, 0, 3, 4, 5, 6, 7, 8])), None);
+        assert_eq!(Board::<2, 3>::goal().reflected_to(&Board::<2, 3>::goal()), None);
+    }
+
+    #[test]
+    fn test_canonical() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..100 {
+            let board = Board::<4>::random(&mut rng);
+            let canonical = board.canonical();
+            assert_eq!(canonical.canonical(), canonical);
+            assert_eq!(board.reflected_to(&Board::goal()).unwrap().canonical(), canonical);
+            assert!(canonical == board || Some(canonical) == board.reflected_to(&Board::goal()));
+            assert!(canonical.is_solvable());
+        }
+    }
+
+    #[test]
+    fn test_rank() {
+        assert_eq!(Board::new([0, 1, 2, 3, 4, 5, 6, 7, 8]).rank(), 0);
+        assert_eq!(Board::new([8, 7, 6, 5, 4, 3, 2, 1, 0]).rank(), Board::RANKS - 1);
+        assert_eq!(Board::new([0, 1, 2, 3, 4, 5, 6, 8, 7]).rank(), 1);
+        assert_eq!(Board::unrank(GOAL.rank()), GOAL);
+
+        let mut seen = vec![false; Board::RANKS as usize];
+        for rank in 0..Board::RANKS {
+            let board = Board::unrank(rank);
+            assert_eq!(board.rank(), rank);
+            assert!(!seen[rank as usize]);
+            seen[rank as usize] = true;
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unrank_out_of_range() {
+        Board::unrank(Board::RANKS);
+    }
+
     #[test]
     fn test_displaced_tiles() {
         assert_eq!(0, Board::new([1, 2, 3, 4, 5, 6, 7, 8, 0]).displaced_tiles());
@@ -352,4 +1396,215 @@ mod tests {
         assert_eq!(4, Board::new([1, 2, 3, 7, 4, 6, 5, 0, 8]).displaced_tiles());
         assert_eq!(3, Board::new([1, 2, 3, 7, 4, 6, 5, 8, 0]).displaced_tiles());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_inverse_permutation() {
+        assert_eq!(GOAL.inverse_permutation(), GOAL);
+
+        let board = Board::new([2, 3, 1, 4, 5, 6, 7, 8, 0]);
+        assert_eq!(board.inverse_permutation(), Board::new([3, 1, 2, 4, 5, 6, 7, 8, 0]));
+
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 1, 0]);
+        assert_eq!(board.inverse_permutation().inverse_permutation(), board);
+    }
+
+    #[test]
+    fn test_distances_to_goal_match() {
+        let board = Board::new([1, 2, 3, 7, 4, 6, 5, 0, 8]);
+        assert_eq!(board.manhattan_dist(), board.manhattan_dist_to(&GOAL));
+        assert_eq!(board.displaced_tiles(), board.displaced_tiles_to(&GOAL));
+    }
+
+    #[test]
+    fn test_distances_to_other_goal() {
+        let goal = Board::new([0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(0, goal.manhattan_dist_to(&goal));
+        assert_eq!(1, Board::new([1, 0, 2, 3, 4, 5, 6, 7, 8]).manhattan_dist_to(&goal));
+        assert_eq!(12, GOAL.manhattan_dist_to(&goal));
+        assert_eq!(8, GOAL.displaced_tiles_to(&goal));
+    }
+
+    #[test]
+    fn test_fifteen_puzzle_goal() {
+        let goal = Board::<4>::goal();
+        assert_eq!((goal.height(), goal.width()), (4, 4));
+        assert_eq!(goal.tiles(), &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 0]);
+        assert!(goal.is_goal());
+        assert!(goal.is_blank_home());
+        assert_eq!(goal.manhattan_dist(), 0);
+        assert_eq!(Board::<3>::goal(), GOAL);
+    }
+
+    #[test]
+    fn test_fifteen_puzzle_successors() {
+        let goal = Board::<4>::goal();
+        assert_eq!(goal.successors().len(), goal.successor_count());
+        assert_eq!(goal.successors().len(), 2);
+
+        let board = Board::try_from([[1, 2, 3, 4], [5, 0, 6, 7], [8, 9, 10, 11], [12, 13, 14, 15]]).unwrap();
+        let successors = board.successors();
+        assert_eq!(successors.len(), board.successor_count());
+        assert_eq!(successors, vec![
+            Board::try_from([[1, 2, 3, 4], [5, 6, 0, 7], [8, 9, 10, 11], [12, 13, 14, 15]]).unwrap(),
+            Board::try_from([[1, 2, 3, 4], [5, 9, 6, 7], [8, 0, 10, 11], [12, 13, 14, 15]]).unwrap(),
+            Board::try_from([[1, 0, 3, 4], [5, 2, 6, 7], [8, 9, 10, 11], [12, 13, 14, 15]]).unwrap(),
+            Board::try_from([[1, 2, 3, 4], [0, 5, 6, 7], [8, 9, 10, 11], [12, 13, 14, 15]]).unwrap(),
+        ]);
+
+        let moves: Vec<Option<Move>> = successors.iter().map(|successor| board.move_to(successor)).collect();
+        assert_eq!(moves, vec![Some(Move::Left), Some(Move::Up), Some(Move::Down), Some(Move::Right)]);
+        assert_eq!(board.successors_in(MoveOrder::Reversed), successors.iter().rev().copied().collect::<Vec<Board<4>>>());
+    }
+
+    #[test]
+    fn test_fifteen_puzzle_manhattan_distance() {
+        let board = Board::try_from([[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12], [13, 14, 0, 15]]).unwrap();
+        assert_eq!(board.manhattan_dist(), 1);
+        assert_eq!(board.displaced_tiles(), 1);
+
+        let board = Board::try_from([[15, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12], [13, 14, 1, 0]]).unwrap();
+        assert_eq!(board.manhattan_dist(), 10);
+        assert_eq!(board.manhattan_dist(), board.manhattan_dist_to(&Board::goal()));
+    }
+
+    #[test]
+    fn test_fifteen_puzzle_is_solvable() {
+        //one vertical move away from the goal, which changes the row of the blank
+        let board = Board::try_from([[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 0], [13, 14, 15, 12]]).unwrap();
+        assert!(board.is_solvable());
+
+        //Loyd's 14-15 puzzle
+        let board = Board::try_from([[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12], [13, 15, 14, 0]]).unwrap();
+        assert!(!board.is_solvable());
+    }
+
+    #[test]
+    fn test_twenty_four_puzzle() {
+        let goal = Board::<5>::goal();
+        assert_eq!(goal.tiles().iter().filter(|tile| **tile == 0).count(), 1);
+        assert_eq!(goal.successors().len(), 2);
+        assert!(goal.successors().iter().all(|successor| successor.manhattan_dist() == 1 && successor.is_solvable()));
+        assert_eq!(Board::<5>::try_from((0..25).collect::<Vec<Tile>>()).unwrap().zero, 0);
+        assert_eq!(Board::<5>::try_from(vec![1, 2, 3]), Err(BoardError::WrongSize { expected: 25, found: 3 }));
+    }
+
+    #[test]
+    fn test_tiles_above_i8() {
+        //the 143-puzzle numbers tiles past what an i8 holds
+        let goal = Board::<12>::goal();
+        assert_eq!(goal.tiles()[142], 143);
+        assert!(goal.is_goal() && goal.is_solvable());
+        assert!(goal.successors().iter().all(|successor| successor.manhattan_dist() == 1 && successor.displaced_tiles() == 1));
+
+        let text: Vec<String> = goal.tiles().iter().map(|tile| tile.to_string()).collect();
+        assert_eq!(text.join(" ").parse::<Board<12>>(), Ok(goal));
+        assert_eq!("-1 0 2 3".parse::<Board<2>>(), Err(ParseBoardError::InvalidNumber("-1".to_string())));
+        assert_eq!(Board::<15>::goal().tiles()[223], 224);
+    }
+
+    #[test]
+    fn test_rectangular_board() {
+        let goal = Board::<2, 3>::goal();
+        assert_eq!((goal.height(), goal.width()), (2, 3));
+        assert_eq!(goal.tiles(), &[1, 2, 3, 4, 5, 0]);
+        assert_eq!(goal.row(1).unwrap().collect::<Vec<Tile>>(), vec![4, 5, 0]);
+        assert_eq!(goal.column(2).unwrap().collect::<Vec<Tile>>(), vec![3, 0]);
+        assert!(goal.row(2).is_none() && goal.column(3).is_none());
+        assert_eq!(goal.get(2, 0), None);
+        assert_eq!(goal.to_string(), "1 2 3\r\n4 5 0\r\n");
+        assert!(goal.is_goal());
+    }
+
+    #[test]
+    fn test_rectangular_successors() {
+        let board = Board::try_from([[1, 0, 2], [4, 5, 3]]).unwrap();
+        let successors = board.successors();
+        assert_eq!(successors.len(), board.successor_count());
+        assert_eq!(successors, vec![
+            Board::try_from([[1, 2, 0], [4, 5, 3]]).unwrap(),
+            Board::try_from([[1, 5, 2], [4, 0, 3]]).unwrap(),
+            Board::try_from([[0, 1, 2], [4, 5, 3]]).unwrap(),
+        ]);
+
+        let moves: Vec<Option<Move>> = successors.iter().map(|successor| board.move_to(successor)).collect();
+        assert_eq!(moves, vec![Some(Move::Left), Some(Move::Up), Some(Move::Right)]);
+        assert_eq!(board.manhattan_dist(), 2);
+        assert_eq!(board.displaced_tiles(), 2);
+    }
+
+    #[test]
+    fn test_rectangular_is_solvable() {
+        assert!(Board::<3, 4>::goal().is_solvable());
+        assert!(Board::try_from([[1, 2, 3, 4], [5, 6, 7, 0], [9, 10, 11, 8]]).unwrap().is_solvable());
+        assert!(!Board::try_from([[1, 2, 3, 4], [5, 6, 7, 8], [9, 11, 10, 0]]).unwrap().is_solvable());
+        assert_eq!(Board::<3, 4>::try_from(vec![1, 2, 3]), Err(BoardError::WrongSize { expected: 12, found: 3 }));
+    }
+
+    #[test]
+    fn test_line_is_solvable() {
+        //the tiles of a single row or column cannot pass each other, whatever the parity
+        assert!(Board::<1, 3>::try_from([[1, 0, 2]]).unwrap().is_solvable());
+        assert!(!Board::<1, 3>::try_from([[2, 0, 1]]).unwrap().is_solvable());
+        assert!(!Board::<1, 4>::try_from([[3, 1, 2, 0]]).unwrap().is_solvable());
+        assert_eq!(Board::<1, 4>::try_from([[3, 1, 2, 0]]).unwrap().parity(), 0);
+        assert!(Board::<3, 1>::try_from([[0], [1], [2]]).unwrap().is_solvable());
+        assert!(!Board::<3, 1>::try_from([[2], [0], [1]]).unwrap().is_solvable());
+        assert!(!Board::<4, 1>::try_from([[2], [3], [1], [0]]).unwrap().is_solvable());
+
+        assert_eq!(reachable_from(Board::<1, 4>::goal()).len(), 4);
+        assert!(reachable_from(Board::<4, 1>::goal()).iter().all(|board| board.is_solvable()));
+
+        let mut rng = StdRng::seed_from_u64(5);
+        assert!((0..20).all(|_| Board::<1, 4>::random(&mut rng).is_solvable() && Board::<4, 1>::random(&mut rng).is_solvable()));
+    }
+
+    #[test]
+    fn test_rectangular_reachable_boards() {
+        //half of the permutations can be reached, and they are all the solvable ones
+        let reachable = reachable_from(Board::<2, 3>::goal());
+        assert_eq!(reachable.len(), 360);
+        assert!(reachable.iter().all(|board| board.is_solvable()));
+
+        let reachable = reachable_from(Board::<2, 4>::goal());
+        assert_eq!(reachable.len(), 20_160);
+        assert!(reachable.iter().all(|board| board.is_solvable()));
+    }
+
+    fn reachable_from<const R: usize, const C: usize>(board: Board<R, C>) -> HashSet<Board<R, C>> {
+        let mut reachable = HashSet::new();
+        let mut pending = vec![board];
+        reachable.insert(board);
+
+        while let Some(board) = pending.pop() {
+            for successor in board.successors() {
+                if reachable.insert(successor) {
+                    pending.push(successor);
+                }
+            }
+        }
+
+        reachable
+    }
+
+    #[test]
+    fn test_from_str() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        assert_eq!("8 6 7 2 5 4 3 0 1".parse::<Board>(), Ok(board));
+        assert_eq!("8,6,7,2,5,4,3,0,1".parse::<Board>(), Ok(board));
+        assert_eq!(" 8, 6, 7,\n2, 5, 4,\n3, 0, 1 ".parse::<Board>(), Ok(board));
+        assert_eq!("8 6 7 / 2 5 4 / 3 0 1".parse::<Board>(), Ok(board));
+        assert_eq!("8,6,7/2,5,4/3,0,1".parse::<Board>(), Ok(board));
+
+        let board = Board::try_from([[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12], [13, 14, 15, 0]]).unwrap();
+        assert_eq!("1 2 3 4 / 5 6 7 8 / 9 10 11 12 / 13 14 15 0".parse::<Board<4>>(), Ok(board));
+    }
+
+    #[test]
+    fn test_from_str_invalid() {
+        assert_eq!("8 6 7 2 5 4 3 0 x".parse::<Board>(), Err(ParseBoardError::InvalidNumber("x".to_string())));
+        assert_eq!("8 6 7 2 5 4 3 0".parse::<Board>(), Err(ParseBoardError::Board(BoardError::WrongSize { expected: 9, found: 8 })));
+        assert_eq!("8 6 7 2 / 5 4 / 3 0 1".parse::<Board>(), Err(ParseBoardError::WrongRowSize { row: 0, expected: 3, found: 4 }));
+        assert_eq!("8 6 7 2 5 4 3 1 1".parse::<Board>(), Err(ParseBoardError::Board(BoardError::DuplicateTile(1))));
+        assert_eq!("".parse::<Board>(), Err(ParseBoardError::Board(BoardError::WrongSize { expected: 9, found: 0 })));
+    }
+}
@@ -0,0 +1,127 @@
+use std::hash::{Hash, Hasher};
+
+use crate::board::{Board, Move, Tile};
+use crate::search::State;
+
+/// The cost of sliding the tile from one position to the neighbouring one (where the blank was)
+pub type MoveCost = fn(tile: Tile, from: usize, to: usize) -> u32;
+
+/// A board whose moves cost what the cost function says, such as the number on the tile, or two for
+/// the horizontal moves, which the searches add up instead of counting the moves.
+/// Boards with the same tiles are equal whatever their costs, as a search only compares boards that
+/// share the same cost function.
+#[derive(Debug, Copy, Clone)]
+pub struct CostedBoard<const R: usize = 3, const C: usize = R> {
+    board: Board<R, C>,
+    cost: MoveCost,
+    //the cheapest move of any tile between any two positions, which turns the manhattan distance
+    //into a lower bound of the cost
+    min_cost: u32,
+}
+
+impl<const R: usize, const C: usize> CostedBoard<R, C> {
+    pub fn new(board: Board<R, C>, cost: MoveCost) -> CostedBoard<R, C> {
+        let mut min_cost = u32::MAX;
+        for from in 0..R * C {
+            let neighbours = [(from % C + 1 < C, from + 1), (from + C < R * C, from + C)];
+            for (_, to) in neighbours.iter().filter(|(exists, _)| *exists) {
+                for tile in 1..(R * C) as Tile {
+                    min_cost = min_cost.min(cost(tile, from, *to)).min(cost(tile, *to, from));
+                }
+            }
+        }
+
+        CostedBoard { board, cost, min_cost }
+    }
+
+    pub fn board(&self) -> &Board<R, C> {
+        &self.board
+    }
+
+    /// The lowest cost of a single move on the board
+    pub fn min_cost(&self) -> u32 {
+        self.min_cost
+    }
+
+    /// The cost of the move, if there is a tile to slide in that direction
+    pub fn move_cost(&self, next_move: Move) -> Option<u32> {
+        self.board.moved_tile(next_move)
+            .map(|pos| (self.cost)(self.board.tiles()[pos], pos, self.board.zero))
+    }
+
+    /// The total cost of the moves from this board, if they can all be made
+    pub fn plan_cost(&self, moves: &[Move]) -> Option<u32> {
+        let mut board = *self;
+        let mut total = 0;
+        for next_move in moves {
+            total += board.move_cost(*next_move)?;
+            board.board = board.board.apply_move(*next_move)?;
+        }
+
+        Some(total)
+    }
+}
+
+impl<const R: usize, const C: usize> PartialEq for CostedBoard<R, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board
+    }
+}
+
+impl<const R: usize, const C: usize> Eq for CostedBoard<R, C> {}
+
+impl<const R: usize, const C: usize> Hash for CostedBoard<R, C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.board.hash(state);
+    }
+}
+
+impl<const R: usize, const C: usize> State for CostedBoard<R, C> {
+    type Action = Move;
+    type Data = ();
+
+    fn successors_with_actions(&self) -> Vec<(Move, Self)> {
+        self.board.successors_with_moves().into_iter()
+            .map(|(next_move, board)| (next_move, CostedBoard { board, ..*self }))
+            .collect()
+    }
+
+    fn h(&self) -> i32 {
+        self.board.manhattan_dist() * self.min_cost as i32
+    }
+
+    fn cost(&self, action: Move) -> u32 {
+        self.move_cost(action).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_cost() {
+        let board = CostedBoard::new(Board::new([1, 2, 3, 4, 0, 5, 6, 7, 8]), |tile, _, _| tile as u32);
+        assert_eq!(board.min_cost(), 1);
+        assert_eq!(board.move_cost(Move::Left), Some(5));
+        assert_eq!(board.move_cost(Move::Up), Some(7));
+        assert_eq!(board.plan_cost(&[Move::Left, Move::Up]), Some(5 + 8));
+        assert_eq!(board.plan_cost(&[Move::Left, Move::Left]), None);
+
+        let successors = board.successors_with_actions();
+        assert_eq!(successors.len(), 4);
+        assert!(successors.iter().all(|(next_move, successor)| Some(*successor.board()) == board.board().apply_move(*next_move)));
+    }
+
+    #[test]
+    fn test_scaled_heuristic() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let horizontal_double = CostedBoard::new(board, |_, from, to| if from / 3 == to / 3 { 2 } else { 1 });
+        assert_eq!(horizontal_double.h(), board.manhattan_dist());
+
+        let expensive = CostedBoard::new(board, |tile, _, _| 3 + tile as u32);
+        assert_eq!(expensive.min_cost(), 4);
+        assert_eq!(expensive.h(), 4 * board.manhattan_dist());
+        assert_eq!(expensive, CostedBoard::new(board, |_, _, _| 1));
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt::{Display, Formatter, Result};
+
+use crate::board::Board;
+
+/// How the blank space is shown
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Blank {
+    Zero,
+    Underscore,
+    Space,
+}
+
+/// What ends every row
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LineEnding {
+    CrLf,
+    Lf,
+}
+
+impl LineEnding {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Lf => "\n",
+        }
+    }
+}
+
+/// How a board is written out, the default being the `Display` of `Board`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DisplayOptions {
+    pub blank: Blank,
+    /// Pads every tile to the width of the largest one, so that the columns line up
+    pub aligned: bool,
+    pub line_ending: LineEnding,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> DisplayOptions {
+        DisplayOptions { blank: Blank::Zero, aligned: false, line_ending: LineEnding::CrLf }
+    }
+}
+
+/// A board with the options to display it with, made by `Board::display_with`
+#[derive(Debug, Copy, Clone)]
+pub struct BoardDisplay<'a, const R: usize, const C: usize> {
+    pub(super) board: &'a Board<R, C>,
+    pub(super) options: DisplayOptions,
+}
+
+impl<const R: usize, const C: usize> Display for BoardDisplay<'_, R, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let width = if self.options.aligned { (R * C - 1).to_string().len() } else { 0 };
+        for row in self.board.rows() {
+            for (col, tile) in row.iter().enumerate() {
+                let tile = match (tile, self.options.blank) {
+                    (0, Blank::Underscore) => "_".to_string(),
+                    (0, Blank::Space) => " ".to_string(),
+                    _ => tile.to_string(),
+                };
+
+                let separator = if col + 1 < C { " " } else { self.options.line_ending.as_str() };
+                write!(f, "{:>width$}{}", tile, separator, width = width)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::GOAL;
+
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        assert_eq!(board.display_with(DisplayOptions::default()).to_string(), board.to_string());
+    }
+
+    #[test]
+    fn test_blank_and_line_ending() {
+        let underscore = DisplayOptions { blank: Blank::Underscore, line_ending: LineEnding::Lf, ..DisplayOptions::default() };
+        assert_eq!(GOAL.display_with(underscore).to_string(), "1 2 3\n4 5 6\n7 8 _\n");
+
+        let space = DisplayOptions { blank: Blank::Space, ..DisplayOptions::default() };
+        assert_eq!(GOAL.display_with(space).to_string(), "1 2 3\r\n4 5 6\r\n7 8  \r\n");
+    }
+
+    #[test]
+    fn test_aligned() {
+        let board: Board<4> = "5 1 3 4 / 9 2 7 8 / 13 6 10 12 / 14 0 11 15".parse().unwrap();
+        let options = DisplayOptions { blank: Blank::Underscore, aligned: true, line_ending: LineEnding::Lf };
+        assert_eq!(board.display_with(options).to_string(), " 5  1  3  4\n 9  2  7  8\n13  6 10 12\n14  _ 11 15\n");
+
+        //one digit tiles need no padding
+        let options = DisplayOptions { aligned: true, ..DisplayOptions::default() };
+        assert_eq!(GOAL.display_with(options).to_string(), GOAL.to_string());
+    }
+}
@@ -0,0 +1,259 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::board::{Board, Move, Tile};
+
+/// How the tiles of a board are stored.
+/// Every representation has to generate the same successors, in the same order, as `Board`, and
+/// equal boards need to have equal (and equally hashed) representations.
+pub trait Repr: Copy + Eq + Hash + Debug {
+    fn from_tiles(tiles: [Tile; 9]) -> Self;
+
+    fn to_tiles(&self) -> [Tile; 9];
+
+    /// The position of the blank space
+    fn zero(&self) -> usize;
+
+    /// Slides the tile at the position into the blank space
+    fn slide(&self, pos: usize) -> Self;
+
+    fn successors(&self) -> Vec<Self> {
+        self.successors_with_moves().into_iter().map(|(_, successor)| successor).collect()
+    }
+
+    /// The successors, each with the move that slides a tile into the blank to reach it
+    fn successors_with_moves(&self) -> Vec<(Move, Self)> {
+        let zero = self.zero();
+        let col = zero % 3;
+        let mut successors = Vec::with_capacity(4);
+
+        //left
+        if col != 2 {
+            successors.push((Move::Left, self.slide(zero + 1)));
+        }
+
+        //up
+        if zero <= 5 {
+            successors.push((Move::Up, self.slide(zero + 3)));
+        }
+
+        //down
+        if zero >= 3 {
+            successors.push((Move::Down, self.slide(zero - 3)));
+        }
+
+        //right
+        if col != 0 {
+            successors.push((Move::Right, self.slide(zero - 1)));
+        }
+
+        successors
+    }
+}
+
+impl Repr for Board {
+    fn from_tiles(tiles: [Tile; 9]) -> Board {
+        Board::new(tiles)
+    }
+
+    fn to_tiles(&self) -> [Tile; 9] {
+        (*self).into()
+    }
+
+    fn zero(&self) -> usize {
+        self.zero
+    }
+
+    fn slide(&self, pos: usize) -> Board {
+        self.swap(self.zero, pos)
+    }
+}
+
+const TILE_BITS: usize = 4;
+const TILE_MASK: u64 = 0x0f;
+const ZERO_SHIFT: usize = 9 * TILE_BITS;
+
+/// The tiles packed into a single word, 4 bits per position, with the position of the blank
+/// stored above them
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Packed(u64);
+
+impl Packed {
+    /// The tile at the position, 0 for the blank
+    pub fn tile(&self, pos: usize) -> u64 {
+        (self.0 >> (pos * TILE_BITS)) & TILE_MASK
+    }
+}
+
+impl Repr for Packed {
+    fn from_tiles(tiles: [Tile; 9]) -> Packed {
+        let mut packed = 0;
+        for (pos, tile) in tiles.iter().enumerate() {
+            assert!(*tile <= 8, "Invalid tile {}.", tile);
+            packed |= (*tile as u64) << (pos * TILE_BITS);
+            if *tile == 0 {
+                packed |= (pos as u64) << ZERO_SHIFT;
+            }
+        }
+
+        Packed(packed)
+    }
+
+    fn to_tiles(&self) -> [Tile; 9] {
+        let mut tiles = [0; 9];
+        for (pos, tile) in tiles.iter_mut().enumerate() {
+            *tile = self.tile(pos) as Tile;
+        }
+
+        tiles
+    }
+
+    fn zero(&self) -> usize {
+        (self.0 >> ZERO_SHIFT) as usize
+    }
+
+    fn slide(&self, pos: usize) -> Packed {
+        let zero = self.zero();
+        assert!(pos < 9);
+
+        //the blank is stored as 0, so the tile can be OR-ed into its slot
+        let tile = self.tile(pos);
+        let tiles = (self.0 & !(TILE_MASK << (pos * TILE_BITS))) | (tile << (zero * TILE_BITS));
+        let tiles = tiles & !(TILE_MASK << ZERO_SHIFT);
+
+        Packed(tiles | ((pos as u64) << ZERO_SHIFT))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::time::Instant;
+
+    use crate::board::GOAL;
+
+    use super::*;
+
+    /// Every arrangement of the tiles, generated with Heap's algorithm
+    fn permutations() -> Vec<[Tile; 9]> {
+        let mut tiles = [0, 1, 2, 3, 4, 5, 6, 7, 8];
+        let mut counters = [0; 9];
+        let mut permutations = vec![tiles];
+
+        let mut i = 0;
+        while i < tiles.len() {
+            if counters[i] < i {
+                if i % 2 == 0 {
+                    tiles.swap(0, i);
+                } else {
+                    tiles.swap(counters[i], i);
+                }
+                permutations.push(tiles);
+                counters[i] += 1;
+                i = 0;
+            } else {
+                counters[i] = 0;
+                i += 1;
+            }
+        }
+
+        permutations
+    }
+
+    /// xorshift, so that random walks are reproducible without extra dependencies
+    fn next_random(seed: &mut u64) -> u64 {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+        *seed
+    }
+
+    fn assert_same<R: Repr>(board: &Board, repr: &R) {
+        assert_eq!(repr.to_tiles(), <[Tile; 9]>::from(*board), "{:?}", repr);
+        assert_eq!(repr.zero(), board.zero, "{:?}", repr);
+    }
+
+    #[test]
+    fn test_round_trip_all_permutations() {
+        let permutations = permutations();
+        assert_eq!(permutations.len(), 362_880);
+
+        let mut seen = HashSet::with_capacity(permutations.len());
+        for tiles in permutations {
+            let packed = Packed::from_tiles(tiles);
+            assert_same(&Board::new(tiles), &packed);
+            assert_eq!(Packed::from_tiles(packed.to_tiles()), packed);
+            seen.insert(packed);
+        }
+
+        //distinct boards never share a representation
+        assert_eq!(seen.len(), 362_880);
+    }
+
+    #[test]
+    fn test_successors_all_permutations() {
+        for tiles in permutations() {
+            let board = Board::new(tiles);
+            let successors = Packed::from_tiles(tiles).successors();
+
+            assert_eq!(successors.len(), board.successors().len());
+            for (expected, packed) in board.successors().iter().zip(successors.iter()) {
+                assert_same(expected, packed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_walks() {
+        let mut seed = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..100 {
+            let mut board = GOAL;
+            let mut packed = Packed::from_tiles(GOAL.into());
+
+            for _ in 0..1000 {
+                let successors = board.successors();
+                let choice = next_random(&mut seed) as usize % successors.len();
+                board = successors[choice];
+                packed = packed.successors()[choice];
+
+                assert_same(&board, &packed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_board_repr() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        assert_eq!(Repr::successors(&board), board.successors());
+        assert_eq!(Repr::successors_with_moves(&board), board.successors_with_moves());
+        assert_eq!(Board::from_tiles(board.to_tiles()), board);
+    }
+
+    fn expand_all<R: Repr>(boards: &[[Tile; 9]]) -> usize {
+        boards.iter()
+            .map(|tiles| R::from_tiles(*tiles))
+            .flat_map(|repr| repr.successors())
+            .map(|successor| successor.zero())
+            .sum()
+    }
+
+    /// Compares the cost of generating successors in each representation.
+    /// Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_successors() {
+        let permutations = permutations();
+
+        let start = Instant::now();
+        let board_sum = expand_all::<Board>(&permutations);
+        let board_duration = start.elapsed();
+
+        let start = Instant::now();
+        let packed_sum = expand_all::<Packed>(&permutations);
+        let packed_duration = start.elapsed();
+
+        assert_eq!(board_sum, packed_sum);
+        println!("array:  {:?}", board_duration);
+        println!("packed: {:?}", packed_duration);
+    }
+}
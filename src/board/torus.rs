@@ -0,0 +1,146 @@
+use crate::board::{Board, Move, DEFAULT_ORDER};
+use crate::search::State;
+
+/// A board whose edges wrap around: the blank can slide off one edge and come back on the opposite
+/// one, swapping with the tile there, as if the board was drawn on a torus.
+/// It needs at least two rows and two columns.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct TorusBoard<const R: usize = 3, const C: usize = R> {
+    board: Board<R, C>,
+}
+
+impl<const R: usize, const C: usize> TorusBoard<R, C> {
+    pub fn new(board: Board<R, C>) -> TorusBoard<R, C> {
+        assert!(R >= 2 && C >= 2, "A torus needs at least two rows and two columns, found {}x{}.", R, C);
+        TorusBoard { board }
+    }
+
+    pub fn board(&self) -> &Board<R, C> {
+        &self.board
+    }
+
+    pub fn is_goal(&self) -> bool {
+        self.board.is_goal()
+    }
+
+    /// The position of the tile that would slide into the blank with the move, around the edges if
+    /// the blank is on one
+    fn moved_tile(&self, next_move: Move) -> usize {
+        let (row, col) = (self.board.zero / C, self.board.zero % C);
+        match next_move {
+            Move::Left => row * C + (col + 1) % C,
+            Move::Up => (row + 1) % R * C + col,
+            Move::Down => (row + R - 1) % R * C + col,
+            Move::Right => row * C + (col + C - 1) % C,
+        }
+    }
+
+    /// The board after the move, which is always possible
+    pub fn apply_move(&self, next_move: Move) -> TorusBoard<R, C> {
+        TorusBoard { board: self.board.swap(self.board.zero, self.moved_tile(next_move)) }
+    }
+
+    /// The successors with their moves, in the order of `Board::successors`. Along a dimension of
+    /// two, both directions slide the same tile, so only the first of them is kept.
+    pub fn successors_with_moves(&self) -> Vec<(Move, TorusBoard<R, C>)> {
+        let mut tiles = Vec::with_capacity(4);
+        let mut successors = Vec::with_capacity(4);
+        for next_move in DEFAULT_ORDER.iter() {
+            let pos = self.moved_tile(*next_move);
+            if !tiles.contains(&pos) {
+                tiles.push(pos);
+                successors.push((*next_move, self.apply_move(*next_move)));
+            }
+        }
+
+        successors
+    }
+
+    pub fn successors(&self) -> Vec<TorusBoard<R, C>> {
+        self.successors_with_moves().into_iter().map(|(_, successor)| successor).collect()
+    }
+
+    /// The manhattan distance from the goal, with each tile going the shorter way around in both
+    /// directions
+    pub fn manhattan_dist(&self) -> i32 {
+        let mut distance = 0;
+        for (pos, tile) in self.board.tiles().iter().enumerate() {
+            if *tile > 0 {
+                let target = *tile as usize - 1;
+                let rows = (pos / C).abs_diff(target / C);
+                let cols = (pos % C).abs_diff(target % C);
+                distance += rows.min(R - rows) + cols.min(C - cols);
+            }
+        }
+
+        distance as i32
+    }
+
+    /// Whether the goal can be reached from the board. Around an odd dimension the blank comes back
+    /// on the same color of a checkerboard with a single swap, which changes the parity of the
+    /// inversions, so every board can be solved. When both dimensions are even, the wrapping moves
+    /// keep the parity like the others, and only the boards of the flat puzzle that can be solved can
+    /// be solved around the torus.
+    pub fn is_solvable(&self) -> bool {
+        R % 2 == 1 || C % 2 == 1 || self.board.is_solvable()
+    }
+}
+
+impl<const R: usize, const C: usize> State for TorusBoard<R, C> {
+    type Action = Move;
+    type Data = ();
+
+    fn successors_with_actions(&self) -> Vec<(Move, Self)> {
+        self.successors_with_moves()
+    }
+
+    fn h(&self) -> i32 {
+        self.manhattan_dist()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::board::GOAL;
+    use crate::search::breadth_first_search;
+
+    use super::*;
+
+    #[test]
+    fn test_wrapping_moves() {
+        let board = TorusBoard::new(Board::new([0, 1, 2, 3, 4, 5, 6, 7, 8]));
+        assert_eq!(*board.apply_move(Move::Right).board(), Board::new([2, 1, 0, 3, 4, 5, 6, 7, 8]));
+        assert_eq!(*board.apply_move(Move::Down).board(), Board::new([6, 1, 2, 3, 4, 5, 0, 7, 8]));
+        assert_eq!(*board.apply_move(Move::Left).board(), Board::new([1, 0, 2, 3, 4, 5, 6, 7, 8]));
+        assert_eq!(board.successors().len(), 4);
+
+        //on two columns, sliding left or right moves the same tile
+        let narrow = TorusBoard::new(Board::<3, 2>::goal());
+        assert_eq!(narrow.successors_with_moves().iter().map(|(next_move, _)| *next_move).collect::<Vec<Move>>(), vec![Move::Left, Move::Up, Move::Down]);
+    }
+
+    #[test]
+    fn test_cyclic_manhattan_dist() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        //no tile is more than one row and one column away around the torus
+        assert_eq!(TorusBoard::new(board).manhattan_dist(), 13);
+        assert!(TorusBoard::new(board).manhattan_dist() <= board.manhattan_dist());
+        assert_eq!(TorusBoard::new(GOAL).manhattan_dist(), 0);
+    }
+
+    #[test]
+    fn test_solvable() {
+        //a single swap of two tiles cannot be undone on the flat board, but can around the torus
+        let swapped = Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]);
+        assert!(TorusBoard::new(swapped).is_solvable());
+        let result = breadth_first_search(&TorusBoard::new(swapped), |state| state.is_goal());
+        assert!(result.plan.is_some());
+
+        let swapped = Board::<2, 4>::try_from([[2, 1, 3, 4], [5, 6, 7, 0]]).unwrap();
+        assert!(!TorusBoard::new(swapped).is_solvable());
+        assert!(breadth_first_search(&TorusBoard::new(swapped), |state| state.is_goal()).plan.is_none());
+        assert!(TorusBoard::new(Board::<2, 4>::try_from([[1, 2, 3, 4], [5, 6, 0, 7]]).unwrap()).is_solvable());
+    }
+}
@@ -0,0 +1,368 @@
+//! Plans found earlier, kept so that a board solved again is answered without searching.
+//!
+//! A board and its mirror image along the diagonal share one entry, under the canonical form of
+//! the board, and the least recently used entries make room for the new ones once the cache is
+//! full. The cache can be saved to a text file:
+//!
+//! ```text
+//! tiles-plan-cache v1
+//! size 3x3
+//! capacity 10000
+//! 1 2 3 4 5 6 7 8 0 | 1 2 3 4 5 0 7 8 6 | U | optimal
+//! ```
+//!
+//! with one entry per line, from the least to the most recently used: the goal, the canonical
+//! board, the moves of the plan from the board to the goal in the notation of `notation`, and
+//! whether the plan is known to be optimal (`optimal`) or not (`any`).
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::iter::once;
+use std::path::Path;
+
+use crate::board::{Board, Move};
+use crate::notation::{format_moves, parse_moves};
+use crate::plan::Plan;
+
+const HEADER: &str = "tiles-plan-cache v1";
+
+/// The number of plans kept by default, a few megabytes for the 8-puzzle
+pub const DEFAULT_CAPACITY: usize = 100_000;
+
+struct Entry {
+    moves: Vec<Move>,
+    cost: u32,
+    //whether the search that found the plan proved it optimal
+    optimal: bool,
+    //when the entry was last used, which orders the entries for eviction
+    used: u64,
+}
+
+/// Plans to the goal keyed by canonical board, evicting the least recently used ones first.
+/// The cache keeps the cheapest plan it was given for every board, and whether it is known to be
+/// optimal, so that the solvers promising optimal plans only take those from the other solvers
+/// sharing the cache when they are.
+pub struct PlanCache<const R: usize = 3, const C: usize = R> {
+    capacity: usize,
+    entries: HashMap<(Board<R, C>, Board<R, C>), Entry>,
+    //the goal and canonical board of every entry, by the time it was last used
+    recency: BTreeMap<u64, (Board<R, C>, Board<R, C>)>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl<const R: usize, const C: usize> PlanCache<R, C> {
+    pub fn new(capacity: usize) -> PlanCache<R, C> {
+        assert!(capacity > 0, "The plan cache needs room for at least one plan.");
+        PlanCache { capacity, entries: HashMap::new(), recency: BTreeMap::new(), clock: 0, hits: 0, misses: 0 }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// How many lookups found a plan, and how many did not
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// The plan from the board to the goal, if the cache has one for the board or its mirror image,
+    /// which has to be known to be optimal when `optimal_only` is set
+    pub fn get(&mut self, board: &Board<R, C>, goal: &Board<R, C>, optimal_only: bool) -> Option<Plan<R, C>> {
+        let key = (*goal, board.canonical_to(goal));
+        let Some(entry) = self.entries.get(&key).filter(|entry| entry.optimal || !optimal_only) else {
+            self.misses += 1;
+            return None;
+        };
+
+        let boards = replay(key.1, &entry.moves)?;
+        self.hits += 1;
+        self.touch(key);
+
+        //the entry is the plan of the mirror image when the board is not canonical
+        if key.1 == *board {
+            Plan::new(boards).ok()
+        } else {
+            Plan::new(boards.iter().map(|board| board.reflected_to(goal).unwrap()).collect()).ok()
+        }
+    }
+
+    /// Whether the cache has a plan for the board or its mirror image, known to be optimal when
+    /// `optimal_only` is set, without counting a lookup or marking the plan as used
+    pub fn contains(&self, board: &Board<R, C>, goal: &Board<R, C>, optimal_only: bool) -> bool {
+        self.entries.get(&(*goal, board.canonical_to(goal))).is_some_and(|entry| entry.optimal || !optimal_only)
+    }
+
+    /// Every board the cache has a plan for to the goal, known to be optimal when `optimal_only` is
+    /// set, with the mirror images of the canonical boards, so that a search can test the boards
+    /// it meets without canonicalising them or holding on to the cache
+    pub fn boards(&self, goal: &Board<R, C>, optimal_only: bool) -> HashSet<Board<R, C>> {
+        self.entries.iter()
+            .filter(|((entry_goal, _), entry)| entry_goal == goal && (entry.optimal || !optimal_only))
+            .flat_map(|((_, board), _)| once(*board).chain(board.reflected_to(goal)))
+            .collect()
+    }
+
+    /// Keeps the plan for its start, unless the cache has a plan as cheap already, in which case a
+    /// plan as cheap that is `optimal` only marks it as optimal. The plan is taken to lead to
+    /// `goal`, and ignored otherwise.
+    pub fn insert(&mut self, plan: &Plan<R, C>, goal: &Board<R, C>, optimal: bool) {
+        if plan.end() != goal {
+            return;
+        }
+
+        let key = (*goal, plan.start().canonical_to(goal));
+        if let Some(entry) = self.entries.get_mut(&key).filter(|entry| entry.cost <= plan.cost()) {
+            entry.optimal |= optimal && entry.cost == plan.cost();
+            self.touch(key);
+            return;
+        }
+
+        let moves = if key.1 == *plan.start() {
+            plan.moves().to_vec()
+        } else {
+            let boards: Vec<Board<R, C>> = plan.boards().iter().map(|board| board.reflected_to(goal).unwrap()).collect();
+            boards.windows(2).map(|step| step[0].move_to(&step[1]).unwrap()).collect()
+        };
+
+        if let Some(previous) = self.entries.insert(key, Entry { moves, cost: plan.cost(), optimal, used: 0 }) {
+            self.recency.remove(&previous.used);
+        }
+        self.touch(key);
+
+        while self.entries.len() > self.capacity {
+            let (_, oldest) = self.recency.pop_first().unwrap();
+            self.entries.remove(&oldest);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    //marks the entry as the most recently used
+    fn touch(&mut self, key: (Board<R, C>, Board<R, C>)) {
+        let entry = self.entries.get_mut(&key).unwrap();
+        self.recency.remove(&entry.used);
+        self.clock += 1;
+        entry.used = self.clock;
+        self.recency.insert(self.clock, key);
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "{}", HEADER)?;
+        writeln!(writer, "size {}x{}", R, C)?;
+        writeln!(writer, "capacity {}", self.capacity)?;
+
+        for (goal, board) in self.recency.values() {
+            let entry = &self.entries[&(*goal, *board)];
+            let optimal = if entry.optimal { "optimal" } else { "any" };
+            writeln!(writer, "{} | {} | {} | {}", tiles(goal), tiles(board), format_moves(&entry.moves), optimal)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a cache written by `write`, which has to be of boards of this size. Every plan is
+    /// checked to lead from its board to its goal.
+    pub fn read<B: BufRead>(reader: B) -> io::Result<PlanCache<R, C>> {
+        let mut lines = reader.lines();
+        if lines.next().transpose()?.as_deref() != Some(HEADER) {
+            return Err(invalid("Not a plan cache file.".to_string()));
+        }
+
+        let size = lines.next().transpose()?.unwrap_or_default();
+        if size != format!("size {}x{}", R, C) {
+            return Err(invalid(format!("Expecting a plan cache of size {}x{}, found '{}'.", R, C, size)));
+        }
+
+        let capacity = lines.next().transpose()?.unwrap_or_default();
+        let capacity = capacity.strip_prefix("capacity ")
+            .and_then(|capacity| capacity.parse().ok())
+            .filter(|capacity| *capacity > 0)
+            .ok_or_else(|| invalid(format!("Invalid capacity: {}", capacity)))?;
+
+        let mut cache = PlanCache::new(capacity);
+        for line in lines {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+            let (goal, board, moves, optimal) = match fields[..] {
+                [goal, board, moves, "optimal"] => (goal, board, moves, true),
+                [goal, board, moves, "any"] => (goal, board, moves, false),
+                _ => return Err(invalid(format!("Expecting a goal, a board, moves and whether they are optimal: {}", line))),
+            };
+            let goal: Board<R, C> = goal.parse().map_err(|e| invalid(format!("Invalid goal {}: {}", goal, e)))?;
+            let board: Board<R, C> = board.parse().map_err(|e| invalid(format!("Invalid board {}: {}", board, e)))?;
+            let moves = parse_moves(moves).map_err(|e| invalid(format!("Invalid moves {}: {}", moves, e)))?;
+
+            match replay(board, &moves) {
+                Some(boards) if *boards.last().unwrap() == goal => cache.insert(&Plan::new(boards).unwrap(), &goal, optimal),
+                _ => return Err(invalid(format!("The moves do not lead to the goal: {}", line))),
+            }
+        }
+
+        Ok(cache)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<PlanCache<R, C>> {
+        PlanCache::read(BufReader::new(File::open(path)?))
+    }
+}
+
+//the boards the moves go through from the board, if they can all be made
+fn replay<const R: usize, const C: usize>(board: Board<R, C>, moves: &[Move]) -> Option<Vec<Board<R, C>>> {
+    let mut boards = vec![board];
+    for next_move in moves {
+        boards.push(boards.last().unwrap().apply_move(*next_move)?);
+    }
+
+    Some(boards)
+}
+
+fn tiles<const R: usize, const C: usize>(board: &Board<R, C>) -> String {
+    let tiles: Vec<String> = board.tiles().iter().map(|tile| tile.to_string()).collect();
+    tiles.join(" ")
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{BLANK_FIRST_GOAL, GOAL};
+
+    use super::*;
+
+    #[test]
+    fn test_mirror_images_share_an_entry() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let mirror = board.reflected_to(&GOAL).unwrap();
+        let plan = crate::a_star_search(board).unwrap();
+
+        let mut cache = PlanCache::new(10);
+        assert!(cache.get(&board, &GOAL, false).is_none());
+        cache.insert(&plan, &GOAL, true);
+        assert_eq!(cache.len(), 1);
+
+        let cached = cache.get(&board, &GOAL, false).unwrap();
+        assert_eq!(cached.boards(), plan.boards());
+        let mirrored = cache.get(&mirror, &GOAL, false).unwrap();
+        assert_eq!((*mirrored.start(), *mirrored.end(), mirrored.cost()), (mirror, GOAL, 31));
+        assert_eq!((cache.hits(), cache.misses()), (2, 1));
+
+        //the goal is part of the key
+        assert!(cache.get(&board, &BLANK_FIRST_GOAL, false).is_none());
+    }
+
+    #[test]
+    fn test_keeps_the_cheapest() {
+        let board = Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8]);
+        let detour = Plan::new(vec![board, Board::new([1, 2, 3, 4, 0, 6, 7, 5, 8]), board, GOAL]).unwrap();
+        let mut cache = PlanCache::new(10);
+
+        cache.insert(&detour, &GOAL, false);
+        cache.insert(&crate::a_star_search(board).unwrap(), &GOAL, true);
+        cache.insert(&detour, &GOAL, false);
+        assert_eq!(cache.get(&board, &GOAL, true).unwrap().cost(), 1);
+
+        //plans to another goal are not kept
+        cache.insert(&detour, &BLANK_FIRST_GOAL, false);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_optimal_only() {
+        let board = Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8]);
+        let plan = crate::a_star_search(board).unwrap();
+        let mut cache = PlanCache::new(10);
+
+        cache.insert(&plan, &GOAL, false);
+        assert!(cache.contains(&board, &GOAL, false));
+        assert!(!cache.contains(&board, &GOAL, true));
+        assert!(cache.get(&board, &GOAL, true).is_none());
+        assert_eq!((cache.hits(), cache.misses()), (0, 1));
+
+        assert!(cache.boards(&GOAL, true).is_empty());
+        assert!(cache.boards(&GOAL, false).contains(&board));
+        assert!(cache.boards(&GOAL, false).contains(&board.reflected_to(&GOAL).unwrap()));
+        assert!(cache.boards(&BLANK_FIRST_GOAL, false).is_empty());
+
+        //the same plan found by an optimal search marks it as optimal
+        cache.insert(&plan, &GOAL, true);
+        assert!(cache.get(&board, &GOAL, true).is_some());
+        cache.insert(&plan, &GOAL, false);
+        assert!(cache.contains(&board, &GOAL, true));
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let boards = [
+            Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8]),
+            Board::new([1, 2, 3, 4, 5, 6, 0, 7, 8]),
+            Board::new([1, 2, 3, 4, 0, 5, 7, 8, 6]),
+        ];
+        let mut cache = PlanCache::new(2);
+        cache.insert(&crate::a_star_search(boards[0]).unwrap(), &GOAL, true);
+        cache.insert(&crate::a_star_search(boards[1]).unwrap(), &GOAL, true);
+        assert!(cache.get(&boards[0], &GOAL, false).is_some());
+        cache.insert(&crate::a_star_search(boards[2]).unwrap(), &GOAL, true);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&boards[1], &GOAL, false).is_none());
+        assert!(cache.get(&boards[0], &GOAL, false).is_some());
+        assert!(cache.get(&boards[2], &GOAL, false).is_some());
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let mut cache = PlanCache::new(5);
+        for tiles in [[8, 6, 7, 2, 5, 4, 3, 0, 1], [1, 2, 3, 4, 5, 6, 7, 0, 8], [1, 2, 3, 4, 5, 6, 7, 8, 0]] {
+            cache.insert(&crate::a_star_search(Board::new(tiles)).unwrap(), &GOAL, true);
+        }
+        let mut written = Vec::new();
+        cache.write(&mut written).unwrap();
+
+        let text = String::from_utf8(written.clone()).unwrap();
+        assert!(text.starts_with("tiles-plan-cache v1\nsize 3x3\ncapacity 5\n"));
+        assert!(text.ends_with("1 2 3 4 5 6 7 8 0 | 1 2 3 4 5 6 7 8 0 |  | optimal\n"));
+
+        let mut read = PlanCache::<3>::read(written.as_slice()).unwrap();
+        assert_eq!((read.len(), read.capacity()), (3, 5));
+        let mut rewritten = Vec::new();
+        read.write(&mut rewritten).unwrap();
+        assert_eq!(rewritten, written);
+        assert_eq!(read.get(&Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]), &GOAL, true).unwrap().cost(), 31);
+
+        let broken = text.clone() + "1 2 3 4 5 6 7 8 0 | 1 2 3 4 5 6 7 0 8 | U | any\n";
+        assert_eq!(PlanCache::<3>::read(broken.as_bytes()).err().unwrap().kind(), ErrorKind::InvalidData);
+        let broken = text + "1 2 3 4 5 6 7 8 0 | 1 2 3 4 5 6 7 0 8 | L\n";
+        assert_eq!(PlanCache::<3>::read(broken.as_bytes()).err().unwrap().kind(), ErrorKind::InvalidData);
+        assert_eq!(PlanCache::<4>::read(written.as_slice()).err().unwrap().kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,137 @@
+//! The time read by the searches for their statistics and limits, as `std::time::Instant` panics
+//! on wasm32-unknown-unknown. There the clock is `performance.now()` from the host with the
+//! `performance-now` feature, and otherwise a counter that only keeps the readings in order.
+//! The `embedded` feature stops the clock on every target, and the time limits have no effect there.
+
+use std::ops::{Add, Sub};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// A source of time that never goes back
+pub trait Clock {
+    /// The time since an arbitrary origin, fixed for the whole process
+    fn now(&self) -> Duration;
+}
+
+/// The clock of the standard library
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SystemClock;
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        static ORIGIN: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        ORIGIN.get_or_init(std::time::Instant::now).elapsed()
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "performance-now"))]
+#[link(wasm_import_module = "env")]
+extern "C" {
+    fn performance_now() -> f64;
+}
+
+/// `performance.now()`, which the host has to provide as the `performance_now` import of the `env`
+/// module, in milliseconds
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "performance-now"))]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PerformanceClock;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "performance-now"))]
+impl Clock for PerformanceClock {
+    fn now(&self) -> Duration {
+        Duration::from_secs_f64(unsafe { performance_now() } / 1000.0)
+    }
+}
+
+/// A counter that moves a microsecond forward on every reading, for targets without a clock.
+/// Durations then count readings rather than time, so time limits become limits on the number of
+/// times the searches check them.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct CounterClock;
+
+impl Clock for CounterClock {
+    fn now(&self) -> Duration {
+        static TICKS: AtomicU64 = AtomicU64::new(0);
+        Duration::from_micros(TICKS.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// A clock that always reads zero, which costs nothing to read
+#[derive(Debug, Default, Copy, Clone)]
+pub struct FrozenClock;
+
+impl Clock for FrozenClock {
+    fn now(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+#[cfg(feature = "embedded")]
+use self::FrozenClock as DefaultClock;
+#[cfg(all(not(feature = "embedded"), not(all(target_arch = "wasm32", target_os = "unknown"))))]
+use self::SystemClock as DefaultClock;
+#[cfg(all(not(feature = "embedded"), target_arch = "wasm32", target_os = "unknown", feature = "performance-now"))]
+use self::PerformanceClock as DefaultClock;
+#[cfg(all(not(feature = "embedded"), target_arch = "wasm32", target_os = "unknown", not(feature = "performance-now")))]
+use self::CounterClock as DefaultClock;
+
+/// A reading of the clock of the target, used like `std::time::Instant`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Instant(Duration);
+
+impl Instant {
+    pub fn now() -> Instant {
+        Instant(DefaultClock.now())
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Instant::now() - *self
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, duration: Duration) -> Instant {
+        Instant(self.0 + duration)
+    }
+}
+
+/// The time between the readings, or zero if the other one is later
+impl Sub for Instant {
+    type Output = Duration;
+
+    fn sub(self, earlier: Instant) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_clock() {
+        let first = CounterClock.now();
+        let second = CounterClock.now();
+        assert!(second >= first + Duration::from_micros(1));
+    }
+
+    #[test]
+    fn test_frozen_clock() {
+        assert_eq!(FrozenClock.now(), Duration::ZERO);
+        assert_eq!(FrozenClock.now(), FrozenClock.now());
+    }
+
+    #[test]
+    fn test_instant() {
+        let start = Instant::now();
+        let later = start + Duration::from_millis(5);
+        assert!(later > start);
+        assert_eq!(later - start, Duration::from_millis(5));
+        assert_eq!(start - later, Duration::ZERO);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::board::{Board, GOAL};
+use crate::heuristic::HeuristicKind;
+use crate::solver::{Algorithm, SolveResult, Solver};
+
+/// The outcome of one algorithm and heuristic on the compared board
+#[derive(Debug, Clone)]
+pub struct ComparisonRow {
+    pub algorithm: Algorithm,
+    pub heuristic: HeuristicKind,
+    pub result: SolveResult,
+    /// Whether the plan found is as short as the optimal one
+    pub optimal: bool,
+}
+
+/// Side by side results of several algorithms and heuristics on the same board
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub board: Board,
+    /// The number of moves in the optimal plan, if the board can be solved
+    pub optimal_steps: Option<usize>,
+    pub rows: Vec<ComparisonRow>,
+}
+
+/// Every combination of the algorithms and heuristics, with breadth first search (which ignores the
+/// heuristic) only included once
+pub fn configurations(algorithms: &[Algorithm], heuristics: &[HeuristicKind]) -> Vec<(Algorithm, HeuristicKind)> {
+    let mut configurations = Vec::new();
+    for algorithm in algorithms {
+        if algorithm.is_informed() {
+            configurations.extend(heuristics.iter().map(|heuristic| (*algorithm, *heuristic)));
+        } else if let Some(heuristic) = heuristics.first() {
+            configurations.push((*algorithm, *heuristic));
+        }
+    }
+
+    configurations
+}
+
+/// Runs each configuration on the board, and marks the plans that are optimal according to an A*
+/// search with the manhattan distance
+pub fn compare(board: Board, configurations: &[(Algorithm, HeuristicKind)]) -> Comparison {
+    compare_to(board, &GOAL, configurations)
+}
+
+/// Same as `compare`, solving towards another goal
+pub fn compare_to(board: Board, goal: &Board, configurations: &[(Algorithm, HeuristicKind)]) -> Comparison {
+    let optimal_steps = Solver::new(Algorithm::AStar).with_goal(*goal).solve(board).map(|plan| plan.steps());
+
+    let rows = configurations.iter()
+        .map(|(algorithm, heuristic)| {
+            let result = Solver::new(*algorithm).with_heuristic(*heuristic).with_goal(*goal).run(board);
+            let optimal = result.plan.as_ref().map(|plan| plan.steps()) == optimal_steps && optimal_steps.is_some();
+            ComparisonRow { algorithm: *algorithm, heuristic: *heuristic, result, optimal }
+        })
+        .collect();
+
+    Comparison { board, optimal_steps, rows }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<14} {:<10} {:>6} {:>9} {:>9} {:>6} {:>12} {:>12}", "Algorithm", "Heuristic", "Steps", "Created", "Expanded", "b*", "Memory (kB)", "Time")?;
+        for row in self.rows.iter() {
+            let heuristic = if row.algorithm.is_informed() { row.heuristic.name() } else { "-" };
+            let steps = match &row.result.plan {
+                Some(plan) if row.optimal => format!("{}*", plan.steps()),
+                Some(plan) => plan.steps().to_string(),
+                None => "-".to_string(),
+            };
+            let statistics = &row.result.statistics;
+            let branching_factor = statistics.branching_factor.map_or("-".to_string(), |b| format!("{:.3}", b));
+
+            writeln!(f, "{:<14} {:<10} {:>6} {:>9} {:>9} {:>6} {:>12} {:>12}",
+                     row.algorithm.name(),
+                     heuristic,
+                     steps,
+                     statistics.created,
+                     statistics.expanded,
+                     branching_factor,
+                     statistics.memory / 1024,
+                     format!("{:.2?}", statistics.duration))?;
+        }
+
+        write!(f, "* optimal")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configurations() {
+        let configurations = configurations(&Algorithm::all(), &HeuristicKind::all());
+
+        //breadth first search once, and every other algorithm with both heuristics
+        assert_eq!(configurations.len(), 9);
+        assert_eq!(configurations[0], (Algorithm::BreadthFirst, HeuristicKind::Manhattan));
+    }
+
+    #[test]
+    fn test_compare() {
+        let board = Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]);
+        let comparison = compare(board, &configurations(&Algorithm::all(), &HeuristicKind::all()));
+
+        assert_eq!(comparison.rows.len(), 9);
+        for row in comparison.rows.iter() {
+            let optimal_algorithm = row.algorithm == Algorithm::AStar || row.algorithm == Algorithm::BreadthFirst;
+            if optimal_algorithm {
+                assert!(row.optimal);
+            }
+            assert!(row.result.plan.is_some());
+        }
+
+        let table = comparison.to_string();
+        assert!(table.starts_with("Algorithm"));
+        assert_eq!(table.lines().count(), 11);
+    }
+
+    #[test]
+    fn test_compare_to_blank_first() {
+        let board = Board::new([1, 2, 0, 3, 4, 5, 6, 7, 8]);
+        let comparison = compare_to(board, &crate::board::BLANK_FIRST_GOAL, &[(Algorithm::AStar, HeuristicKind::Manhattan)]);
+
+        assert_eq!(comparison.optimal_steps, Some(2));
+        assert!(comparison.rows[0].optimal);
+    }
+
+    #[test]
+    fn test_compare_unsolvable() {
+        let board = Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]);
+        let comparison = compare(board, &[(Algorithm::AStar, HeuristicKind::Manhattan)]);
+
+        assert_eq!(comparison.optimal_steps, None);
+        assert!(!comparison.rows[0].optimal);
+    }
+}
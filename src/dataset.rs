@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::board::{Board, GOAL};
+use crate::oracle::OracleTable;
+use crate::solver::UnknownName;
+
+/// How the boards of a dataset are picked
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Sampling {
+    /// The board reached after this number of random moves from the goal
+    RandomWalk(u32),
+    /// Any solvable board, with equal probability
+    Uniform,
+}
+
+impl FromStr for Sampling {
+    type Err = UnknownName;
+
+    /// `uniform`, or `walk-N` for random walks of N moves
+    fn from_str(name: &str) -> Result<Sampling, UnknownName> {
+        match name {
+            "uniform" => Ok(Sampling::Uniform),
+            _ => name.strip_prefix("walk-")
+                .and_then(|length| length.parse().ok())
+                .map(Sampling::RandomWalk)
+                .ok_or_else(|| UnknownName(name.to_string())),
+        }
+    }
+}
+
+impl Display for Sampling {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Sampling::RandomWalk(length) => write!(f, "walk-{}", length),
+            Sampling::Uniform => write!(f, "uniform"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Format {
+    /// A header line, then the 9 tiles and the cost of every board, separated by commas
+    Csv,
+    /// One `{"tiles": [...], "cost": n}` object per line
+    Jsonl,
+}
+
+/// A board together with the cost of its optimal plan to the goal
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LabelledBoard {
+    pub board: Board,
+    pub cost: u32,
+}
+
+/// Samples boards and labels them with their exact distance to the goal.
+/// The distances of all the boards are looked up in an `OracleTable`, so labelling a board is a
+/// single lookup rather than an A* search.
+pub struct DatasetGenerator {
+    oracle: OracleTable,
+    sampling: Sampling,
+    rng: StdRng,
+}
+
+impl DatasetGenerator {
+    /// Creates a generator whose samples are reproducible for the same seed, with an oracle table
+    /// built for it
+    pub fn new(sampling: Sampling, seed: u64) -> DatasetGenerator {
+        DatasetGenerator::with_oracle(sampling, seed, OracleTable::build())
+    }
+
+    /// Creates a generator that labels its samples from the given table, such as one mapped from
+    /// a file shared with other processes
+    pub fn with_oracle(sampling: Sampling, seed: u64, oracle: OracleTable) -> DatasetGenerator {
+        DatasetGenerator { oracle, sampling, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    pub fn sampling(&self) -> Sampling {
+        self.sampling
+    }
+
+    pub fn sample(&mut self) -> LabelledBoard {
+        let board = match self.sampling {
+            Sampling::RandomWalk(length) => self.random_walk(length),
+            Sampling::Uniform => self.uniform(),
+        };
+
+        let cost = self.oracle.distance(&board).expect("Every solvable board is in the oracle table.");
+        LabelledBoard { board, cost }
+    }
+
+    fn random_walk(&mut self, length: u32) -> Board {
+        let mut board = GOAL;
+        for _ in 0..length {
+            let successors = board.successors();
+            board = successors[self.rng.gen_range(0..successors.len())];
+        }
+
+        board
+    }
+
+    fn uniform(&mut self) -> Board {
+        Board::random(&mut self.rng)
+    }
+}
+
+impl Iterator for DatasetGenerator {
+    type Item = LabelledBoard;
+
+    fn next(&mut self) -> Option<LabelledBoard> {
+        Some(self.sample())
+    }
+}
+
+/// Writes the samples in the given format, returning how many were written
+pub fn write_dataset<W: Write, I: IntoIterator<Item = LabelledBoard>>(writer: &mut W, samples: I, format: Format) -> io::Result<usize> {
+    if format == Format::Csv {
+        writeln!(writer, "t0,t1,t2,t3,t4,t5,t6,t7,t8,cost")?;
+    }
+
+    let mut count = 0;
+    for sample in samples {
+        let tiles: Vec<String> = sample.board.tiles().iter().map(|tile| tile.to_string()).collect();
+        match format {
+            Format::Csv => writeln!(writer, "{},{}", tiles.join(","), sample.cost)?,
+            Format::Jsonl => writeln!(writer, "{{\"tiles\":[{}],\"cost\":{}}}", tiles.join(","), sample.cost)?,
+        }
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// How many samples have each optimal plan length, to check that a generator produces boards of
+/// the difficulty it is meant to
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct LengthHistogram {
+    counts: BTreeMap<u32, usize>,
+}
+
+impl LengthHistogram {
+    pub fn new<I: IntoIterator<Item = LabelledBoard>>(samples: I) -> LengthHistogram {
+        let mut counts = BTreeMap::new();
+        for sample in samples {
+            *counts.entry(sample.cost).or_insert(0) += 1;
+        }
+
+        LengthHistogram { counts }
+    }
+
+    /// The number of samples of each length, in increasing order of length
+    pub fn counts(&self) -> &BTreeMap<u32, usize> {
+        &self.counts
+    }
+
+    pub fn count(&self, length: u32) -> usize {
+        self.counts.get(&length).copied().unwrap_or(0)
+    }
+
+    /// The number of samples
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// The share of the samples whose length is in the range, 0 when there are no samples
+    pub fn fraction_within(&self, lengths: RangeInclusive<u32>) -> f64 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+
+        self.counts.range(lengths).map(|(_, count)| count).sum::<usize>() as f64 / total as f64
+    }
+}
+
+impl Display for LengthHistogram {
+    /// One line per length, with its count and a bar proportional to it
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let highest = self.counts.values().copied().max().unwrap_or(0);
+        for (length, count) in &self.counts {
+            let bar = "#".repeat((count * 50).div_ceil(highest));
+            writeln!(f, "{:3} {:8} {}", length, count, bar)?;
+        }
+
+        write!(f, "({} samples)", self.total())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_walk_labels() {
+        let mut generator = DatasetGenerator::new(Sampling::RandomWalk(10), 7);
+
+        for sample in generator.by_ref().take(20) {
+            assert!(sample.cost <= 10);
+            assert_eq!(sample.cost % 2, 0);
+            assert_eq!(sample.cost as usize, crate::a_star_search(sample.board).unwrap().steps());
+        }
+    }
+
+    #[test]
+    fn test_uniform_samples_are_solvable() {
+        let generator = DatasetGenerator::new(Sampling::Uniform, 7);
+
+        for sample in generator.take(100) {
+            assert!(sample.board.is_solvable());
+            //the longest optimal plan of the 8-puzzle
+            assert!(sample.cost <= 31);
+        }
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let first: Vec<LabelledBoard> = DatasetGenerator::new(Sampling::Uniform, 42).take(10).collect();
+        let second: Vec<LabelledBoard> = DatasetGenerator::with_oracle(Sampling::Uniform, 42, OracleTable::build()).take(10).collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_length_histogram() {
+        let histogram = LengthHistogram::new(DatasetGenerator::new(Sampling::RandomWalk(6), 7).take(200));
+
+        assert_eq!(histogram.total(), 200);
+        assert!(histogram.counts().keys().all(|length| *length <= 6 && length % 2 == 0));
+        assert_eq!(histogram.fraction_within(0..=6), 1.0);
+        assert_eq!(histogram.count(1), 0);
+        assert!(histogram.to_string().ends_with("(200 samples)"));
+
+        let uniform = LengthHistogram::new(DatasetGenerator::new(Sampling::Uniform, 7).take(200));
+        //most solvable boards are between 18 and 26 moves away from the goal
+        assert!(uniform.fraction_within(18..=26) > 0.8);
+        assert_eq!(LengthHistogram::default().fraction_within(0..=31), 0.0);
+    }
+
+    #[test]
+    fn test_parse_sampling() {
+        assert_eq!("uniform".parse(), Ok(Sampling::Uniform));
+        assert_eq!("walk-20".parse(), Ok(Sampling::RandomWalk(20)));
+        assert!("walk".parse::<Sampling>().is_err());
+        assert_eq!(Sampling::RandomWalk(20).to_string().parse(), Ok(Sampling::RandomWalk(20)));
+    }
+
+    #[test]
+    fn test_write_formats() {
+        let samples = vec![LabelledBoard { board: GOAL, cost: 0 }];
+
+        let mut csv = Vec::new();
+        assert_eq!(write_dataset(&mut csv, samples.clone(), Format::Csv).unwrap(), 1);
+        assert_eq!(String::from_utf8(csv).unwrap(), "t0,t1,t2,t3,t4,t5,t6,t7,t8,cost\n1,2,3,4,5,6,7,8,0,0\n");
+
+        let mut jsonl = Vec::new();
+        write_dataset(&mut jsonl, samples, Format::Jsonl).unwrap();
+        assert_eq!(String::from_utf8(jsonl).unwrap(), "{\"tiles\":[1,2,3,4,5,6,7,8,0],\"cost\":0}\n");
+    }
+}
@@ -0,0 +1,269 @@
+//! Weighted A* with several weights at once, one thread each, sharing the cheapest plan found so
+//! far so that every thread prunes the boards that cannot lead to a cheaper one.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use crate::board::Board;
+use crate::clock::Instant;
+use crate::plan::Plan;
+use crate::search::{effective_branching_factor, CancellationToken, Limits, Statistics};
+
+/// Optimal A* first, then weights that find (longer) plans sooner
+pub const DEFAULT_WEIGHTS: [f64; 4] = [1.0, 1.5, 2.0, 4.0];
+
+//the priorities are kept as integers, with this many steps per move
+pub(crate) const PRIORITY_SCALE: f64 = 1024.0;
+
+/// The best plan found by the ensemble
+#[derive(Debug, Clone)]
+pub struct EnsembleResult<const R: usize = 3, const C: usize = R> {
+    /// The cheapest plan found by any of the threads before the deadline
+    pub plan: Option<Plan<R, C>>,
+    /// Whether a thread went through every board that could lead to a cheaper plan, which proves
+    /// the plan optimal (or that there is none) when the heuristic is admissible
+    pub exhausted: bool,
+}
+
+/// The cheapest plan found so far, shared by the threads
+pub(crate) struct Incumbent<const R: usize, const C: usize> {
+    cost: AtomicU32,
+    boards: Mutex<Option<Vec<Board<R, C>>>>,
+}
+
+impl<const R: usize, const C: usize> Incumbent<R, C> {
+    pub(crate) fn new() -> Incumbent<R, C> {
+        Incumbent { cost: AtomicU32::new(u32::MAX), boards: Mutex::new(None) }
+    }
+
+    pub(crate) fn cost(&self) -> u32 {
+        self.cost.load(Ordering::SeqCst)
+    }
+
+    /// Keeps the plan if it is cheaper than the current one, returning whether it was
+    fn offer(&self, boards: Vec<Board<R, C>>) -> bool {
+        let cost = boards.len() as u32 - 1;
+        let mut incumbent = self.boards.lock().unwrap();
+        if cost < self.cost() {
+            self.cost.store(cost, Ordering::SeqCst);
+            *incumbent = Some(boards);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The cheapest plan, with the statistics of the searches that found it
+    pub(crate) fn into_plan(self, statistics: Statistics) -> Option<Plan<R, C>> {
+        self.boards.into_inner().unwrap().map(|boards| {
+            let statistics = Statistics { branching_factor: effective_branching_factor(statistics.created as usize, boards.len() - 1), ..statistics };
+            Plan::from_search(boards, statistics)
+        })
+    }
+}
+
+struct Node<const R: usize, const C: usize> {
+    board: Board<R, C>,
+    parent: Option<usize>,
+    g: u32,
+    h: u32,
+}
+
+/// Runs weighted A* (f = g + weight × h) with every weight on its own thread until the deadline,
+/// and returns the cheapest plan any of them found.
+/// Once a plan is found, every thread skips the boards whose g + h is not below its cost, so with
+/// an admissible heuristic the search of any weight ends once nothing cheaper is left, and the
+/// other threads are stopped as the plan is then optimal.
+/// With the `embedded` feature, which has no clock, the deadline has no effect.
+pub fn weighted_a_star_ensemble<const R: usize, const C: usize, H>(board: Board<R, C>, heuristic: H, weights: &[f64], deadline: Duration) -> EnsembleResult<R, C>
+    where H: Fn(&Board<R, C>) -> i32 + Sync
+{
+    assert!(!weights.is_empty(), "The ensemble needs at least one weight.");
+    assert!(weights.iter().all(|weight| *weight >= 1.0), "The weights cannot be lower than 1: {:?}", weights);
+
+    let incumbent = Incumbent::new();
+    let stop = CancellationToken::new();
+    let limits = Limits { max_duration: Some(deadline), cancel: Some(stop.clone()), ..Limits::none() };
+
+    let outcomes: Vec<(Statistics, bool)> = thread::scope(|scope| {
+        let threads: Vec<_> = weights.iter()
+            .map(|weight| {
+                let (heuristic, incumbent, limits, stop) = (&heuristic, &incumbent, limits.clone(), &stop);
+                scope.spawn(move || {
+                    let outcome = weighted_a_star(board, heuristic, || *weight, incumbent, &limits, |_, _| {});
+                    if outcome.1 {
+                        stop.cancel();
+                    }
+                    outcome
+                })
+            })
+            .collect();
+
+        threads.into_iter().map(|thread| thread.join().unwrap()).collect()
+    });
+
+    let exhausted = outcomes.iter().any(|(_, exhausted)| *exhausted);
+    let statistics = outcomes.iter().skip(1).fold(outcomes[0].0.clone(), |total, (statistics, _)| total.combine(statistics));
+    EnsembleResult { plan: incumbent.into_plan(statistics), exhausted }
+}
+
+//how many expansions go by between two readings of the weight
+const WEIGHT_INTERVAL: i32 = 256;
+
+/// Weighted A* pruned by the incumbent, returning its statistics and whether it went through every
+/// board that could still lead to a cheaper plan.
+/// The weight is read again every few hundred expansions, and the queued boards are reordered when
+/// it changes. Every plan this search makes the incumbent is passed to `on_improvement`, with the
+/// statistics of the search so far.
+pub(crate) fn weighted_a_star<const R: usize, const C: usize, H, W, F>(board: Board<R, C>, heuristic: &H, weight: W, incumbent: &Incumbent<R, C>, limits: &Limits, mut on_improvement: F) -> (Statistics, bool)
+    where H: Fn(&Board<R, C>) -> i32,
+          W: Fn() -> f64,
+          F: FnMut(&[Board<R, C>], &Statistics),
+{
+    let start = Instant::now();
+    let mut statistics = Statistics::initial();
+    let mut current_weight = weight();
+    let priority = |weight: f64, g: u32, h: u32| ((g as f64 + weight * h as f64) * PRIORITY_SCALE) as u64;
+
+    let h = heuristic(&board).max(0) as u32;
+    let mut nodes = vec![Node { board, parent: None, g: 0, h }];
+    let mut best_g = HashMap::new();
+    best_g.insert(board, 0);
+    //ties are broken in favour of the deepest node, then the oldest
+    let mut open = BinaryHeap::new();
+    open.push(Reverse((priority(current_weight, 0, h), Reverse(0), 0)));
+
+    let mut exhausted = true;
+    let mut bound = incumbent.cost();
+    while let Some(Reverse((_, _, index))) = open.pop() {
+        if limits.reached(&statistics, start) {
+            exhausted = false;
+            break;
+        }
+
+        if statistics.expanded % WEIGHT_INTERVAL == 0 && weight() != current_weight {
+            current_weight = weight();
+            open = open.into_iter()
+                .map(|Reverse((_, g, index))| Reverse((priority(current_weight, nodes[index].g, nodes[index].h), g, index)))
+                .collect();
+        }
+
+        //drop the queued nodes a cheaper plan made useless right away, rather than one by one
+        if incumbent.cost() < bound {
+            bound = incumbent.cost();
+            let queued = open.len();
+            open.retain(|Reverse((_, _, index))| nodes[*index].g + nodes[*index].h < bound);
+            statistics.count_purged((queued - open.len()) as i32);
+        }
+
+        let Node { board, g, h, .. } = nodes[index];
+        //skip nodes reached again with a lower cost, and those that cannot beat the incumbent
+        if g > best_g[&board] || g + h >= incumbent.cost() {
+            continue;
+        }
+
+        if board.is_goal() {
+            let boards = path(&nodes, index);
+            if incumbent.offer(boards.clone()) {
+                statistics.set_seen(best_g.len());
+                statistics.duration = start.elapsed();
+                on_improvement(&boards, &statistics);
+            }
+            continue;
+        }
+
+        statistics.expanded += 1;
+        for successor in board.successors() {
+            let g = g + 1;
+            if best_g.get(&successor).is_some_and(|best| *best <= g) {
+                continue;
+            }
+
+            statistics.count_created(1);
+            best_g.insert(successor, g);
+            let h = heuristic(&successor).max(0) as u32;
+            if g + h < incumbent.cost() {
+                statistics.count_queued(1);
+                open.push(Reverse((priority(current_weight, g, h), Reverse(g), nodes.len())));
+                nodes.push(Node { board: successor, parent: Some(index), g, h });
+            }
+        }
+    }
+
+    statistics.set_seen(best_g.len());
+    statistics.memory = nodes.len() * size_of::<Node<R, C>>() + best_g.len() * size_of::<(Board<R, C>, u32)>();
+    statistics.duration = start.elapsed();
+    (statistics, exhausted)
+}
+
+fn path<const R: usize, const C: usize>(nodes: &[Node<R, C>], mut index: usize) -> Vec<Board<R, C>> {
+    let mut boards = vec![nodes[index].board];
+    while let Some(parent) = nodes[index].parent {
+        boards.push(nodes[parent].board);
+        index = parent;
+    }
+
+    boards.reverse();
+    boards
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::GOAL;
+    use crate::manhattan_heuristic;
+
+    use super::*;
+
+    #[test]
+    fn test_optimal_when_exhausted() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let result = weighted_a_star_ensemble(board, manhattan_heuristic, &DEFAULT_WEIGHTS, Duration::from_secs(60));
+
+        assert!(result.exhausted);
+        let plan = result.plan.unwrap();
+        assert_eq!(plan.steps(), 31);
+        assert_eq!(*plan.start(), board);
+        assert_eq!(*plan.end(), GOAL);
+        assert!(plan.statistics().unwrap().expanded > 0);
+    }
+
+    #[test]
+    fn test_single_heavy_weight() {
+        let board = Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]);
+        let result = weighted_a_star_ensemble(board, manhattan_heuristic, &[4.0], Duration::from_secs(60));
+
+        //the heavy weight finds a plan first, then keeps pruning with it until none is cheaper
+        assert!(result.exhausted);
+        let plan = result.plan.unwrap();
+        assert_eq!(plan.steps(), 31);
+        assert!(plan.statistics().unwrap().purged > 0 || cfg!(feature = "embedded"));
+    }
+
+    //timed on the wall clock, which a loaded machine can throw off, so only run when asked for with
+    //`cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    #[cfg(not(feature = "embedded"))]
+    fn test_deadline_on_large_board() {
+        let board: Board<4> = "5 1 3 4 / 9 2 7 8 / 13 6 10 12 / 14 0 11 15".parse().unwrap();
+        let start = Instant::now();
+        let result = weighted_a_star_ensemble(board, manhattan_heuristic, &DEFAULT_WEIGHTS, Duration::from_millis(200));
+
+        assert!(start.elapsed() < Duration::from_secs(30));
+        assert!(result.plan.unwrap().end().is_goal());
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let board = Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]);
+        let result = weighted_a_star_ensemble(board, manhattan_heuristic, &[1.0, 2.0], Duration::from_secs(60));
+
+        assert!(result.exhausted);
+        assert!(result.plan.is_none());
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::board::Board;
+use crate::heuristic::HeuristicKind;
+use crate::schedule::{solve_scheduled, ScheduleDecision};
+use crate::solver::{Algorithm, SolveResult, Solver};
+
+/// One algorithm and heuristic solving one instance of an experiment
+#[derive(Debug, Clone)]
+pub struct Run {
+    pub algorithm: Algorithm,
+    pub heuristic: HeuristicKind,
+    /// Index of the instance, so that runs on the same board can be compared
+    pub instance: usize,
+    pub result: SolveResult,
+    /// The time budget of the run, when the instances were scheduled
+    pub decision: Option<ScheduleDecision>,
+}
+
+/// Summary of all the runs of one algorithm and heuristic
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub algorithm: Algorithm,
+    pub heuristic: HeuristicKind,
+    pub runs: usize,
+    pub solved: usize,
+    /// Plan lengths, over the solved instances only
+    pub mean_steps: f64,
+    pub median_steps: f64,
+    pub mean_expanded: f64,
+    pub median_expanded: f64,
+    pub mean_millis: f64,
+    pub median_millis: f64,
+    /// IPC quality score: the sum over the instances of the shortest plan found by any
+    /// configuration divided by the length of this plan, with unsolved instances scoring 0
+    pub quality_score: f64,
+}
+
+/// Runs collected over many instances and configurations
+#[derive(Debug, Clone, Default)]
+pub struct Experiment {
+    runs: Vec<Run>,
+}
+
+impl Experiment {
+    pub fn new() -> Experiment {
+        Experiment { runs: Vec::new() }
+    }
+
+    /// Solves every board with every configuration, each configuration solving the boards in
+    /// parallel with the limits and the queue of the template solver
+    pub fn run(boards: &[Board], configurations: &[(Algorithm, HeuristicKind)], template: &Solver) -> Experiment {
+        let mut experiment = Experiment::new();
+        for (algorithm, heuristic) in configurations {
+            for (instance, result) in configure(template, *algorithm, *heuristic).solve_all(boards).into_iter().enumerate() {
+                experiment.record(Run { algorithm: *algorithm, heuristic: *heuristic, instance, result, decision: None });
+            }
+        }
+
+        experiment
+    }
+
+    /// Solves every board with every configuration like `run`, but with each configuration
+    /// solving the boards one after the other within `total`, with the budgets of `solve_scheduled`
+    pub fn run_scheduled(boards: &[Board], configurations: &[(Algorithm, HeuristicKind)], template: &Solver, total: Duration) -> Experiment {
+        let mut experiment = Experiment::new();
+        for (algorithm, heuristic) in configurations {
+            for (instance, scheduled) in solve_scheduled(&configure(template, *algorithm, *heuristic), boards, total).into_iter().enumerate() {
+                experiment.record(Run { algorithm: *algorithm, heuristic: *heuristic, instance, result: scheduled.result, decision: Some(scheduled.decision) });
+            }
+        }
+
+        experiment
+    }
+
+    pub fn record(&mut self, run: Run) {
+        self.runs.push(run);
+    }
+
+    pub fn runs(&self) -> &[Run] {
+        &self.runs
+    }
+
+    /// A summary per configuration, in the order in which the configurations were first recorded
+    pub fn summarize(&self) -> Vec<Summary> {
+        let mut best_steps: HashMap<usize, usize> = HashMap::new();
+        for run in self.runs.iter() {
+            if let Some(plan) = &run.result.plan {
+                let best = best_steps.entry(run.instance).or_insert(plan.steps());
+                *best = (*best).min(plan.steps());
+            }
+        }
+
+        let mut configurations: Vec<(Algorithm, HeuristicKind)> = Vec::new();
+        for run in self.runs.iter() {
+            if !configurations.contains(&(run.algorithm, run.heuristic)) {
+                configurations.push((run.algorithm, run.heuristic));
+            }
+        }
+
+        configurations.iter()
+            .map(|(algorithm, heuristic)| {
+                let runs: Vec<&Run> = self.runs.iter()
+                    .filter(|run| run.algorithm == *algorithm && run.heuristic == *heuristic)
+                    .collect();
+                summarize(*algorithm, *heuristic, &runs, &best_steps)
+            })
+            .collect()
+    }
+}
+
+fn configure(template: &Solver, algorithm: Algorithm, heuristic: HeuristicKind) -> Solver {
+    Solver::new(algorithm).with_heuristic(heuristic).with_limits(template.limits().clone()).with_queue(template.queue()).with_symmetry(template.symmetry())
+}
+
+fn summarize(algorithm: Algorithm, heuristic: HeuristicKind, runs: &[&Run], best_steps: &HashMap<usize, usize>) -> Summary {
+    let steps: Vec<f64> = runs.iter()
+        .filter_map(|run| run.result.plan.as_ref())
+        .map(|plan| plan.steps() as f64)
+        .collect();
+    let expanded: Vec<f64> = runs.iter().map(|run| run.result.statistics.expanded as f64).collect();
+    let millis: Vec<f64> = runs.iter().map(|run| run.result.statistics.duration.as_secs_f64() * 1000.0).collect();
+
+    let quality_score = runs.iter()
+        .filter_map(|run| run.result.plan.as_ref().map(|plan| (run.instance, plan.steps())))
+        .map(|(instance, steps)| if steps == 0 { 1.0 } else { best_steps[&instance] as f64 / steps as f64 })
+        .sum();
+
+    Summary {
+        algorithm,
+        heuristic,
+        runs: runs.len(),
+        solved: steps.len(),
+        mean_steps: mean(&steps),
+        median_steps: median(&steps),
+        mean_expanded: mean(&expanded),
+        median_expanded: median(&expanded),
+        mean_millis: mean(&millis),
+        median_millis: median(&millis),
+        quality_score,
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let middle = sorted.len() / 2;
+    if sorted.len() % 2 == 1 {
+        sorted[middle]
+    } else {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    }
+}
+
+const CSV_HEADER: &str = "algorithm,heuristic,runs,solved,mean_steps,median_steps,mean_expanded,median_expanded,mean_millis,median_millis,quality_score";
+
+pub fn write_csv<W: Write>(writer: &mut W, summaries: &[Summary]) -> io::Result<()> {
+    writeln!(writer, "{}", CSV_HEADER)?;
+    for summary in summaries {
+        writeln!(writer, "{},{},{},{},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3}",
+                 summary.algorithm, summary.heuristic, summary.runs, summary.solved,
+                 summary.mean_steps, summary.median_steps, summary.mean_expanded, summary.median_expanded,
+                 summary.mean_millis, summary.median_millis, summary.quality_score)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the summaries as a JSON array, one object per configuration
+pub fn write_json<W: Write>(writer: &mut W, summaries: &[Summary]) -> io::Result<()> {
+    let objects: Vec<String> = summaries.iter()
+        .map(|summary| format!(
+            "{{\"algorithm\":\"{}\",\"heuristic\":\"{}\",\"runs\":{},\"solved\":{},\"mean_steps\":{:.3},\"median_steps\":{:.3},\
+\"mean_expanded\":{:.3},\"median_expanded\":{:.3},\"mean_millis\":{:.3},\"median_millis\":{:.3},\"quality_score\":{:.3}}}",
+            summary.algorithm, summary.heuristic, summary.runs, summary.solved,
+            summary.mean_steps, summary.median_steps, summary.mean_expanded, summary.median_expanded,
+            summary.mean_millis, summary.median_millis, summary.quality_score))
+        .collect();
+
+    writeln!(writer, "[{}]", objects.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compare::configurations;
+
+    use super::*;
+
+    fn boards() -> Vec<Board> {
+        vec![
+            Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]),
+            Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]),
+            Board::new([1, 2, 3, 0, 4, 6, 7, 5, 8]),
+        ]
+    }
+
+    #[test]
+    fn test_summarize() {
+        let configurations = configurations(&[Algorithm::AStar, Algorithm::GreedyBestFirst], &[HeuristicKind::Manhattan]);
+        let experiment = Experiment::run(&boards(), &configurations, &Solver::new(Algorithm::AStar));
+        let summaries = experiment.summarize();
+
+        assert_eq!(experiment.runs().len(), 6);
+        assert_eq!(summaries.len(), 2);
+
+        let a_star = &summaries[0];
+        assert_eq!(a_star.algorithm, Algorithm::AStar);
+        assert_eq!(a_star.solved, 3);
+        //A* is optimal, so it gets the full score on every instance
+        assert!((a_star.quality_score - 3.0).abs() < 1e-9);
+        assert!(summaries[1].quality_score <= 3.0);
+    }
+
+    #[test]
+    fn test_run_scheduled() {
+        let experiment = Experiment::run_scheduled(&boards(), &[(Algorithm::AStar, HeuristicKind::Manhattan)], &Solver::new(Algorithm::AStar), Duration::from_secs(60));
+
+        assert_eq!(experiment.summarize()[0].solved, 3);
+        assert!(experiment.runs().iter().all(|run| run.decision.as_ref().is_some_and(|decision| decision.instance == run.instance)));
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(&[4.0, 1.0, 2.0, 3.0]), 2.5);
+        assert_eq!(median(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_write() {
+        let experiment = Experiment::run(&boards()[2..], &[(Algorithm::AStar, HeuristicKind::Manhattan)], &Solver::new(Algorithm::AStar));
+        let summaries = experiment.summarize();
+
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &summaries).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.lines().nth(1).unwrap().starts_with("astar,manhattan,1,1,"));
+
+        let mut json = Vec::new();
+        write_json(&mut json, &summaries).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.starts_with("[{\"algorithm\":\"astar\",\"heuristic\":\"manhattan\",\"runs\":1,\"solved\":1,"));
+    }
+}
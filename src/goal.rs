@@ -0,0 +1,244 @@
+use std::str::FromStr;
+
+use crate::board::{position_dist, Board, BoardError, ParseBoardError, Tile};
+
+/// A set of acceptable goal configurations, where reaching any one of them solves the puzzle.
+#[derive(Debug, Clone)]
+pub struct GoalSet<const R: usize = 3, const C: usize = R> {
+    goals: Vec<Board<R, C>>,
+    //for every tile, the positions it occupies across all the goals
+    tile_positions: Vec<Vec<usize>>,
+}
+
+impl<const R: usize, const C: usize> GoalSet<R, C> {
+    pub fn new(goals: Vec<Board<R, C>>) -> GoalSet<R, C> {
+        assert!(!goals.is_empty(), "A goal set needs at least one goal configuration.");
+
+        let mut tile_positions = vec![Vec::new(); R * C];
+        for goal in goals.iter() {
+            for (tile, position) in goal.positions().iter().enumerate() {
+                let positions = &mut tile_positions[tile];
+                if !positions.contains(position) {
+                    positions.push(*position);
+                }
+            }
+        }
+
+        GoalSet { goals, tile_positions }
+    }
+
+    pub fn single(goal: Board<R, C>) -> GoalSet<R, C> {
+        GoalSet::new(vec![goal])
+    }
+
+    pub fn goals(&self) -> &[Board<R, C>] {
+        &self.goals
+    }
+
+    pub fn contains(&self, board: &Board<R, C>) -> bool {
+        self.goals.contains(board)
+    }
+
+    /// The manhattan distance to the closest goal in the set
+    pub fn manhattan_dist(&self, board: &Board<R, C>) -> i32 {
+        self.goals.iter()
+            .map(|goal| board.manhattan_dist_to(goal))
+            .min()
+            .unwrap()
+    }
+
+    /// The number of displaced tiles with respect to the closest goal in the set
+    pub fn displaced_tiles(&self, board: &Board<R, C>) -> i32 {
+        self.goals.iter()
+            .map(|goal| board.displaced_tiles_to(goal))
+            .min()
+            .unwrap()
+    }
+
+    /// Relaxed pattern heuristic, which lets each tile move to the nearest position it occupies in
+    /// any of the goals, independently of the other tiles. Less informed than the minimum over the
+    /// goals, but its cost does not grow with the size of the goal set.
+    pub fn pattern_dist(&self, board: &Board<R, C>) -> i32 {
+        let mut distance = 0;
+        for (tile, index) in board.positions().iter().enumerate().skip(1) {
+            distance += self.tile_positions[tile].iter()
+                .map(|position| position_dist::<R, C>(*index, *position))
+                .min()
+                .unwrap();
+        }
+
+        distance
+    }
+}
+
+/// A goal where only some positions have to hold a given tile (or the blank), while the others can
+/// hold any tile, such as "the top row reads 1 2 3". Written like a board, with `*` for the
+/// positions that do not matter.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GoalPattern<const R: usize = 3, const C: usize = R> {
+    cells: Vec<Option<Tile>>,
+    //the numbered tiles the pattern places, with their positions
+    targets: Vec<(Tile, usize)>,
+}
+
+impl<const R: usize, const C: usize> GoalPattern<R, C> {
+    /// The pattern with the tile expected at every position in reading order, or None where any
+    /// tile will do
+    pub fn new(cells: Vec<Option<Tile>>) -> Result<GoalPattern<R, C>, BoardError> {
+        if cells.len() != R * C {
+            return Err(BoardError::WrongSize { expected: R * C, found: cells.len() });
+        }
+
+        let mut present = vec![false; R * C];
+        for tile in cells.iter().flatten() {
+            if *tile as usize >= R * C {
+                return Err(BoardError::InvalidTile(*tile));
+            }
+            if present[*tile as usize] {
+                return Err(BoardError::DuplicateTile(*tile));
+            }
+
+            present[*tile as usize] = true;
+        }
+
+        let targets = cells.iter().enumerate()
+            .filter_map(|(pos, cell)| cell.filter(|tile| *tile > 0).map(|tile| (tile, pos)))
+            .collect();
+        Ok(GoalPattern { cells, targets })
+    }
+
+    /// The pattern that only asks for the tiles to be where they are in the goal
+    pub fn from_goal(goal: &Board<R, C>, tiles: &[Tile]) -> GoalPattern<R, C> {
+        let cells = goal.tiles().iter().copied().map(|tile| Some(tile).filter(|tile| tiles.contains(tile))).collect();
+        GoalPattern::new(cells).unwrap()
+    }
+
+    /// The expected tile at every position in reading order, if any
+    pub fn cells(&self) -> &[Option<Tile>] {
+        &self.cells
+    }
+
+    pub fn matches(&self, board: &Board<R, C>) -> bool {
+        board.tiles().iter().copied().zip(self.cells.iter()).all(|(tile, cell)| cell.is_none_or(|expected| expected == tile))
+    }
+
+    /// The manhattan distance of the numbered tiles the pattern places, leaving out the others and
+    /// the blank, so that it never over-estimates the moves left
+    pub fn manhattan_dist(&self, board: &Board<R, C>) -> i32 {
+        let positions = board.positions();
+        self.targets.iter()
+            .map(|(tile, target)| position_dist::<R, C>(positions[*tile as usize], *target))
+            .sum()
+    }
+}
+
+impl<const R: usize, const C: usize> FromStr for GoalPattern<R, C> {
+    type Err = ParseBoardError;
+
+    /// The same text as a board, with `*` for the positions that do not matter
+    fn from_str(text: &str) -> Result<GoalPattern<R, C>, ParseBoardError> {
+        let rows: Vec<&str> = text.split('/').collect();
+        let mut cells = Vec::with_capacity(R * C);
+        for (index, row) in rows.iter().enumerate() {
+            let mut row_cells = row.split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|cell| !cell.is_empty())
+                .map(|cell| match cell {
+                    "*" => Ok(None),
+                    _ => cell.parse::<Tile>().map(Some).map_err(|_| ParseBoardError::InvalidNumber(cell.to_string())),
+                })
+                .collect::<Result<Vec<Option<Tile>>, ParseBoardError>>()?;
+
+            if rows.len() > 1 && row_cells.len() != C {
+                return Err(ParseBoardError::WrongRowSize { row: index, expected: C, found: row_cells.len() });
+            }
+            cells.append(&mut row_cells);
+        }
+
+        Ok(GoalPattern::new(cells)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::board::GOAL;
+
+    use super::*;
+
+    #[test]
+    fn test_contains() {
+        let other = Board::new([0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        let goals = GoalSet::new(vec![GOAL, other]);
+
+        assert!(goals.contains(&GOAL));
+        assert!(goals.contains(&other));
+        assert!(!goals.contains(&Board::new([1, 0, 2, 3, 4, 5, 6, 7, 8])));
+    }
+
+    #[test]
+    fn test_manhattan_dist_is_min_over_goals() {
+        let other = Board::new([0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        let goals = GoalSet::new(vec![GOAL, other]);
+
+        assert_eq!(0, goals.manhattan_dist(&GOAL));
+        assert_eq!(0, goals.manhattan_dist(&other));
+        assert_eq!(1, goals.manhattan_dist(&Board::new([1, 0, 2, 3, 4, 5, 6, 7, 8])));
+        assert_eq!(1, goals.manhattan_dist(&Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8])));
+    }
+
+    #[test]
+    fn test_pattern_dist_is_relaxed() {
+        let other = Board::new([0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        let goals = GoalSet::new(vec![GOAL, other]);
+
+        for board in [GOAL, other, Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1])].iter() {
+            assert!(goals.pattern_dist(board) <= goals.manhattan_dist(board));
+        }
+        assert_eq!(0, goals.pattern_dist(&GOAL));
+    }
+
+    #[test]
+    fn test_goal_pattern() {
+        let pattern: GoalPattern = "1 2 3 / * * * / * * *".parse().unwrap();
+        assert_eq!(pattern, GoalPattern::from_goal(&GOAL, &[1, 2, 3]));
+        assert!(pattern.matches(&GOAL));
+        assert!(pattern.matches(&Board::new([1, 2, 3, 8, 6, 7, 0, 5, 4])));
+        assert!(!pattern.matches(&Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0])));
+
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        //tile 1 is 4 moves away, 2 is 2 away, and 3 is 4 away
+        assert_eq!(pattern.manhattan_dist(&board), 10);
+        assert!(pattern.manhattan_dist(&board) <= board.manhattan_dist());
+
+        let blank_corner: GoalPattern = "0 * * / * * * / * * *".parse().unwrap();
+        assert_eq!(blank_corner.manhattan_dist(&board), 0);
+        assert!(blank_corner.matches(&Board::new([0, 1, 2, 3, 4, 5, 6, 7, 8])));
+    }
+
+    #[test]
+    fn test_invalid_goal_pattern() {
+        assert_eq!("1 2 3 / * * *".parse::<GoalPattern>(), Err(ParseBoardError::Board(BoardError::WrongSize { expected: 9, found: 6 })));
+        assert_eq!("1 1 * * * * * * *".parse::<GoalPattern>(), Err(ParseBoardError::Board(BoardError::DuplicateTile(1))));
+        assert_eq!("1 ? * * * * * * *".parse::<GoalPattern>(), Err(ParseBoardError::InvalidNumber("?".to_string())));
+    }
+
+    #[test]
+    fn test_rectangular_goal_set() {
+        let goal = Board::<3, 4>::goal();
+        let other = Board::<3, 4>::try_from([[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 0, 11]]).unwrap();
+        let goals = GoalSet::new(vec![goal, other]);
+
+        let board = Board::<3, 4>::try_from([[1, 2, 3, 4], [5, 6, 7, 8], [9, 0, 10, 11]]).unwrap();
+        assert!(goals.contains(&other) && !goals.contains(&board));
+        assert_eq!(goals.manhattan_dist(&board), 1);
+        assert_eq!(goals.pattern_dist(&board), 1);
+        assert_eq!(crate::a_star_search_goals(board, &goals).unwrap().end(), &other);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_empty_goal_set() {
+        GoalSet::<3>::new(vec![]);
+    }
+}
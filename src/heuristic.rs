@@ -0,0 +1,331 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::board::{Board, Tile, GOAL};
+use crate::solver::UnknownName;
+
+/// The built in heuristics, which can be picked by name (e.g. from the command line)
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HeuristicKind {
+    Manhattan,
+    DisplacedTiles,
+}
+
+impl HeuristicKind {
+    pub fn all() -> [HeuristicKind; 2] {
+        [HeuristicKind::Manhattan, HeuristicKind::DisplacedTiles]
+    }
+
+    pub fn h(&self, board: &Board) -> i32 {
+        match self {
+            HeuristicKind::Manhattan => board.manhattan_dist(),
+            HeuristicKind::DisplacedTiles => board.displaced_tiles(),
+        }
+    }
+
+    /// The estimate towards another goal, falling back on the faster `h` for `GOAL`
+    pub fn h_to(&self, board: &Board, goal: &Board) -> i32 {
+        if *goal == GOAL {
+            return self.h(board);
+        }
+
+        match self {
+            HeuristicKind::Manhattan => board.manhattan_dist_to(goal),
+            HeuristicKind::DisplacedTiles => board.displaced_tiles_to(goal),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HeuristicKind::Manhattan => "manhattan",
+            HeuristicKind::DisplacedTiles => "displaced",
+        }
+    }
+}
+
+impl Display for HeuristicKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for HeuristicKind {
+    type Err = UnknownName;
+
+    fn from_str(name: &str) -> Result<HeuristicKind, UnknownName> {
+        HeuristicKind::all().iter()
+            .find(|heuristic| heuristic.name() == name)
+            .copied()
+            .ok_or_else(|| UnknownName(name.to_string()))
+    }
+}
+
+/// Strengthens a heuristic with the dual lookup trick, taking the maximum of the heuristic on the
+/// board and on its dual (inverse permutation). The dual is only as far from the goal as the board
+/// when the blank is in its goal position, so other boards just use the plain heuristic value.
+pub fn dual_max<const R: usize, const C: usize, H: Fn(&Board<R, C>) -> i32>(heuristic: H) -> impl Fn(&Board<R, C>) -> i32 {
+    move |board: &Board<R, C>| {
+        let h = heuristic(board);
+        if board.is_blank_home() {
+            h.max(heuristic(&board.inverse_permutation()))
+        } else {
+            h
+        }
+    }
+}
+
+/// Counts the landmarks of placing the tiles in reading order that the board has not achieved yet.
+/// The landmark of a tile is only achieved once it and every tile before it are in their goal
+/// positions, since a tile cannot be locked in place until the region before it is complete.
+/// A single misplaced tile leaves every later landmark unachieved, so this is not admissible and
+/// suits the greedy searches, on its own or among the candidates of `select_strongest`.
+pub fn unachieved_landmarks<const R: usize, const C: usize>(board: &Board<R, C>) -> i32 {
+    let landmarks = R * C - 1;
+    let achieved = board.tiles().iter().copied()
+        .take(landmarks)
+        .enumerate()
+        .take_while(|(index, tile)| *tile as usize == index + 1)
+        .count();
+
+    (landmarks - achieved) as i32
+}
+
+/// The line of the board two tiles are in conflict on
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Line {
+    Row(usize),
+    Column(usize),
+}
+
+/// Two tiles in the row or column of their goal positions but in reverse order, so that one of
+/// them must leave the line to let the other pass, which the manhattan distance does not count
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Conflict {
+    /// The tile before the other on the line, left of it or above it
+    pub first: Tile,
+    pub second: Tile,
+    pub line: Line,
+}
+
+/// The linear conflicts of the board, row by row then column by column
+pub fn linear_conflicts<const R: usize, const C: usize>(board: &Board<R, C>) -> Vec<Conflict> {
+    //the goal row and column of a tile
+    let home = |tile: Tile| ((tile as usize - 1) / C, (tile as usize - 1) % C);
+    let mut conflicts = Vec::new();
+
+    for row in 0..R {
+        let tiles: Vec<Tile> = board.row(row).unwrap().filter(|tile| *tile != 0 && home(*tile).0 == row).collect();
+        for (index, first) in tiles.iter().enumerate() {
+            for second in &tiles[index + 1..] {
+                if home(*first).1 > home(*second).1 {
+                    conflicts.push(Conflict { first: *first, second: *second, line: Line::Row(row) });
+                }
+            }
+        }
+    }
+
+    for col in 0..C {
+        let tiles: Vec<Tile> = board.column(col).unwrap().filter(|tile| *tile != 0 && home(*tile).1 == col).collect();
+        for (index, first) in tiles.iter().enumerate() {
+            for second in &tiles[index + 1..] {
+                if home(*first).0 > home(*second).0 {
+                    conflicts.push(Conflict { first: *first, second: *second, line: Line::Column(col) });
+                }
+            }
+        }
+    }
+
+    conflicts
+}
+
+/// The manhattan distance plus two moves for each tile that has to leave its goal row or column to
+/// let the others on it pass. Only the fewest tiles that resolve every conflict of a line are
+/// counted, rather than every conflicting pair, so that the estimate stays admissible.
+pub fn linear_conflict_heuristic<const R: usize, const C: usize>(board: &Board<R, C>) -> i32 {
+    let conflicts = linear_conflicts(board);
+    let mut lines: Vec<Line> = conflicts.iter().map(|conflict| conflict.line).collect();
+    lines.dedup();
+
+    let mut removed = 0;
+    for line in lines {
+        let in_conflict = |tile: &Tile| conflicts.iter().any(|conflict| conflict.line == line && (conflict.first == *tile || conflict.second == *tile));
+        //the goal offsets along the line of the conflicting tiles, in the order they are on it
+        let offsets: Vec<usize> = match line {
+            Line::Row(row) => board.row(row).unwrap().filter(in_conflict).map(|tile| (tile as usize - 1) % C).collect(),
+            Line::Column(col) => board.column(col).unwrap().filter(in_conflict).map(|tile| (tile as usize - 1) / C).collect(),
+        };
+
+        //the tiles that stay are the longest run already in goal order, the others leave the line
+        let mut longest = vec![1; offsets.len()];
+        for index in 0..offsets.len() {
+            for before in 0..index {
+                if offsets[before] < offsets[index] {
+                    longest[index] = longest[index].max(longest[before] + 1);
+                }
+            }
+        }
+        removed += offsets.len() - longest.into_iter().max().unwrap_or(0);
+    }
+
+    board.manhattan_dist() + 2 * removed as i32
+}
+
+/// The board and the boards at the end of `walks` random walks of `length` moves from it, on which
+/// heuristics are probed. The walks only depend on the seed, so the same boards are probed again.
+pub fn probe_boards<const R: usize, const C: usize>(board: &Board<R, C>, walks: usize, length: u32, seed: u64) -> Vec<Board<R, C>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut probes = Vec::with_capacity(walks + 1);
+    probes.push(*board);
+    for _ in 0..walks {
+        let mut walked = *board;
+        for _ in 0..length {
+            let successors = walked.successors();
+            walked = successors[rng.gen_range(0..successors.len())];
+        }
+        probes.push(walked);
+    }
+
+    probes
+}
+
+/// The sum of the heuristic over the probed boards
+pub fn probe_total<const R: usize, const C: usize, H: Fn(&Board<R, C>) -> i32>(probes: &[Board<R, C>], heuristic: H) -> i64 {
+    probes.iter().map(|probe| heuristic(probe) as i64).sum()
+}
+
+/// Probes each candidate heuristic on the board and on the boards of random walks from it (see
+/// `probe_boards`), and returns the index of the one with the highest total. Admissible heuristics
+/// never over-estimate, so the highest values are the most informed for this particular instance.
+pub fn select_strongest<const R: usize, const C: usize>(candidates: &[&dyn Fn(&Board<R, C>) -> i32], board: &Board<R, C>, walks: usize, length: u32, seed: u64) -> usize {
+    assert!(!candidates.is_empty(), "Expecting at least one heuristic to select from.");
+
+    let probes = probe_boards(board, walks, length, seed);
+    let mut strongest = 0;
+    let mut strongest_total = i64::MIN;
+    for (index, heuristic) in candidates.iter().enumerate() {
+        let total = probe_total(&probes, heuristic);
+        if total > strongest_total {
+            strongest = index;
+            strongest_total = total;
+        }
+    }
+
+    strongest
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::manhattan_dist_positions;
+
+    use super::*;
+
+    #[test]
+    fn test_heuristic_names() {
+        for heuristic in HeuristicKind::all().iter() {
+            assert_eq!(heuristic.name().parse::<HeuristicKind>(), Ok(*heuristic));
+        }
+        assert_eq!("euclidean".parse::<HeuristicKind>(), Err(UnknownName("euclidean".to_string())));
+    }
+
+    #[test]
+    fn test_dual_max() {
+        let heuristic = dual_max(|board: &Board| board.manhattan_dist());
+        assert_eq!(heuristic(&GOAL), 0);
+
+        for tiles in [[2, 3, 1, 4, 5, 6, 7, 8, 0], [8, 6, 7, 2, 5, 4, 3, 1, 0], [8, 6, 7, 2, 5, 4, 3, 0, 1]].iter() {
+            let board = Board::new(*tiles);
+            let optimal = crate::a_star_search(board).unwrap().steps() as i32;
+            assert!(heuristic(&board) >= board.manhattan_dist());
+            assert!(heuristic(&board) <= optimal);
+        }
+    }
+
+    #[test]
+    fn test_unachieved_landmarks() {
+        assert_eq!(unachieved_landmarks(&GOAL), 0);
+        assert_eq!(unachieved_landmarks(&Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8])), 1);
+        assert_eq!(unachieved_landmarks(&Board::new([1, 2, 3, 4, 5, 0, 7, 8, 6])), 3);
+        //tile 1 out of place leaves every landmark unachieved, even with the others in place
+        assert_eq!(unachieved_landmarks(&Board::new([0, 2, 3, 4, 5, 6, 7, 8, 1])), 8);
+        assert_eq!(unachieved_landmarks(&Board::<4>::goal()), 0);
+
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let plan = crate::greedy_best_first_search_with(board, unachieved_landmarks).unwrap();
+        assert_eq!(*plan.end(), GOAL);
+    }
+
+    #[test]
+    fn test_linear_conflicts() {
+        assert!(linear_conflicts(&GOAL).is_empty());
+        assert_eq!(linear_conflicts(&Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0])), vec![Conflict { first: 2, second: 1, line: Line::Row(0) }]);
+        assert_eq!(linear_conflicts(&Board::new([3, 2, 1, 4, 5, 6, 7, 8, 0])).len(), 3);
+
+        let conflicts = linear_conflicts(&Board::new([4, 2, 3, 1, 5, 6, 7, 8, 0]));
+        assert_eq!(conflicts, vec![Conflict { first: 4, second: 1, line: Line::Column(0) }]);
+        //tiles out of their goal lines are not in conflict
+        assert!(linear_conflicts(&Board::new([5, 2, 3, 4, 1, 6, 7, 8, 0])).is_empty());
+    }
+
+    #[test]
+    fn test_linear_conflict_heuristic() {
+        assert_eq!(linear_conflict_heuristic(&GOAL), 0);
+        let swapped = Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]);
+        assert_eq!(linear_conflict_heuristic(&swapped), swapped.manhattan_dist() + 2);
+        //three conflicting pairs, resolved by moving two of the tiles out of the row
+        let reversed = Board::new([3, 2, 1, 4, 5, 6, 7, 8, 0]);
+        assert_eq!(linear_conflict_heuristic(&reversed), reversed.manhattan_dist() + 4);
+
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let plan = crate::a_star_search_with(board, linear_conflict_heuristic).unwrap();
+        assert_eq!(plan.steps(), 31);
+        assert!(plan.statistics().unwrap().expanded < crate::a_star_search(board).unwrap().statistics().unwrap().expanded);
+    }
+
+    #[test]
+    fn test_select_strongest() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let displaced = |board: &Board| board.displaced_tiles();
+        let manhattan = |board: &Board| board.manhattan_dist();
+
+        assert_eq!(select_strongest(&[&displaced, &manhattan], &board, 10, 20, 7), 1);
+        assert_eq!(select_strongest(&[&manhattan, &displaced], &board, 0, 0, 7), 0);
+
+        let fifteen: Board<4> = "5 1 3 4 / 9 2 7 8 / 13 6 10 12 / 14 0 11 15".parse().unwrap();
+        let manhattan = |board: &Board<4>| board.manhattan_dist();
+        assert_eq!(select_strongest(&[&manhattan, &linear_conflict_heuristic], &fifteen, 10, 20, 7), 1);
+    }
+
+    #[test]
+    fn test_probe_boards() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let probes = probe_boards(&board, 5, 4, 3);
+
+        assert_eq!(probes.len(), 6);
+        assert_eq!(probes[0], board);
+        assert!(probes.iter().all(|probe| probe.is_solvable()));
+        assert_eq!(probes, probe_boards(&board, 5, 4, 3));
+        assert_ne!(probes, probe_boards(&board, 5, 4, 4));
+        assert_eq!(probe_total(&probes[..1], |board: &Board| board.manhattan_dist()), board.manhattan_dist() as i64);
+    }
+
+    #[test]
+    fn test_dual_max_is_stronger() {
+        //manhattan distance of tiles 1, 2 and 3 only, similar to a pattern database lookup
+        let pattern = |board: &Board| {
+            let positions = board.positions();
+            let goal_positions = GOAL.positions();
+            (1..4).map(|tile| manhattan_dist_positions(positions[tile], goal_positions[tile])).sum()
+        };
+
+        let board = Board::new([2, 6, 4, 1, 5, 3, 7, 8, 0]);
+        let heuristic = dual_max(pattern);
+        assert_eq!(pattern(&board), 3);
+        assert_eq!(heuristic(&board), 6);
+
+        let plan = crate::a_star_search_with(board, heuristic).unwrap();
+        assert_eq!(plan.steps(), crate::a_star_search(board).unwrap().steps());
+    }
+}
@@ -0,0 +1,206 @@
+use crate::board::{Board, Move, Tile};
+use crate::heuristic::{linear_conflicts, Conflict};
+use crate::search::{self, Limits};
+use crate::BoardState;
+
+/// The boards the searches of `explain_move` may expand, from before and after the move each
+pub const EXPLAIN_BUDGET: i32 = 100_000;
+
+/// Whether a move is the first of some plan with the fewest moves
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Optimality {
+    /// The move brings the board one move closer to the goal
+    Optimal,
+    /// The move takes the board away from the goal, and the plans through it take this many more
+    /// moves than the best ones
+    Suboptimal { extra: u32 },
+    /// The distance to the goal of the board before or after the move is not known
+    Unknown,
+}
+
+/// Why a move is good or bad, for tutors to tell the player
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveExplanation<const R: usize = 3, const C: usize = R> {
+    pub next_move: Move,
+    /// The tile the move slides into the blank
+    pub tile: Tile,
+    pub after: Board<R, C>,
+    /// The heuristic value of the board before and after the move
+    pub h_before: i32,
+    pub h_after: i32,
+    /// The linear conflicts the move removed and the ones it added
+    pub resolved: Vec<Conflict>,
+    pub created: Vec<Conflict>,
+    /// The fewest moves to the goal before and after the move, when known
+    pub distance_before: Option<u32>,
+    pub distance_after: Option<u32>,
+    pub optimality: Optimality,
+}
+
+impl<const R: usize, const C: usize> MoveExplanation<R, C> {
+    /// How much closer the heuristic says the move brings the board to the goal
+    pub fn h_gain(&self) -> i32 {
+        self.h_before - self.h_after
+    }
+}
+
+/// Explains a move with the manhattan distance, telling whether it is optimal with searches that
+/// expand at most `EXPLAIN_BUDGET` boards each. There is no explanation for a blocked move.
+pub fn explain_move<const R: usize, const C: usize>(board: Board<R, C>, next_move: Move) -> Option<MoveExplanation<R, C>> {
+    explain_move_with(board, next_move, crate::manhattan_heuristic, |board| bounded_distance(*board, EXPLAIN_BUDGET))
+}
+
+/// Explains a move with any heuristic, and with any way of finding the fewest moves to the goal,
+/// such as `OracleTable::distance` or `bounded_distance`. There is no explanation for a blocked move.
+pub fn explain_move_with<const R: usize, const C: usize, H, D>(board: Board<R, C>, next_move: Move, heuristic: H, distance: D) -> Option<MoveExplanation<R, C>>
+    where H: Fn(&Board<R, C>) -> i32,
+          D: Fn(&Board<R, C>) -> Option<u32>,
+{
+    let after = board.apply_move(next_move)?;
+    //the tile is where the blank went
+    let tile = board.tiles()[after.tiles().iter().position(|tile| *tile == 0).unwrap()];
+
+    let (before_conflicts, after_conflicts) = (linear_conflicts(&board), linear_conflicts(&after));
+    let resolved = before_conflicts.iter().filter(|conflict| !after_conflicts.contains(conflict)).copied().collect();
+    let created = after_conflicts.iter().filter(|conflict| !before_conflicts.contains(conflict)).copied().collect();
+
+    let (distance_before, distance_after) = (distance(&board), distance(&after));
+    let optimality = match (distance_before, distance_after) {
+        (Some(before), Some(after)) if after < before => Optimality::Optimal,
+        (Some(before), Some(after)) => Optimality::Suboptimal { extra: after + 1 - before },
+        _ => Optimality::Unknown,
+    };
+
+    Some(MoveExplanation {
+        next_move,
+        tile,
+        after,
+        h_before: heuristic(&board),
+        h_after: heuristic(&after),
+        resolved,
+        created,
+        distance_before,
+        distance_after,
+        optimality,
+    })
+}
+
+/// The fewest moves from the board to the goal, found with an A* search guided by the manhattan
+/// distance, unless it needs to expand more than `budget` boards or the board cannot reach the goal
+pub fn bounded_distance<const R: usize, const C: usize>(board: Board<R, C>, budget: i32) -> Option<u32> {
+    let limits = Limits { max_expanded: Some(budget.max(1)), ..Limits::none() };
+    let result = search::a_star_search_limited(&BoardState::manhattan(board), |state| state.board.is_goal(), limits);
+    result.plan.map(|plan| plan.len() as u32 - 1)
+}
+
+/// The first move of the best path found by an A* search that expands at most `budget` boards,
+/// guided by the manhattan distance. The path leads to the goal if the search reaches it within the
+/// budget, and otherwise to the closest board to the goal found, so the time taken by a hint stays
+/// bounded even on boards that are too large to solve, such as those of the 24-puzzle.
+/// There is no move to suggest when the board is already the goal.
+pub fn best_next_move<const R: usize, const C: usize>(board: Board<R, C>, budget: i32) -> Option<Move> {
+    if board.is_goal() {
+        return None;
+    }
+
+    let initial_state = BoardState::manhattan(board);
+    let limits = Limits { max_expanded: Some(budget.max(1)), ..Limits::none() };
+    let result = search::a_star_search_limited(&initial_state, |state| state.board.is_goal(), limits);
+
+    let next = match result.best_path.get(1) {
+        Some(state) => state.board,
+        //nothing closer to the goal was found, so go with the most promising successor
+        None => *board.successors().iter().min_by_key(|successor| successor.manhattan_dist()).unwrap(),
+    };
+
+    board.move_to(&next)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::board::GOAL;
+    use crate::heuristic::Line;
+    use crate::oracle::OracleTable;
+    use crate::search::StopReason;
+
+    use super::*;
+
+    #[test]
+    fn test_no_move_from_goal() {
+        assert_eq!(best_next_move(GOAL, 100), None);
+    }
+
+    #[test]
+    fn test_move_to_goal() {
+        assert_eq!(best_next_move(Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8]), 1), Some(Move::Left));
+        assert_eq!(best_next_move(Board::new([1, 2, 3, 4, 5, 0, 7, 8, 6]), 1), Some(Move::Up));
+    }
+
+    #[test]
+    fn test_enough_budget_is_optimal() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let plan = crate::a_star_search(board).unwrap();
+
+        assert_eq!(best_next_move(board, i32::MAX), Some(plan.moves()[0]));
+    }
+
+    #[test]
+    fn test_explain_optimal_move() {
+        let board = Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8]);
+        let explanation = explain_move(board, Move::Left).unwrap();
+
+        assert_eq!(explanation.tile, 8);
+        assert_eq!(explanation.after, GOAL);
+        assert_eq!((explanation.h_before, explanation.h_after, explanation.h_gain()), (1, 0, 1));
+        assert_eq!((explanation.distance_before, explanation.distance_after), (Some(1), Some(0)));
+        assert_eq!(explanation.optimality, Optimality::Optimal);
+
+        assert_eq!(explain_move(board, Move::Up), None);
+        assert_eq!(explain_move(board, Move::Right).unwrap().optimality, Optimality::Suboptimal { extra: 2 });
+    }
+
+    #[test]
+    fn test_explain_conflicts() {
+        //sliding 1 down takes it out of the way of 2 in the top row
+        let board = Board::new([2, 1, 3, 4, 0, 6, 7, 5, 8]);
+        let explanation = explain_move(board, Move::Down).unwrap();
+        assert_eq!(explanation.tile, 1);
+        assert_eq!(explanation.resolved, vec![Conflict { first: 2, second: 1, line: Line::Row(0) }]);
+        assert!(explanation.created.is_empty());
+
+        let back = explain_move(explanation.after, Move::Up).unwrap();
+        assert_eq!(back.created, explanation.resolved);
+    }
+
+    #[test]
+    fn test_explain_with_oracle() {
+        let oracle = OracleTable::build();
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let plan = crate::a_star_search(board).unwrap();
+
+        let explanation = explain_move_with(board, plan.moves()[0], crate::manhattan_heuristic, |board| oracle.distance(board)).unwrap();
+        assert_eq!(explanation.distance_before, Some(31));
+        assert_eq!(explanation.optimality, Optimality::Optimal);
+
+        //not enough budget to tell
+        let explanation = explain_move_with(board, plan.moves()[0], crate::manhattan_heuristic, |board| bounded_distance(*board, 10)).unwrap();
+        assert_eq!(explanation.optimality, Optimality::Unknown);
+    }
+
+    #[test]
+    fn test_large_board_within_budget() {
+        //a 24-puzzle board far from the goal, which A* cannot solve
+        let board = Board::<5>::random(&mut StdRng::seed_from_u64(5));
+        let next_move = best_next_move(board, 1000).unwrap();
+        assert!(board.successors().iter().any(|successor| board.move_to(successor) == Some(next_move)));
+
+        //the budget bounds the work of the hint, rather than the size of the board
+        let limits = Limits { max_expanded: Some(1000), ..Limits::none() };
+        let result = search::a_star_search_limited(&BoardState::manhattan(board), |state| state.board.is_goal(), limits);
+        assert_eq!(result.statistics.expanded, 1000);
+        assert_eq!(result.stop_reason, Some(StopReason::MaxExpanded));
+    }
+}
@@ -0,0 +1,146 @@
+//! A file format for sets of boards to search from, which records where they came from so that an
+//! experiment can be run again on the same instances:
+//!
+//! ```text
+//! tiles-instances v1
+//! size 3x3
+//! seed 0
+//! sampling uniform
+//! 8 6 7 2 5 4 3 0 1
+//! ```
+//!
+//! The size is required, the seed and sampling only when the boards were generated. Then comes one
+//! board per line, with its tiles in reading order.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::path::Path;
+
+use crate::board::Board;
+use crate::dataset::{DatasetGenerator, Sampling};
+
+const HEADER: &str = "tiles-instances v1";
+
+/// Boards to search from, with the generator settings they were sampled with, if any
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InstanceSet<const R: usize = 3, const C: usize = R> {
+    pub seed: Option<u64>,
+    pub sampling: Option<Sampling>,
+    pub boards: Vec<Board<R, C>>,
+}
+
+impl InstanceSet {
+    /// Samples the boards, which are the same every time for the same sampling and seed
+    pub fn generate(sampling: Sampling, seed: u64, count: usize) -> InstanceSet {
+        let boards = DatasetGenerator::new(sampling, seed).take(count).map(|sample| sample.board).collect();
+        InstanceSet { seed: Some(seed), sampling: Some(sampling), boards }
+    }
+}
+
+impl<const R: usize, const C: usize> InstanceSet<R, C> {
+    /// Boards of unknown origin
+    pub fn new(boards: Vec<Board<R, C>>) -> InstanceSet<R, C> {
+        InstanceSet { seed: None, sampling: None, boards }
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "{}", HEADER)?;
+        writeln!(writer, "size {}x{}", R, C)?;
+        if let Some(seed) = self.seed {
+            writeln!(writer, "seed {}", seed)?;
+        }
+        if let Some(sampling) = self.sampling {
+            writeln!(writer, "sampling {}", sampling)?;
+        }
+
+        for board in &self.boards {
+            let tiles: Vec<String> = board.tiles().iter().map(|tile| tile.to_string()).collect();
+            writeln!(writer, "{}", tiles.join(" "))?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a set written by `write`, which has to be of boards of this size
+    pub fn read<B: BufRead>(reader: B) -> io::Result<InstanceSet<R, C>> {
+        let mut lines = reader.lines();
+        if lines.next().transpose()?.as_deref() != Some(HEADER) {
+            return Err(invalid("Not an instance set file.".to_string()));
+        }
+
+        let mut size = None;
+        let mut set = InstanceSet::new(Vec::new());
+        for line in lines {
+            let line = line?;
+            let (key, value) = line.split_once(' ').unwrap_or((&line, ""));
+            match key {
+                "size" => size = Some(value.to_string()),
+                "seed" => set.seed = Some(value.parse().map_err(|_| invalid(format!("Invalid seed: {}", value)))?),
+                "sampling" => set.sampling = Some(value.parse().map_err(|e| invalid(format!("{}", e)))?),
+                "" => continue,
+                _ => {
+                    let board = line.parse().map_err(|e| invalid(format!("Invalid board {}: {}", line, e)))?;
+                    set.boards.push(board);
+                }
+            }
+        }
+
+        match size {
+            Some(size) if size == format!("{}x{}", R, C) => Ok(set),
+            Some(size) => Err(invalid(format!("Expecting boards of size {}x{}, found {}.", R, C, size))),
+            None => Err(invalid("Missing the size of the boards.".to_string())),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<InstanceSet<R, C>> {
+        InstanceSet::read(BufReader::new(File::open(path)?))
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_and_read() {
+        let set = InstanceSet::generate(Sampling::RandomWalk(20), 3, 5);
+        let mut written = Vec::new();
+        set.write(&mut written).unwrap();
+
+        let text = String::from_utf8(written.clone()).unwrap();
+        assert!(text.starts_with("tiles-instances v1\nsize 3x3\nseed 3\nsampling walk-20\n"));
+        assert_eq!(text.lines().count(), 4 + 5);
+        assert_eq!(InstanceSet::read(written.as_slice()).unwrap(), set);
+        assert_eq!(InstanceSet::generate(Sampling::RandomWalk(20), 3, 5), set);
+    }
+
+    #[test]
+    fn test_without_provenance() {
+        let set = InstanceSet::<4>::new(vec![Board::goal()]);
+        let mut written = Vec::new();
+        set.write(&mut written).unwrap();
+
+        assert_eq!(String::from_utf8(written.clone()).unwrap(), "tiles-instances v1\nsize 4x4\n1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 0\n");
+        assert_eq!(InstanceSet::read(written.as_slice()).unwrap(), set);
+        assert_eq!(InstanceSet::<3>::read(written.as_slice()).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let read = |text: &str| InstanceSet::<3>::read(text.as_bytes()).unwrap_err().kind();
+        assert_eq!(read("1 2 3 4 5 6 7 8 0\n"), ErrorKind::InvalidData);
+        assert_eq!(read("tiles-instances v1\n1 2 3 4 5 6 7 8 0\n"), ErrorKind::InvalidData);
+        assert_eq!(read("tiles-instances v1\nsize 3x3\n1 2 3 4 5 6 7 8 8\n"), ErrorKind::InvalidData);
+        assert_eq!(read("tiles-instances v1\nsize 3x3\nsampling spiral\n"), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash};
+use std::sync::Mutex;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::board::Board;
+
+const SHARDS: usize = 64;
+
+/// Set of visited states that many threads can insert into at once.
+/// States are spread over independently locked shards by their hash, so threads rarely contend.
+pub struct ShardedSet<S> {
+    shards: Vec<Mutex<HashSet<S>>>,
+    hasher: RandomState,
+}
+
+impl<S: Eq + Hash> ShardedSet<S> {
+    pub fn new() -> ShardedSet<S> {
+        ShardedSet { shards: (0..SHARDS).map(|_| Mutex::new(HashSet::new())).collect(), hasher: RandomState::new() }
+    }
+
+    /// Adds the state, returning whether it was not in the set before
+    pub fn insert(&self, state: S) -> bool {
+        let shard = (self.hasher.hash_one(&state) as usize) % self.shards.len();
+        self.shards[shard].lock().unwrap().insert(state)
+    }
+
+    pub fn contains(&self, state: &S) -> bool {
+        let shard = (self.hasher.hash_one(state) as usize) % self.shards.len();
+        self.shards[shard].lock().unwrap().contains(state)
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.lock().unwrap().len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<S: Eq + Hash> Default for ShardedSet<S> {
+    fn default() -> Self {
+        ShardedSet::new()
+    }
+}
+
+/// Breadth first search from the roots, returning the states at every depth (the roots first).
+/// Each layer is expanded in parallel on the rayon thread pool with the `parallel` feature, and
+/// the search stops after `max_depth` layers, or when no new states are found.
+pub fn parallel_layers<S, F>(roots: &[S], successors: F, max_depth: Option<u32>) -> Vec<Vec<S>>
+    where S: Copy + Eq + Hash + Send + Sync,
+          F: Fn(&S) -> Vec<S> + Sync
+{
+    let visited = ShardedSet::new();
+    let roots: Vec<S> = roots.iter().copied().filter(|root| visited.insert(*root)).collect();
+
+    let mut layers = vec![roots];
+    while max_depth.is_none_or(|max| layers.len() as u32 <= max) {
+        #[cfg(feature = "parallel")]
+        let next: Vec<S> = layers.last().unwrap()
+            .par_iter()
+            .flat_map_iter(&successors)
+            .filter(|successor| visited.insert(*successor))
+            .collect();
+        #[cfg(not(feature = "parallel"))]
+        let next: Vec<S> = layers.last().unwrap()
+            .iter()
+            .flat_map(&successors)
+            .filter(|successor| visited.insert(*successor))
+            .collect();
+
+        if next.is_empty() {
+            break;
+        }
+        layers.push(next);
+    }
+
+    layers
+}
+
+/// The number of boards at every distance from the board, up to the furthest reachable one
+pub fn layer_sizes(board: Board) -> Vec<usize> {
+    parallel_layers(&[board], Board::successors, None)
+        .iter()
+        .map(|layer| layer.len())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::GOAL;
+    use crate::policy::Policy;
+
+    use super::*;
+
+    #[test]
+    fn test_layer_sizes() {
+        let sizes = layer_sizes(GOAL);
+
+        assert_eq!(sizes.len(), 32);
+        assert_eq!(sizes.iter().sum::<usize>(), 181_440);
+        assert_eq!(&sizes[..5], &[1, 2, 4, 8, 16]);
+        assert_eq!(sizes[31], 2);
+    }
+
+    #[test]
+    fn test_matches_serial_distances() {
+        let layers = parallel_layers(&[GOAL], Board::successors, Some(12));
+        let policy = Policy::new(GOAL, 12);
+
+        assert_eq!(layers.len(), 13);
+        assert_eq!(layers.iter().map(|layer| layer.len()).sum::<usize>(), policy.len());
+        for (distance, layer) in layers.iter().enumerate() {
+            for board in layer {
+                assert_eq!(policy.distance(board), Some(distance as u32));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sharded_set() {
+        let set = ShardedSet::new();
+        assert!(set.is_empty());
+        assert!(set.insert(GOAL));
+        assert!(!set.insert(GOAL));
+        assert!(set.contains(&GOAL));
+        assert_eq!(set.len(), 1);
+    }
+}
@@ -0,0 +1,207 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use crate::board::Board;
+use crate::plan::Plan;
+
+const FEATURES: usize = 3;
+const HEADER: &str = "tiles-learned-heuristic v1";
+
+/// Collects (board, true cost to goal) samples from solved instances, and fits a linear model over
+/// the features of the base heuristics to them.
+#[derive(Debug, Default)]
+pub struct HeuristicLearner {
+    samples: Vec<([f64; FEATURES], f64)>,
+}
+
+impl HeuristicLearner {
+    pub fn new() -> HeuristicLearner {
+        HeuristicLearner { samples: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn record(&mut self, board: &Board, cost: u32) {
+        self.samples.push((features(board), cost as f64));
+    }
+
+    /// Records every board along an optimal plan, labelled with its remaining distance to the goal
+    pub fn record_plan(&mut self, plan: &Plan) {
+        for (index, board) in plan.boards().iter().enumerate() {
+            self.record(board, (plan.steps() - index) as u32);
+        }
+    }
+
+    /// Least squares fit of the feature weights, or None if the samples do not determine them
+    pub fn fit(&self) -> Option<LearnedHeuristic> {
+        //normal equations (X^T X) w = X^T y
+        let mut xtx = [[0.0; FEATURES]; FEATURES];
+        let mut xty = [0.0; FEATURES];
+        for (x, y) in self.samples.iter() {
+            for i in 0..FEATURES {
+                for j in 0..FEATURES {
+                    xtx[i][j] += x[i] * x[j];
+                }
+                xty[i] += x[i] * y;
+            }
+        }
+
+        solve(xtx, xty).map(|weights| LearnedHeuristic { weights })
+    }
+}
+
+/// Linear combination of the manhattan distance, the displaced tiles and a constant.
+/// Not admissible, but usually closer to the true cost than either feature on its own, which
+/// makes it useful to guide greedy best first search and enforced hill climbing.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LearnedHeuristic {
+    weights: [f64; FEATURES],
+}
+
+impl LearnedHeuristic {
+    pub fn new(weights: [f64; FEATURES]) -> LearnedHeuristic {
+        LearnedHeuristic { weights }
+    }
+
+    pub fn weights(&self) -> [f64; FEATURES] {
+        self.weights
+    }
+
+    pub fn h(&self, board: &Board) -> i32 {
+        if board.is_goal() {
+            return 0;
+        }
+
+        let estimate: f64 = features(board).iter()
+            .zip(self.weights.iter())
+            .map(|(feature, weight)| feature * weight)
+            .sum();
+
+        estimate.round().max(1.0) as i32
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let weights: Vec<String> = self.weights.iter().map(|weight| weight.to_string()).collect();
+        fs::write(path, format!("{}\n{}\n", HEADER, weights.join(" ")))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<LearnedHeuristic> {
+        let contents = fs::read_to_string(path)?;
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(io::Error::new(ErrorKind::InvalidData, "Not a learned heuristic file."));
+        }
+
+        let weights: Vec<f64> = lines.next()
+            .unwrap_or("")
+            .split_whitespace()
+            .map(|weight| weight.parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+
+        if weights.len() != FEATURES {
+            return Err(io::Error::new(ErrorKind::InvalidData, format!("Expecting {} weights.", FEATURES)));
+        }
+
+        let mut learned = [0.0; FEATURES];
+        learned.copy_from_slice(&weights);
+        Ok(LearnedHeuristic::new(learned))
+    }
+}
+
+fn features(board: &Board) -> [f64; FEATURES] {
+    [board.manhattan_dist() as f64, board.displaced_tiles() as f64, 1.0]
+}
+
+/// Gaussian elimination with partial pivoting
+fn solve(mut a: [[f64; FEATURES]; FEATURES], mut b: [f64; FEATURES]) -> Option<[f64; FEATURES]> {
+    for col in 0..FEATURES {
+        let pivot = (col..FEATURES)
+            .max_by(|r1, r2| a[*r1][col].abs().partial_cmp(&a[*r2][col].abs()).unwrap())
+            .unwrap();
+        if a[pivot][col].abs() < 1e-9 {
+            return None;
+        }
+
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let pivot_row = a[col];
+        for row in col + 1..FEATURES {
+            let factor = a[row][col] / pivot_row[col];
+            for (value, pivot_value) in a[row].iter_mut().zip(pivot_row.iter()).skip(col) {
+                *value -= factor * pivot_value;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = [0.0; FEATURES];
+    for row in (0..FEATURES).rev() {
+        let mut sum = b[row];
+        for k in row + 1..FEATURES {
+            sum -= a[row][k] * x[k];
+        }
+        x[row] = sum / a[row][row];
+    }
+
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn trained() -> LearnedHeuristic {
+        let mut learner = HeuristicLearner::new();
+        for tiles in [[8, 6, 7, 2, 5, 4, 3, 0, 1], [6, 4, 7, 8, 5, 0, 3, 2, 1], [1, 2, 3, 0, 4, 6, 7, 5, 8]].iter() {
+            learner.record_plan(&crate::a_star_search(Board::new(*tiles)).unwrap());
+        }
+
+        learner.fit().unwrap()
+    }
+
+    #[test]
+    fn test_fit() {
+        let learned = trained();
+
+        assert_eq!(learned.h(&crate::board::GOAL), 0);
+        //on hard boards the learned correction should exceed plain manhattan distance
+        let hard_board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        assert!(learned.h(&hard_board) > hard_board.manhattan_dist());
+    }
+
+    #[test]
+    fn test_fit_without_samples() {
+        assert!(HeuristicLearner::new().fit().is_none());
+    }
+
+    #[test]
+    fn test_save_load() {
+        let learned = trained();
+        let path = env::temp_dir().join(format!("tiles-learned-{}.txt", std::process::id()));
+
+        learned.save(&path).unwrap();
+        let loaded = LearnedHeuristic::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(learned, loaded);
+    }
+
+    #[test]
+    fn test_greedy_with_learned() {
+        let learned = trained();
+        let plan = crate::greedy_best_first_search_with(Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]), |board| learned.h(board));
+
+        assert!(plan.is_some());
+    }
+}
@@ -1,89 +1,633 @@
-#[macro_use]
-extern crate lazy_static;
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::OnceLock;
 
-use crate::board::Board;
-use crate::search::{SearchResult, State};
+use crate::board::costed::CostedBoard;
+use crate::board::repr::{Packed, Repr};
+use crate::board::torus::TorusBoard;
+use crate::board::{Board, Move, MoveOrder, Tile};
+use crate::goal::{GoalPattern, GoalSet};
+use crate::pdb::{Compression, DisjointPatternDatabases};
+use crate::perimeter::Perimeter;
+use crate::plan::Plan;
+use crate::queue::QueueKind;
+use crate::search::{Limits, MutableState, PathResult, SearchBuffers, SearchHooks, SearchResult, State};
+use crate::solver::Algorithm;
+use crate::trace::{Expansion, Inconsistency, Trace};
 
+pub mod clock;
 pub mod queue;
 pub mod search;
 pub mod board;
+pub mod goal;
+pub mod solver;
+pub mod lpa;
+pub mod policy;
+pub mod learning;
+pub mod heuristic;
+pub mod pdb;
+pub mod plan;
+pub mod dataset;
+pub mod batch;
+pub mod layers;
+pub mod compare;
+pub mod trace;
+pub mod analysis;
+pub mod experiments;
+pub mod hint;
+pub mod abstraction;
+pub mod ensemble;
+pub mod anytime;
+pub mod cache;
+pub mod instances;
+pub mod realtime;
+pub mod beam;
+pub mod notation;
+pub mod perimeter;
+pub mod narration;
+pub mod schedule;
+pub mod oracle;
+pub mod prelude;
 
-#[derive(Hash, Debug, Copy, Clone, Eq, PartialEq)]
-struct BoardState {
-    board: Board
+#[derive(Copy, Clone)]
+pub(crate) struct BoardState<'a, const R: usize = 3, const C: usize = R> {
+    pub(crate) board: Board<R, C>,
+    heuristic: &'a dyn Fn(&Board<R, C>) -> i32,
+    //computed once when the state is created, since the searches ask for it repeatedly
+    h: i32,
+    trace: Option<&'a RefCell<Trace<R, C>>>,
+    order: MoveOrder,
+    //whether the heuristic is the manhattan distance to the goal, updated from the moved tile
+    incremental: bool,
+    //the goal towards which mirrored boards are the same state, if any
+    symmetry: Option<Board<R, C>>,
 }
 
-impl BoardState {
-    fn new(board: Board) -> BoardState {
-        BoardState { board }
+impl<'a, const R: usize, const C: usize> BoardState<'a, R, C> {
+    pub(crate) fn new(board: Board<R, C>, heuristic: &'a dyn Fn(&Board<R, C>) -> i32) -> BoardState<'a, R, C> {
+        BoardState { board, heuristic, h: heuristic(&board), trace: None, order: MoveOrder::Default, incremental: false, symmetry: None }
+    }
+
+    /// A state guided by the manhattan distance to the goal, which its successors update from the
+    /// tile that moved rather than add up over every tile
+    pub(crate) fn manhattan(board: Board<R, C>) -> BoardState<'a, R, C> {
+        BoardState { incremental: true, ..BoardState::new(board, &manhattan_heuristic) }
+    }
+
+    /// A state equal to its mirror image towards the goal, and so are the states reached from it,
+    /// which leaves about half the boards to tell apart. The heuristic has to give mirrored boards
+    /// the same value, as the manhattan distance and the displaced tiles do.
+    pub(crate) fn symmetric(self, goal: Board<R, C>) -> BoardState<'a, R, C> {
+        BoardState { symmetry: Some(goal), ..self }
+    }
+
+    //the board that tells the states apart
+    fn key(&self) -> Board<R, C> {
+        match &self.symmetry {
+            Some(goal) => self.board.canonical_to(goal),
+            None => self.board,
+        }
+    }
+
+    /// A state whose heuristic is the manhattan distance to the goal, so that its successors can
+    /// update it from the tile that moved
+    pub(crate) fn incremental(self) -> BoardState<'a, R, C> {
+        BoardState { incremental: true, ..self }
+    }
+
+    /// A state that records every expansion of it, and of the states reached from it, in the trace
+    pub(crate) fn traced(self, trace: &'a RefCell<Trace<R, C>>) -> BoardState<'a, R, C> {
+        BoardState { trace: Some(trace), ..self }
+    }
+
+    //the state of a board reached from this one, keeping its settings
+    fn successor(&self, board: Board<R, C>) -> BoardState<'a, R, C> {
+        let h = if self.incremental { board.manhattan_dist_from(&self.board, self.h) } else { (self.heuristic)(&board) };
+        BoardState { board, h, ..*self }
+    }
+
+    /// A state whose successors, and theirs, are generated in the given order
+    pub(crate) fn ordered(self, order: MoveOrder) -> BoardState<'a, R, C> {
+        BoardState { order, ..self }
     }
 }
 
-impl State for BoardState {
-    fn successors(&self) -> Vec<Self> {
-        self.board.successors()
-            .iter()
-            .map(|board| BoardState::new(*board))
+impl<const R: usize, const C: usize> PartialEq for BoardState<'_, R, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl<const R: usize, const C: usize> Eq for BoardState<'_, R, C> {}
+
+impl<const R: usize, const C: usize> Hash for BoardState<'_, R, C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl<const R: usize, const C: usize> Debug for BoardState<'_, R, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoardState").field("board", &self.board).finish()
+    }
+}
+
+impl<const R: usize, const C: usize> State for BoardState<'_, R, C> {
+    type Action = Move;
+    type Data = ();
+
+    fn successors_with_actions(&self) -> Vec<(Move, Self)> {
+        let successors = self.board.successors_with_moves_in(self.order);
+        if let Some(trace) = self.trace {
+            //the search only asks for the successors of the states it expands
+            let boards = successors.iter().map(|(_, board)| *board).collect();
+            trace.borrow_mut().record(Expansion { board: self.board, h: self.h(), successors: boards });
+        }
+
+        let states: Vec<(Move, Self)> = successors.iter()
+            .map(|(next_move, board)| (*next_move, self.successor(*board)))
+            .collect();
+
+        if let Some(trace) = self.trace {
+            let mut trace = trace.borrow_mut();
+            if trace.checks_consistency() {
+                //every move costs one, so a consistent heuristic drops by one at most
+                for (_, state) in states.iter().filter(|(_, state)| self.h - state.h > 1) {
+                    trace.record_inconsistency(Inconsistency { from: self.board, from_h: self.h, to: state.board, to_h: state.h });
+                }
+            }
+        }
+
+        states
+    }
+
+    fn successors_with_actions_iter(&self) -> impl Iterator<Item = (Move, Self)> {
+        //the trace records every successor on expansion, so it needs them all at once
+        let state = *self;
+        let traced = self.trace.map(|_| self.successors_with_actions());
+        let lazy = traced.is_none().then(|| {
+            IntoIterator::into_iter(self.order.moves(&self.board))
+                .filter_map(move |next_move| state.board.apply_move(next_move).map(|board| (next_move, state.successor(board))))
+        });
+
+        traced.into_iter().flatten().chain(lazy.into_iter().flatten())
+    }
+
+    fn successors_with_data_kept<K: FnMut(Move) -> bool>(&self, _data: &(), mut keep: K) -> impl Iterator<Item = (Move, Self)> {
+        //the moves are vetoed on the boards they lead to, before the heuristic is worked out for them,
+        //unless the trace has them all made anyway
+        let state = *self;
+        let traced = self.trace.map(|_| self.successors_with_actions());
+        IntoIterator::into_iter(self.order.moves(&self.board))
+            .filter_map(move |next_move| state.board.apply_move(next_move).map(|board| (next_move, board)))
+            .filter(move |(next_move, _)| keep(*next_move))
+            .map(move |(next_move, board)| {
+                let successor = match &traced {
+                    Some(successors) => successors.iter().find(|(traced_move, _)| *traced_move == next_move).unwrap().1,
+                    None => state.successor(board),
+                };
+                (next_move, successor)
+            })
+    }
+
+    fn predecessors_with_actions(&self) -> Vec<(Move, Self)> {
+        //every move is undone by the opposite one
+        self.board.successors_with_moves_in(self.order).into_iter()
+            .map(|(next_move, board)| (next_move.inverse(), self.successor(board)))
+            .collect()
+    }
+
+    fn h(&self) -> i32 {
+        self.h
+    }
+}
+
+/// An 8-puzzle state stored as a single packed word, guided by the manhattan distance, which is
+/// updated from the tile each move slides rather than recomputed for every successor
+#[derive(Copy, Clone, Debug)]
+struct PackedState {
+    packed: Packed,
+    h: i32,
+}
+
+impl PackedState {
+    fn new(board: Board) -> PackedState {
+        PackedState { packed: Packed::from_tiles(board.into()), h: board.manhattan_dist() }
+    }
+
+    fn board(&self) -> Board {
+        Board::new(self.packed.to_tiles())
+    }
+}
+
+impl PartialEq for PackedState {
+    fn eq(&self, other: &Self) -> bool {
+        self.packed == other.packed
+    }
+}
+
+impl Eq for PackedState {}
+
+impl Hash for PackedState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.packed.hash(state);
+    }
+}
+
+impl State for PackedState {
+    type Action = Move;
+    type Data = ();
+
+    fn successors_with_actions(&self) -> Vec<(Move, Self)> {
+        let zero = self.packed.zero();
+        self.packed.successors_with_moves().into_iter()
+            .map(|(next_move, packed)| {
+                //the tile slid from the new blank position into the old one
+                let h = board::manhattan_dist_after_move::<3, 3>(packed.tile(zero) as Tile, packed.zero(), zero, self.h);
+                (next_move, PackedState { packed, h })
+            })
             .collect()
     }
 
     fn h(&self) -> i32 {
-        //todo: make the heuristic configurable
-        self.board.manhattan_dist()
-//        self.board.displaced_tiles()
+        self.h
     }
 }
 
-fn goal_check(candidate: &BoardState) -> bool {
-    candidate.board == board::GOAL
+/// A board moved in place by IDA*, guided by the manhattan distance updated from each moved tile
+#[derive(Debug)]
+struct BoardPath<const R: usize, const C: usize> {
+    board: Board<R, C>,
+    h: i32,
 }
 
-pub fn breadth_first_search(board: Board) -> Option<Vec<Board>> {
-    let initial_state = BoardState::new(board);
+impl<const R: usize, const C: usize> MutableState for BoardPath<R, C> {
+    type Action = Move;
+
+    fn actions(&self) -> Vec<Move> {
+        self.board.possible_moves().collect()
+    }
+
+    fn apply(&mut self, action: Move) {
+        let previous = self.board;
+        self.board.apply_move_in_place(action);
+        self.h = self.board.manhattan_dist_from(&previous, self.h);
+    }
+
+    fn undo(&mut self, action: Move) {
+        self.apply(action.inverse());
+    }
+
+    fn reverses(&self, action: Move, previous: Move) -> bool {
+        action == previous.inverse()
+    }
+
+    fn h(&self) -> i32 {
+        self.h
+    }
+}
+
+fn goal_check<const R: usize, const C: usize>(candidate: &BoardState<R, C>) -> bool {
+    candidate.board.is_goal()
+}
+
+pub fn manhattan_heuristic<const R: usize, const C: usize>(board: &Board<R, C>) -> i32 {
+    board.manhattan_dist()
+}
+
+/// The number of tiles out of place, which is weaker than the manhattan distance but cheaper
+pub fn displaced_tiles_heuristic<const R: usize, const C: usize>(board: &Board<R, C>) -> i32 {
+    board.displaced_tiles()
+}
+
+pub fn breadth_first_search<const R: usize, const C: usize>(board: Board<R, C>) -> Option<Plan<R, C>> {
+    let initial_state = BoardState::manhattan(board);
     let result = search::breadth_first_search(&initial_state, goal_check);
     process_result(result)
 }
 
-pub fn ehc_search(board: Board) -> Option<Vec<Board>> {
-    let initial_state = BoardState::new(board);
+pub fn ehc_search<const R: usize, const C: usize>(board: Board<R, C>) -> Option<Plan<R, C>> {
+    let result = search::ehc_search(&BoardState::manhattan(board), goal_check);
+    process_result(result)
+}
+
+pub fn ehc_search_with<const R: usize, const C: usize, H: Fn(&Board<R, C>) -> i32>(board: Board<R, C>, heuristic: H) -> Option<Plan<R, C>> {
+    let initial_state = BoardState::new(board, &heuristic);
     let result = search::ehc_search(&initial_state, goal_check);
     process_result(result)
 }
 
-pub fn ehc_steepest_search(board: Board) -> Option<Vec<Board>> {
-    let initial_state = BoardState::new(board);
+pub fn ehc_steepest_search<const R: usize, const C: usize>(board: Board<R, C>) -> Option<Plan<R, C>> {
+    let result = search::ehc_steepest_search(&BoardState::manhattan(board), goal_check);
+    process_result(result)
+}
+
+pub fn ehc_steepest_search_with<const R: usize, const C: usize, H: Fn(&Board<R, C>) -> i32>(board: Board<R, C>, heuristic: H) -> Option<Plan<R, C>> {
+    let initial_state = BoardState::new(board, &heuristic);
     let result = search::ehc_steepest_search(&initial_state, goal_check);
     process_result(result)
 }
 
-pub fn greedy_best_first_search(board: Board) -> Option<Vec<Board>> {
-    let initial_state = BoardState::new(board);
+pub fn greedy_best_first_search<const R: usize, const C: usize>(board: Board<R, C>) -> Option<Plan<R, C>> {
+    let result = search::greedy_best_first_search(&BoardState::manhattan(board), goal_check);
+    process_result(result)
+}
+
+pub fn greedy_best_first_search_with<const R: usize, const C: usize, H: Fn(&Board<R, C>) -> i32>(board: Board<R, C>, heuristic: H) -> Option<Plan<R, C>> {
+    let initial_state = BoardState::new(board, &heuristic);
     let result = search::greedy_best_first_search(&initial_state, goal_check);
     process_result(result)
 }
 
-pub fn a_star_search(board: Board) -> Option<Vec<Board>> {
-    let initial_state = BoardState::new(board);
+pub fn a_star_search<const R: usize, const C: usize>(board: Board<R, C>) -> Option<Plan<R, C>> {
+    let result = search::a_star_search(&BoardState::manhattan(board), goal_check);
+    process_result(result)
+}
+
+pub fn a_star_search_with<const R: usize, const C: usize, H: Fn(&Board<R, C>) -> i32>(board: Board<R, C>, heuristic: H) -> Option<Plan<R, C>> {
+    let initial_state = BoardState::new(board, &heuristic);
     let result = search::a_star_search(&initial_state, goal_check);
     process_result(result)
 }
 
-fn process_result(result: SearchResult<BoardState>) -> Option<Vec<Board>> {
-    println!("{:?}", result.statistics);
-    match result.plan {
-        Some(plan_states) => {
-            let mut plan = Vec::with_capacity(plan_states.len());
-            for state in plan_states {
-                plan.push(state.board);
-            }
+/// A* guided by the manhattan distance, like `a_star_search`, on boards packed into a single word,
+/// which makes the seen states smaller and faster to hash on long searches
+pub fn a_star_search_packed(board: Board) -> Option<Plan> {
+    let result = search::a_star_search(&PackedState::new(board), |state| state.h == 0);
+    let statistics = result.statistics;
+    let moves = result.plan_actions;
+    result.plan.zip(moves).map(|(plan_states, moves)| {
+        let boards = plan_states.iter().map(PackedState::board).collect();
+        Plan::from_search_moves(boards, moves, statistics)
+    })
+}
+
+/// MM bidirectional search guided by the manhattan distance both ways, which finds optimal plans
+/// like `a_star_search` with the two searches meeting halfway, so neither has to go as deep
+pub fn mm_search<const R: usize, const C: usize>(board: Board<R, C>) -> Option<Plan<R, C>> {
+    if !board.is_solvable() {
+        //both sides would go through every board they can reach before giving up
+        return None;
+    }
+
+    let initial = BoardState::manhattan(board);
+    let goal = BoardState::manhattan(Board::goal());
+    process_result(search::mm_search(&initial, &goal, |state| state.board.manhattan_dist_to(&board), Limits::none()))
+}
+
+/// IDA* guided by the manhattan distance, which finds optimal plans like `a_star_search` while only
+/// keeping the boards of the current path in memory, so it suits the 15-puzzle
+pub fn ida_star_search<const R: usize, const C: usize>(board: Board<R, C>) -> Option<Plan<R, C>> {
+    if !board.is_solvable() {
+        //without the seen boards, the bound would keep growing forever
+        return None;
+    }
+
+    let result = search::ida_star_search(BoardPath { board, h: board.manhattan_dist() }, |path| path.board.is_goal(), Limits::none());
+    process_path_result(board, result)
+}
+
+/// Iterative deepening depth first search, which finds the plans with the fewest moves like
+/// `breadth_first_search` while only keeping the boards of the current path in memory. It is
+/// much slower than `ida_star_search`, as it does not look at how far the boards are from the goal.
+pub fn iddfs_search<const R: usize, const C: usize>(board: Board<R, C>) -> Option<Plan<R, C>> {
+    if !board.is_solvable() {
+        //without the seen boards, the depth would keep growing forever
+        return None;
+    }
+
+    let result = search::iddfs_search(BoardPath { board, h: 0 }, |path| path.board.is_goal(), Limits::none());
+    process_path_result(board, result)
+}
+
+/// Depth first branch and bound guided by the manhattan distance, bounded by the plan of a greedy
+/// best first search, which it then improves on until it proves its plan optimal. It keeps as
+/// little in memory as `ida_star_search` once the greedy plan is found, and suits long plans
+/// where IDA* would go over the same boards again with every bound.
+pub fn dfbnb_search<const R: usize, const C: usize>(board: Board<R, C>) -> Option<Plan<R, C>> {
+    if !board.is_solvable() {
+        //the greedy search for the bound would go through every board reachable from this one
+        return None;
+    }
+
+    let greedy = greedy_best_first_search(board)?;
+    let bound = greedy.steps() as i32 + 1;
+    let result = search::dfbnb_search(BoardPath { board, h: board.manhattan_dist() }, |path| path.board.is_goal(), bound, Limits::none());
+    process_path_result(board, result)
+}
 
-            Some(plan)
+fn process_path_result<const R: usize, const C: usize>(board: Board<R, C>, result: PathResult<BoardPath<R, C>>) -> Option<Plan<R, C>> {
+    let statistics = result.statistics;
+    result.actions.map(|moves| {
+        let mut boards = vec![board];
+        for next_move in &moves {
+            boards.push(boards.last().unwrap().apply_move(*next_move).unwrap());
         }
+        Plan::from_search_moves(boards, moves, statistics)
+    })
+}
+
+/// The additive pattern databases of `solve_optimal_fast`, with five tiles each
+const FIFTEEN_PARTITION: [[Tile; 5]; 3] = [[1, 2, 5, 6, 9], [3, 4, 7, 8, 12], [10, 11, 13, 14, 15]];
+
+/// The depth of the perimeter of `solve_optimal_fast`
+const FIFTEEN_PERIMETER_DEPTH: u32 = 10;
+
+/// Optimal plans for the 15-puzzle, from IDA* guided by three additive pattern databases of five
+/// tiles, also looked up on the dual of the boards, towards the perimeter of the boards within ten
+/// moves of the goal. The databases and the perimeter are built by the first call, which takes a
+/// few seconds, and kept for the later ones.
+pub fn solve_optimal_fast(board: Board<4>) -> Option<Plan<4>> {
+    static TABLES: OnceLock<(DisjointPatternDatabases<4>, Perimeter<4>)> = OnceLock::new();
+    let (databases, perimeter) = TABLES.get_or_init(|| {
+        let partition: Vec<Vec<Tile>> = FIFTEEN_PARTITION.iter().map(|tiles| tiles.to_vec()).collect();
+        (DisjointPatternDatabases::build(&partition, Compression::None), Perimeter::build(FIFTEEN_PERIMETER_DEPTH))
+    });
+
+    perimeter::perimeter_search(board, perimeter, pdb::pattern_db_dual_heuristic(databases))
+}
+
+/// The manhattan distance above which A* keeps too many 15-puzzle boards in memory
+const AUTO_A_STAR_MAX_H: i32 = 30;
+
+/// The search picked by `solve_auto`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum AutoSearch {
+    AStar,
+    IdaStar,
+    GreedyBestFirst,
+}
 
-        None => None
+fn auto_search<const R: usize, const C: usize>(board: &Board<R, C>) -> AutoSearch {
+    let cells = R * C;
+    if cells <= 9 || (cells <= 16 && board.manhattan_dist() <= AUTO_A_STAR_MAX_H) {
+        AutoSearch::AStar
+    } else if cells <= 16 {
+        AutoSearch::IdaStar
+    } else {
+        AutoSearch::GreedyBestFirst
     }
 }
 
+/// Solves the board with a search suited to its size and difficulty: A* guided by the manhattan
+/// distance and linear conflicts for the 8-puzzle and for boards of the 15-puzzle close to the
+/// goal, IDA* for the other boards of the 15-puzzle, whose plans are also optimal but take longer
+/// to find, and greedy best first search guided by the manhattan distance for larger boards, where
+/// optimal plans are out of reach. IDA* is that of `solve_optimal_fast` on 4x4 boards, guided by
+/// pattern databases, and guided by the manhattan distance on the other boards of up to 16
+/// positions.
+/// Boards that cannot reach the goal have no plan.
+pub fn solve_auto<const R: usize, const C: usize>(board: Board<R, C>) -> Option<Plan<R, C>> {
+    if !board.is_solvable() {
+        return None;
+    }
+
+    match auto_search(&board) {
+        AutoSearch::AStar => a_star_search_with(board, heuristic::linear_conflict_heuristic),
+        AutoSearch::IdaStar if R == 4 && C == 4 => {
+            let fifteen = Board::<4>::try_from(board.tiles()).unwrap();
+            solve_optimal_fast(fifteen).map(|plan| {
+                let boards = plan.boards().iter().map(|board| Board::try_from(board.tiles()).unwrap()).collect();
+                Plan::from_search_moves(boards, plan.moves().to_vec(), plan.statistics().cloned().unwrap_or_default())
+            })
+        }
+        AutoSearch::IdaStar => ida_star_search(board),
+        AutoSearch::GreedyBestFirst => greedy_best_first_search(board),
+    }
+}
+
+/// Breadth first search until any of the goals in the set is reached
+pub fn breadth_first_search_goals<const R: usize, const C: usize>(board: Board<R, C>, goals: &GoalSet<R, C>) -> Option<Plan<R, C>> {
+    let heuristic = |board: &Board<R, C>| goals.manhattan_dist(board);
+    let initial_state = BoardState::new(board, &heuristic);
+    let result = search::breadth_first_search(&initial_state, |state| goals.contains(&state.board));
+    process_result(result)
+}
+
+/// Enforced hill climbing until any of the goals in the set is reached
+pub fn ehc_search_goals<const R: usize, const C: usize>(board: Board<R, C>, goals: &GoalSet<R, C>) -> Option<Plan<R, C>> {
+    let heuristic = |board: &Board<R, C>| goals.manhattan_dist(board);
+    let initial_state = BoardState::new(board, &heuristic);
+    let result = search::ehc_search(&initial_state, |state| goals.contains(&state.board));
+    process_result(result)
+}
+
+/// Enforced hill climbing with steepest ascent until any of the goals in the set is reached
+pub fn ehc_steepest_search_goals<const R: usize, const C: usize>(board: Board<R, C>, goals: &GoalSet<R, C>) -> Option<Plan<R, C>> {
+    let heuristic = |board: &Board<R, C>| goals.manhattan_dist(board);
+    let initial_state = BoardState::new(board, &heuristic);
+    let result = search::ehc_steepest_search(&initial_state, |state| goals.contains(&state.board));
+    process_result(result)
+}
+
+/// Greedy best first search until any of the goals in the set is reached, guided by the manhattan
+/// distance to the closest goal
+pub fn greedy_best_first_search_goals<const R: usize, const C: usize>(board: Board<R, C>, goals: &GoalSet<R, C>) -> Option<Plan<R, C>> {
+    let heuristic = |board: &Board<R, C>| goals.manhattan_dist(board);
+    let initial_state = BoardState::new(board, &heuristic);
+    let result = search::greedy_best_first_search(&initial_state, |state| goals.contains(&state.board));
+    process_result(result)
+}
+
+/// A* search until any of the goals in the set is reached, guided by the manhattan distance to the
+/// closest goal
+pub fn a_star_search_goals<const R: usize, const C: usize>(board: Board<R, C>, goals: &GoalSet<R, C>) -> Option<Plan<R, C>> {
+    let heuristic = |board: &Board<R, C>| goals.manhattan_dist(board);
+    let initial_state = BoardState::new(board, &heuristic);
+    let result = search::a_star_search(&initial_state, |state| goals.contains(&state.board));
+    process_result(result)
+}
+
+/// A* guided by the manhattan distance that only makes the moves `keep` allows, given the board and
+/// the board the move leads to, such as never moving a tile once it is home. The plan is the
+/// shortest one among the moves allowed, and the statistics count the moves left out.
+pub fn a_star_search_filtered<const R: usize, const C: usize, F>(board: Board<R, C>, keep: F) -> Option<Plan<R, C>>
+    where F: Fn(&Board<R, C>, &Board<R, C>) -> bool
+{
+    let hooks = SearchHooks::filter(|state: &BoardState<R, C>, next_move: Move| keep(&state.board, &state.board.apply_move(next_move).unwrap()));
+    let result = search::a_star_search_hooked(&BoardState::manhattan(board), goal_check, Limits::none(), hooks);
+    process_result(result)
+}
+
+/// A* search until the board matches the pattern, guided by the manhattan distance of the tiles the
+/// pattern places, which finds the fewest moves that reach a subgoal such as completing the top row
+pub fn a_star_search_pattern<const R: usize, const C: usize>(board: Board<R, C>, pattern: &GoalPattern<R, C>) -> Option<Plan<R, C>> {
+    let heuristic = |board: &Board<R, C>| pattern.manhattan_dist(board);
+    let initial_state = BoardState::new(board, &heuristic);
+    let result = search::a_star_search(&initial_state, |state| pattern.matches(&state.board));
+    process_result(result)
+}
+
+/// Searches for a plan to any board passing the goal test with the algorithm, giving up once any of
+/// the limits is reached, and keeping the storage of the search in the buffers for the next one.
+/// The informed searches order their states with the given kind of queue.
+pub(crate) fn search_limited<'a, G: Fn(&Board) -> bool>(initial_state: &BoardState<'a>, goal: G, algorithm: Algorithm, limits: Limits, queue: QueueKind, buffers: &mut SearchBuffers<BoardState<'a>>) -> SearchResult<BoardState<'a>> {
+    let goal_check = |state: &BoardState| goal(&state.board);
+    match algorithm {
+        Algorithm::BreadthFirst => search::breadth_first_search_buffered(initial_state, goal_check, limits, buffers),
+        Algorithm::Ehc => search::ehc_search_buffered(initial_state, goal_check, limits, buffers),
+        Algorithm::EhcSteepest => search::ehc_steepest_search_buffered(initial_state, goal_check, limits, buffers),
+        Algorithm::GreedyBestFirst => search::greedy_best_first_search_queued(initial_state, goal_check, limits, buffers, queue),
+        Algorithm::AStar => search::a_star_search_queued(initial_state, goal_check, limits, buffers, queue),
+    }
+}
+
+/// Uniform cost search on a board whose moves cost differently, the blind baseline of
+/// `a_star_search_costed`, which finds plans of the same cost
+pub fn uniform_cost_search_costed<const R: usize, const C: usize>(board: CostedBoard<R, C>) -> Option<Plan<R, C>> {
+    if !board.board().is_solvable() {
+        return None;
+    }
+
+    process_costed_result(search::uniform_cost_search(&board, |state| state.board().is_goal()))
+}
+
+/// A* search over a board whose moves have their own costs, for the plan with the lowest total cost
+/// (rather than the fewest moves), or None if the board cannot be solved
+pub fn a_star_search_costed<const R: usize, const C: usize>(board: CostedBoard<R, C>) -> Option<Plan<R, C>> {
+    if !board.board().is_solvable() {
+        return None;
+    }
+
+    process_costed_result(search::a_star_search(&board, |state| state.board().is_goal()))
+}
+
+fn process_costed_result<const R: usize, const C: usize>(result: SearchResult<CostedBoard<R, C>>) -> Option<Plan<R, C>> {
+    let cost = result.solution_cost;
+    let statistics = result.statistics;
+    let moves = result.plan_actions;
+    result.plan.zip(moves).zip(cost).map(|((plan_states, moves), cost)| {
+        let boards = plan_states.iter().map(|state| *state.board()).collect();
+        Plan::from_search_costed(boards, moves, cost, statistics)
+    })
+}
+
+/// A* search on a board whose edges wrap around, guided by the manhattan distance around the
+/// torus, or None if the board cannot be solved
+pub fn a_star_search_torus<const R: usize, const C: usize>(board: TorusBoard<R, C>) -> Option<Plan<R, C>> {
+    if !board.is_solvable() {
+        return None;
+    }
+
+    let result = search::a_star_search(&board, |state| state.is_goal());
+    let statistics = result.statistics;
+    result.plan.zip(result.plan_actions).map(|(plan_states, moves)| {
+        let boards = plan_states.iter().map(|state| *state.board()).collect();
+        Plan::from_search_moves(boards, moves, statistics)
+    })
+}
+
+fn process_result<const R: usize, const C: usize>(result: SearchResult<BoardState<R, C>>) -> Option<Plan<R, C>> {
+    let statistics = result.statistics;
+    let moves = result.plan_actions;
+    result.plan.zip(moves).map(|(plan_states, moves)| {
+        let boards = plan_states.iter().map(|state| state.board).collect();
+        Plan::from_search_moves(boards, moves, statistics)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::board::GOAL;
@@ -152,6 +696,22 @@ mod tests {
         expect_plan(result, 32);
     }
 
+    #[test]
+    fn test_a_star_packed_matches_a_star() {
+        for board in [Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]), Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1])] {
+            let expected = a_star_search(board).unwrap();
+            let plan = a_star_search_packed(board).unwrap();
+
+            //the same successor order and heuristic values make for the very same search
+            assert_eq!(plan.boards(), expected.boards());
+            assert_eq!(plan.moves(), expected.moves());
+            assert_eq!(plan.statistics().unwrap().expanded, expected.statistics().unwrap().expanded);
+            //the embedded profile does not estimate the memory
+            assert!(plan.statistics().unwrap().memory < expected.statistics().unwrap().memory || cfg!(feature = "embedded"));
+            expect_plan(Some(plan), 32);
+        }
+    }
+
     #[test]
     fn test_hard_board2_a_star() {
         let hard_board = Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]);
@@ -162,6 +722,47 @@ mod tests {
         expect_plan(result, 32);
     }
 
+    #[test]
+    fn test_solution_cost_against_initial_h() {
+        let hard_board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let initial_state = BoardState::new(hard_board, &manhattan_heuristic);
+
+        //with a consistent heuristic, the last f-layer of A* is the optimal cost
+        let result = search::a_star_search(&initial_state, goal_check);
+        assert_eq!(result.initial_h, hard_board.manhattan_dist());
+        assert_eq!(result.solution_cost, Some(31));
+        assert_eq!(result.max_f, 31);
+
+        let result = search::greedy_best_first_search(&initial_state, goal_check);
+        assert!(result.solution_cost.unwrap() > 31);
+    }
+
+    #[test]
+    fn test_inconsistent_heuristic_recorded() {
+        let hard_board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let tripled = |board: &Board| 3 * board.manhattan_dist();
+        let trace = RefCell::new(Trace::checking_consistency());
+        let initial_state = BoardState::new(hard_board, &tripled).traced(&trace);
+        search::a_star_search(&initial_state, goal_check);
+
+        let trace = trace.into_inner();
+        assert!(!trace.inconsistencies().is_empty());
+        for inconsistency in trace.inconsistencies() {
+            assert_eq!(inconsistency.from_h - inconsistency.to_h, 3);
+            assert!(inconsistency.from.move_to(&inconsistency.to).is_some());
+        }
+    }
+
+    #[test]
+    fn test_hard_board1_a_star_displaced_tiles() {
+        let hard_board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+
+        println!("Starting A* search with displaced tiles for hard board 1:\n{}", hard_board);
+        let result = a_star_search_with(hard_board, displaced_tiles_heuristic);
+
+        expect_plan(result, 32);
+    }
+
     #[test]
     fn test_hard_board1_ehc() {
         let tiles = [8, 6, 7, 2, 5, 4, 3, 0, 1];
@@ -210,14 +811,337 @@ mod tests {
         expect_plan(result, 46);
     }
 
-    fn expect_plan(result: Option<Vec<Board>>, len: usize) {
+    #[test]
+    fn test_a_star_goals() {
+        let hard_board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let goals = GoalSet::new(vec![GOAL, Board::new([0, 1, 2, 3, 4, 5, 6, 7, 8])]);
+
+        println!("Starting A* search with goal set for hard board 1:\n{}", hard_board);
+        let result = a_star_search_goals(hard_board, &goals);
+
+        assert!(result.is_some());
+        let plan = result.unwrap();
+        assert!(plan.steps() <= 31);
+        assert!(goals.contains(plan.end()));
+    }
+
+    #[test]
+    fn test_breadth_first_goals() {
+        let board = Board::new([1, 2, 3, 4, 5, 6, 0, 7, 8]);
+        let goals = GoalSet::new(vec![GOAL, Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8])]);
+
+        let result = breadth_first_search_goals(board, &goals);
+
+        assert!(result.is_some());
+        assert_eq!(result.unwrap().steps(), 1);
+    }
+
+    #[test]
+    fn test_fifteen_puzzle_a_star() {
+        let board = Board::try_from([[5, 1, 2, 4], [9, 6, 3, 8], [13, 10, 7, 11], [0, 14, 15, 12]]).unwrap();
+
+        println!("Starting A* search for the 15-puzzle:\n{}", board);
+        let plan = a_star_search(board).unwrap();
+
+        assert!(plan.end().is_goal());
+        assert_eq!(plan.steps(), breadth_first_search(board).unwrap().steps());
+        assert_eq!(plan.steps(), 9);
+    }
+
+    #[test]
+    fn test_rectangular_a_star() {
+        let board = Board::try_from([[0, 5, 4], [3, 2, 1]]).unwrap();
+        let plan = a_star_search(board).unwrap();
+
+        assert!(plan.end().is_goal());
+        assert_eq!(plan.steps(), breadth_first_search(board).unwrap().steps());
+    }
+
+    #[test]
+    fn test_ida_star() {
+        for board in [Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]), Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1])] {
+            let plan = ida_star_search(board);
+            assert_eq!(plan.as_ref().unwrap().start(), &board);
+            expect_plan(plan, 32);
+        }
+
+        let board = Board::try_from([[5, 1, 2, 4], [9, 6, 3, 8], [13, 10, 7, 11], [0, 14, 15, 12]]).unwrap();
+        assert_eq!(ida_star_search(board).unwrap().steps(), a_star_search(board).unwrap().steps());
+        assert!(ida_star_search(Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0])).is_none());
+    }
+
+    #[test]
+    fn test_mm_search() {
+        for board in [Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]), Board::new([1, 2, 3, 0, 4, 6, 7, 5, 8]), GOAL] {
+            let plan = mm_search(board).unwrap();
+            assert_eq!(*plan.start(), board);
+            assert!(plan.end().is_goal());
+            assert_eq!(plan.steps(), a_star_search(board).unwrap().steps());
+            assert!(Plan::new(plan.boards().to_vec()).is_ok());
+        }
+
+        let board = Board::try_from([[5, 1, 2, 4], [9, 6, 3, 8], [13, 10, 7, 11], [0, 14, 15, 12]]).unwrap();
+        assert_eq!(mm_search(board).unwrap().steps(), a_star_search(board).unwrap().steps());
+        assert!(mm_search(Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0])).is_none());
+    }
+
+    #[test]
+    fn test_predecessors() {
+        let board = Board::new([1, 2, 3, 4, 0, 5, 6, 7, 8]);
+        //the packed states find their predecessors by trying the moves back from their successors
+        let packed = PackedState::new(board).predecessors_with_actions();
+        let states = BoardState::manhattan(board).predecessors_with_actions();
+        assert_eq!(packed.len(), 4);
+        assert_eq!(packed.iter().map(|(next_move, state)| (*next_move, state.board())).collect::<Vec<_>>(),
+                   states.iter().map(|(next_move, state)| (*next_move, state.board)).collect::<Vec<_>>());
+        for (next_move, state) in states {
+            assert_eq!(state.board.apply_move(next_move), Some(board));
+        }
+    }
+
+    #[test]
+    fn test_iddfs() {
+        for board in [Board::new([1, 2, 3, 0, 4, 6, 7, 5, 8]), Board::new([4, 1, 3, 7, 2, 6, 5, 8, 0]), Board::new([0, 1, 3, 4, 2, 5, 7, 8, 6])] {
+            let plan = iddfs_search(board).unwrap();
+            assert_eq!(*plan.start(), board);
+            assert!(plan.end().is_goal());
+            assert_eq!(plan.steps(), breadth_first_search(board).unwrap().steps());
+        }
+
+        let board = Board::<2, 3>::try_from([[2, 0, 3], [1, 4, 5]]).unwrap();
+        assert_eq!(iddfs_search(board).unwrap().steps(), breadth_first_search(board).unwrap().steps());
+        assert!(iddfs_search(Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0])).is_none());
+    }
+
+    #[test]
+    fn test_dfbnb() {
+        for board in [Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]), Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]), Board::new([1, 2, 3, 0, 4, 6, 7, 5, 8]), GOAL] {
+            let plan = dfbnb_search(board).unwrap();
+            assert_eq!(*plan.start(), board);
+            assert!(plan.end().is_goal());
+            assert_eq!(plan.steps(), a_star_search(board).unwrap().steps());
+        }
+
+        let board = Board::try_from([[5, 1, 2, 4], [9, 6, 3, 8], [13, 10, 7, 11], [0, 14, 15, 12]]).unwrap();
+        assert_eq!(dfbnb_search(board).unwrap().steps(), a_star_search(board).unwrap().steps());
+        assert!(dfbnb_search(Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0])).is_none());
+        //rejected without searching half the boards of the 15-puzzle
+        assert!(dfbnb_search(Board::<4>::try_from([[2, 1, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12], [13, 14, 15, 0]]).unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_a_star_search_pattern() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let pattern: GoalPattern = "1 2 3 / * * * / * * *".parse().unwrap();
+        let plan = a_star_search_pattern(board, &pattern).unwrap();
+
+        assert!(pattern.matches(plan.end()));
+        assert!(plan.steps() < a_star_search(board).unwrap().steps());
+        let heuristic = |board: &Board| pattern.manhattan_dist(board);
+        let result = search::breadth_first_search(&BoardState::new(board, &heuristic), |state| pattern.matches(&state.board));
+        assert_eq!(plan.steps(), result.solution_cost.unwrap() as usize);
+
+        let board = Board::<4>::try_from([[5, 1, 2, 4], [9, 6, 3, 8], [13, 10, 7, 11], [0, 14, 15, 12]]).unwrap();
+        let plan = a_star_search_pattern(board, &GoalPattern::from_goal(&Board::goal(), &[1, 2, 3, 4])).unwrap();
+        assert_eq!(&plan.end().tiles()[..4], &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_a_star_search_costed() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let plan = a_star_search_costed(CostedBoard::new(board, |_, _, _| 1)).unwrap();
+        assert_eq!(plan.steps(), 31);
+        assert_eq!(plan.cost(), 31);
+
+        //horizontal moves cost two, so the plan costs more than its moves
+        let horizontal_double = |_, from: usize, to: usize| if from / 3 == to / 3 { 2 } else { 1 };
+        let board = Board::new([4, 1, 3, 7, 2, 6, 5, 8, 0]);
+        let plan = a_star_search_costed(CostedBoard::new(board, horizontal_double)).unwrap();
+        assert!(plan.end().is_goal());
+        assert_eq!(CostedBoard::new(board, horizontal_double).plan_cost(plan.moves()), Some(plan.cost()));
+        assert_eq!(plan.cost(), 12);
+        assert_eq!(uniform_cost_search_costed(CostedBoard::new(board, horizontal_double)).unwrap().cost(), 12);
+
+        assert!(a_star_search_costed(CostedBoard::new(Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]), |_, _, _| 1)).is_none());
+    }
+
+    #[test]
+    fn test_a_star_search_torus() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let plan = a_star_search_torus(TorusBoard::new(board)).unwrap();
+        let optimal = search::breadth_first_search(&TorusBoard::new(board), |state| state.is_goal());
+
+        assert!(plan.end().is_goal());
+        assert_eq!(plan.steps(), optimal.plan.unwrap().len() - 1);
+        assert!(plan.steps() < 31);
+        assert!(a_star_search_torus(TorusBoard::new(Board::<2, 4>::try_from([[2, 1, 3, 4], [5, 6, 7, 0]]).unwrap())).is_none());
+    }
+
+    #[test]
+    fn test_solve_auto() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        assert_eq!(auto_search(&board), AutoSearch::AStar);
+        expect_plan(solve_auto(board), 32);
+        assert!(solve_auto(Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0])).is_none());
+        assert!(solve_auto(Board::<1, 3>::try_from([[2, 0, 1]]).unwrap()).is_none());
+        assert_eq!(solve_auto(Board::<1, 3>::try_from([[0, 1, 2]]).unwrap()).unwrap().steps(), 2);
+
+        let easy = Board::try_from([[5, 1, 2, 4], [9, 6, 3, 8], [13, 10, 7, 11], [0, 14, 15, 12]]).unwrap();
+        assert_eq!(auto_search(&easy), AutoSearch::AStar);
+        assert_eq!(solve_auto(easy).unwrap().steps(), 9);
+        let hard = Board::try_from([[15, 14, 13, 12], [11, 10, 9, 8], [7, 6, 5, 4], [3, 1, 2, 0]]).unwrap();
+        assert_eq!(auto_search(&hard), AutoSearch::IdaStar);
+        let far: Board<4> = "6 0 5 3 / 13 2 9 11 / 1 8 15 10 / 14 12 7 4".parse().unwrap();
+        assert_eq!(auto_search(&far), AutoSearch::IdaStar);
+        let plan = solve_auto(far).unwrap();
+        assert_eq!((*plan.start(), plan.end().is_goal()), (far, true));
+        let manhattan = ida_star_search(far).unwrap();
+        assert_eq!(plan.steps(), manhattan.steps());
+        //guided by the pattern databases, rather than the manhattan distance
+        assert!(plan.statistics().unwrap().expanded < manhattan.statistics().unwrap().expanded);
+        assert_eq!(plan.moves().len(), plan.steps());
+        for (step, next_move) in plan.boards().windows(2).zip(plan.moves()) {
+            assert_eq!(step[0].apply_move(*next_move), Some(step[1]));
+        }
+
+        let mut large = Board::<5>::goal();
+        for step in 0..200 {
+            let successors = large.successors();
+            large = successors[(step * 7) % successors.len()];
+        }
+        assert_eq!(auto_search(&large), AutoSearch::GreedyBestFirst);
+        assert!(solve_auto(large).unwrap().end().is_goal());
+    }
+
+    #[test]
+    fn test_incremental_manhattan() {
+        let state = BoardState::manhattan(Board::<4>::try_from([[5, 1, 2, 4], [9, 6, 3, 8], [13, 10, 7, 11], [0, 14, 15, 12]]).unwrap());
+        let mut states = vec![state];
+        for _ in 0..6 {
+            states = states.iter().flat_map(|state| state.successors()).collect();
+            assert!(states.iter().all(|state| state.h() == state.board.manhattan_dist()));
+        }
+
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let plan = a_star_search(board).unwrap();
+        assert_eq!(plan.statistics().unwrap().expanded, a_star_search_with(board, manhattan_heuristic).unwrap().statistics().unwrap().expanded);
+    }
+
+    #[test]
+    fn test_filtered() {
+        //the shortest plan already leaves tile 1 home
+        let board = Board::new([1, 2, 3, 4, 5, 6, 0, 7, 8]);
+        let plan = a_star_search_filtered(board, |_, next| next.get(0, 0) == Some(1)).unwrap();
+        assert!(plan.boards().iter().all(|board| board.get(0, 0) == Some(1)));
+        assert_eq!(plan.steps(), 2);
+
+        let board = Board::new([1, 6, 2, 5, 3, 0, 4, 7, 8]);
+        let plan = a_star_search_filtered(board, |_, next| next.get(0, 0) == Some(1)).unwrap();
+        assert!(plan.boards().iter().all(|board| board.get(0, 0) == Some(1)));
+        assert!(plan.steps() >= a_star_search(board).unwrap().steps());
+        //the embedded profile does not count the vetoed successors
+        assert!(plan.statistics().unwrap().vetoed > 0 || cfg!(feature = "embedded"));
+
+        //without any move allowed, only the goal has a plan
+        assert!(a_star_search_filtered(board, |_, _| false).is_none());
+        assert_eq!(a_star_search_filtered(GOAL, |_, _| false).unwrap().steps(), 0);
+    }
+
+    #[test]
+    fn test_filter_before_heuristic() {
+        let evaluated = std::cell::Cell::new(0);
+        let heuristic = |board: &Board| {
+            evaluated.set(evaluated.get() + 1);
+            manhattan_heuristic(board)
+        };
+        let initial = BoardState::new(Board::new([1, 2, 3, 4, 5, 6, 0, 7, 8]), &heuristic);
+        let result = search::a_star_search_hooked(&initial, goal_check, Limits::none(), SearchHooks::filter(|_, _| false));
+
+        //the two moves are left out without working out the heuristic of their boards
+        assert!(result.plan.is_none());
+        assert_eq!(result.statistics.vetoed, if cfg!(feature = "embedded") { 0 } else { 2 });
+        assert_eq!(evaluated.get(), 1);
+    }
+
+    #[test]
+    fn test_solve_optimal_fast() {
+        let board: Board<4> = "5 1 3 4 / 9 2 7 8 / 13 6 10 12 / 14 0 11 15".parse().unwrap();
+        let plan = solve_optimal_fast(board).unwrap();
+        assert!(plan.end().is_goal());
+        assert_eq!(plan.steps(), ida_star_search(board).unwrap().steps());
+
+        //further from the goal than the perimeter reaches
+        let board: Board<4> = "2 3 4 8 / 1 6 7 12 / 5 10 11 15 / 9 13 14 0".parse().unwrap();
+        let plan = solve_optimal_fast(board).unwrap();
+        assert!(plan.steps() > FIFTEEN_PERIMETER_DEPTH as usize);
+        assert_eq!(plan.steps(), ida_star_search(board).unwrap().steps());
+        assert!(solve_optimal_fast("2 1 3 4 / 5 6 7 8 / 9 10 11 12 / 13 14 15 0".parse().unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_breadth_first_layers() {
+        let mut sizes = Vec::new();
+        let result = search::breadth_first_layers(&BoardState::manhattan(GOAL), |_| false, false, Limits::none(), |layer| sizes.push(layer.size));
+
+        assert!(result.plan.is_none());
+        assert_eq!(result.statistics.seen, if cfg!(feature = "embedded") { 0 } else { 181_440 });
+        assert_eq!(sizes, layers::layer_sizes(GOAL));
+    }
+
+    #[test]
+    fn test_lazy_successors() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        for order in [MoveOrder::Default, MoveOrder::Reversed, MoveOrder::Random(7)] {
+            let state = BoardState::manhattan(board).ordered(order);
+            let lazy: Vec<(Move, BoardState)> = state.successors_with_actions_iter().collect();
+            let eager = state.successors_with_actions();
+            assert_eq!(lazy.iter().map(|(next_move, state)| (*next_move, state.board, state.h())).collect::<Vec<_>>(),
+                       eager.iter().map(|(next_move, state)| (*next_move, state.board, state.h())).collect::<Vec<_>>());
+        }
+
+        //only the successors taken are made, so the heuristic of the others is never computed
+        let calls = std::cell::Cell::new(0);
+        let heuristic = |board: &Board| {
+            calls.set(calls.get() + 1);
+            board.manhattan_dist()
+        };
+        assert_eq!(BoardState::new(board, &heuristic).successors_iter().take(1).count(), 1);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_heuristic_computed_once_per_state() {
+        let calls = std::cell::Cell::new(0);
+        let heuristic = |board: &Board| {
+            calls.set(calls.get() + 1);
+            board.manhattan_dist()
+        };
+
+        let state = BoardState::new(Board::new([1, 2, 3, 4, 0, 6, 7, 5, 8]), &heuristic);
+        assert_eq!(state.h(), 2);
+        assert_eq!(state.h(), 2);
+        assert_eq!(calls.get(), 1);
+
+        let successors = state.successors();
+        for successor in successors.iter() {
+            assert_eq!(successor.h(), successor.board.manhattan_dist());
+            assert_eq!(successor.h(), successor.board.manhattan_dist());
+        }
+        assert_eq!(calls.get(), 1 + successors.len());
+    }
+
+    fn expect_plan(result: Option<Plan>, len: usize) {
         assert!(result.is_some());
 
         if let Some(plan) = result {
-            let goal_state = plan.last().unwrap();
-            assert_eq!(plan.len(), len);
+            let goal_state = plan.end();
+            assert_eq!(plan.boards().len(), len);
+            assert_eq!(plan.cost() as usize, len - 1);
+            //the moves reported by the search are the ones between the boards
+            assert_eq!(plan.moves(), Plan::new(plan.boards().to_vec()).unwrap().moves());
             assert_eq!(*goal_state, GOAL);
-            println!("Plan length: {:?}", plan.len());
+            assert!(plan.statistics().is_some());
+            println!("Plan length: {:?}", plan.boards().len());
             println!("Goal board state found:\n{}", goal_state);
         }
     }
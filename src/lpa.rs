@@ -0,0 +1,250 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::board::Board;
+use crate::plan::Plan;
+use crate::search::State;
+use crate::BoardState;
+
+const INFINITY: u32 = u32::MAX;
+
+/// Lifelong Planning A* (Koenig & Likhachev) over unit cost, reversible transitions.
+/// The g and rhs values are distances from the start state, so they remain valid when the goal
+/// changes. Only the priorities of the locally inconsistent states need to be recomputed for the
+/// new goal, and the search resumes from the previous search tree instead of starting over.
+pub struct LpaStar<S: State, H: Fn(&S, &S) -> i32> {
+    start: S,
+    goal: S,
+    heuristic: H,
+    g: HashMap<S, u32>,
+    rhs: HashMap<S, u32>,
+    inconsistent: HashSet<S>,
+    open: BinaryHeap<Entry<S>>,
+    expanded: u32,
+}
+
+#[derive(Debug)]
+struct Entry<S> {
+    key: (u32, u32),
+    state: S,
+}
+
+impl<S> PartialEq for Entry<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<S> Eq for Entry<S> {}
+
+impl<S> PartialOrd for Entry<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S> Ord for Entry<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        //reverse comparison to get min heap
+        other.key.cmp(&self.key)
+    }
+}
+
+impl<S: State, H: Fn(&S, &S) -> i32> LpaStar<S, H> {
+    /// Creates the search, where the heuristic estimates the distance from a state (first argument)
+    /// to the goal (second argument). The heuristic needs to be consistent for every goal used.
+    pub fn new(start: S, goal: S, heuristic: H) -> LpaStar<S, H> {
+        let mut lpa = LpaStar {
+            start,
+            goal,
+            heuristic,
+            g: HashMap::new(),
+            rhs: HashMap::new(),
+            inconsistent: HashSet::new(),
+            open: BinaryHeap::new(),
+            expanded: 0,
+        };
+
+        lpa.rhs.insert(start, 0);
+        lpa.inconsistent.insert(start);
+        lpa.open.push(Entry { key: lpa.key(&start), state: start });
+
+        lpa
+    }
+
+    pub fn goal(&self) -> &S {
+        &self.goal
+    }
+
+    /// How many states were expanded over the lifetime of this search
+    pub fn expanded(&self) -> u32 {
+        self.expanded
+    }
+
+    /// Changes the goal, keeping the search tree computed so far
+    pub fn set_goal(&mut self, goal: S) {
+        self.goal = goal;
+
+        //the priorities depend on the heuristic towards the goal, so they all need recomputing
+        let open: Vec<Entry<S>> = self.inconsistent.iter()
+            .map(|state| Entry { key: self.key(state), state: *state })
+            .collect();
+        self.open = BinaryHeap::from(open);
+    }
+
+    /// Brings the search tree up to date for the current goal, and returns the shortest path
+    /// from the start to the goal, if one exists.
+    pub fn plan(&mut self) -> Option<Vec<S>> {
+        self.compute_shortest_path();
+        self.extract_path()
+    }
+
+    fn g(&self, state: &S) -> u32 {
+        *self.g.get(state).unwrap_or(&INFINITY)
+    }
+
+    fn rhs(&self, state: &S) -> u32 {
+        *self.rhs.get(state).unwrap_or(&INFINITY)
+    }
+
+    fn key(&self, state: &S) -> (u32, u32) {
+        let min_g = self.g(state).min(self.rhs(state));
+        let h = (self.heuristic)(state, &self.goal).max(0) as u32;
+        (min_g.saturating_add(h), min_g)
+    }
+
+    fn update_state(&mut self, state: S) {
+        if state != self.start {
+            let rhs = state.successors().iter()
+                .map(|predecessor| self.g(predecessor).saturating_add(1))
+                .min()
+                .unwrap_or(INFINITY);
+            self.rhs.insert(state, rhs);
+        }
+
+        if self.g(&state) != self.rhs(&state) {
+            self.inconsistent.insert(state);
+            self.open.push(Entry { key: self.key(&state), state });
+        } else {
+            self.inconsistent.remove(&state);
+        }
+    }
+
+    fn compute_shortest_path(&mut self) {
+        while let Some(top) = self.open.peek() {
+            let goal = self.goal;
+            if top.key >= self.key(&goal) && self.g(&goal) == self.rhs(&goal) {
+                break;
+            }
+
+            let entry = self.open.pop().unwrap();
+            let state = entry.state;
+            //skip stale entries, the state was either made consistent or pushed with a newer key
+            if !self.inconsistent.contains(&state) || entry.key != self.key(&state) {
+                continue;
+            }
+
+            self.expanded += 1;
+            self.inconsistent.remove(&state);
+            if self.g(&state) > self.rhs(&state) {
+                self.g.insert(state, self.rhs(&state));
+            } else {
+                self.g.insert(state, INFINITY);
+                self.update_state(state);
+            }
+
+            for successor in state.successors() {
+                self.update_state(successor);
+            }
+        }
+    }
+
+    fn extract_path(&self) -> Option<Vec<S>> {
+        if self.g(&self.goal) == INFINITY {
+            return None;
+        }
+
+        let mut path = vec![self.goal];
+        let mut current = self.goal;
+        while current != self.start {
+            current = current.successors().into_iter()
+                .min_by_key(|predecessor| self.g(predecessor))
+                .unwrap();
+            path.push(current);
+        }
+
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Incremental search over boards, guided by the manhattan distance to the current goal
+pub struct IncrementalSearch {
+    lpa: LpaStar<BoardState<'static>, fn(&BoardState, &BoardState) -> i32>,
+}
+
+impl IncrementalSearch {
+    pub fn new(board: Board, goal: Board) -> IncrementalSearch {
+        let heuristic: fn(&BoardState, &BoardState) -> i32 = |state, goal| state.board.manhattan_dist_to(&goal.board);
+        let lpa = LpaStar::new(board_state(board), board_state(goal), heuristic);
+        IncrementalSearch { lpa }
+    }
+
+    pub fn set_goal(&mut self, goal: Board) {
+        self.lpa.set_goal(board_state(goal));
+    }
+
+    pub fn plan(&mut self) -> Option<Plan> {
+        self.lpa.plan()
+            .map(|path| Plan::new(path.iter().map(|state| state.board).collect()).unwrap())
+    }
+
+    pub fn expanded(&self) -> u32 {
+        self.lpa.expanded()
+    }
+}
+
+fn board_state(board: Board) -> BoardState<'static> {
+    BoardState::new(board, &crate::manhattan_heuristic)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::GOAL;
+
+    use super::*;
+
+    #[test]
+    fn test_plan() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let mut search = IncrementalSearch::new(board, GOAL);
+
+        let plan = search.plan().unwrap();
+        assert_eq!(plan.steps(), 31);
+        assert_eq!(*plan.start(), board);
+        assert_eq!(*plan.end(), GOAL);
+    }
+
+    #[test]
+    fn test_goal_change_reuses_search() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let mut search = IncrementalSearch::new(board, GOAL);
+        search.plan().unwrap();
+        let first_expanded = search.expanded();
+
+        //a goal one move away from the previous one
+        let new_goal = Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8]);
+        search.set_goal(new_goal);
+        let plan = search.plan().unwrap();
+
+        assert_eq!(*plan.end(), new_goal);
+        assert_eq!(plan.steps(), crate::a_star_search_goals(board, &crate::goal::GoalSet::single(new_goal)).unwrap().steps());
+        assert!(search.expanded() - first_expanded < first_expanded);
+    }
+
+    #[test]
+    fn test_plan_to_start() {
+        let mut search = IncrementalSearch::new(GOAL, GOAL);
+        assert_eq!(search.plan().unwrap().boards(), &[GOAL]);
+    }
+}
@@ -1,12 +1,35 @@
-use tiles::board::Board;
+use tiles::analysis::{ExpansionHeatmap, TraceDiff};
+use tiles::board::{Board, Tile, BLANK_FIRST_GOAL, GOAL};
+use tiles::cache::{PlanCache, DEFAULT_CAPACITY};
+use tiles::compare::{compare_to, configurations};
+use tiles::dataset::{DatasetGenerator, LengthHistogram, Sampling};
+use tiles::experiments::{write_csv, write_json, Experiment};
+use tiles::heuristic::HeuristicKind;
+use tiles::instances::InstanceSet;
+use tiles::narration::narrate_a_star;
+use tiles::oracle::OracleTable;
+use tiles::plan::Plan;
+use tiles::queue::QueueKind;
+use tiles::schedule::solve_scheduled;
+use tiles::search::{CancellationToken, Limits, ProgressHook};
+use tiles::solver::{Algorithm, SolveResult, Solver};
+use tiles::trace::Trace;
 use std::env;
+use std::fs::File;
+use std::io;
 use std::process::exit;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-fn process_plan(plan_opt: Option<Vec<Board>>) {
+fn process_plan(plan_opt: Option<Plan>) {
     match plan_opt {
         Some(plan) => {
-            println!("Found plan of {} steps", plan.len() - 1);
-            for board in plan {
+            //the embedded profile has next to no statistics to show
+            if let Some(statistics) = plan.statistics().filter(|_| !cfg!(feature = "embedded")) {
+                println!("{:?}", statistics);
+            }
+            println!("Found plan of {} steps", plan.steps());
+            for board in plan.boards() {
                 println!("{}", board);
             }
         }
@@ -15,6 +38,20 @@ fn process_plan(plan_opt: Option<Vec<Board>>) {
     }
 }
 
+fn process_result(result: SolveResult) {
+    if let Some(reason) = result.stop_reason {
+        println!("Search interrupted: {}!", reason);
+        if !cfg!(feature = "embedded") {
+            println!("{:?}", result.statistics);
+            println!("Elapsed time: {:?}", result.statistics.duration);
+        }
+        println!("Best board found (h = {}):", result.best_h);
+        println!("{}", result.best);
+    } else {
+        process_plan(result.plan);
+    }
+}
+
 fn help() {
     println!("Specify your initial board configuration as a sequence of numbers from 0 to 8 (inclusive) separated by space, as command line arguments.");
     println!("The number 0 represent the empty blank space.");
@@ -22,6 +59,389 @@ fn help() {
     println!("  1 2 5");
     println!("  3 4 6");
     println!("  7 8 0");
+    println!();
+    println!("The --board and --goal options also accept commas, or rows separated by slashes, such as \"1 2 5 / 3 4 6 / 7 8 0\".");
+    println!();
+    println!("To compare the algorithms and heuristics on a board:");
+    println!("  tiles compare --board \"1 2 5 3 4 6 7 8 0\" [--algorithms bfs,ehc,ehc-steepest,greedy,astar] [--heuristics manhattan,displaced] [--goal blank-last|blank-first]");
+    println!("To write random solvable boards to an instance file, along with how they were generated:");
+    println!("  tiles generate --output boards.txt [--instances 100] [--seed 0] [--sampling uniform|walk-20]");
+    println!("To solve every board of an instance file:");
+    println!("  tiles solve --batch boards.txt [--algorithm astar] [--heuristic manhattan] [--budget 60] [--cache plans.txt]");
+    println!("  --budget solves the boards one after the other within that many seconds, the easiest first, giving each a share of the time left based on its heuristic value and the solve times so far.");
+    println!("  --cache answers the boards solved in earlier runs from the plans kept in that file, and keeps the new ones in it, optimal algorithms only reusing the plans proven optimal.");
+    println!("To summarize the algorithms and heuristics over random solvable boards (or those of an instance file):");
+    println!("  tiles benchmark [--instances 100] [--seed 0] [--input boards.txt] [--algorithms astar,greedy] [--heuristics manhattan] [--queue binary|4-ary|pairing|buckets] [--format csv|json] [--progress 1000] [--budget 60]");
+    println!("  --progress writes a JSON progress event of every search to stderr each given number of milliseconds.");
+    println!("To count how many random solvable boards have each optimal plan length:");
+    println!("  tiles histogram [--instances 1000] [--seed 0] [--sampling uniform|walk-20]");
+    println!("To show where the blank was in the boards expanded by a search:");
+    println!("  tiles heatmap --board \"1 2 5 3 4 6 7 8 0\" [--algorithm astar] [--heuristic manhattan] [--goal blank-last|blank-first]");
+    println!("To save the boards expanded by a search, then compare the boards expanded by two saved searches:");
+    println!("  tiles trace --board \"1 2 5 3 4 6 7 8 0\" --output manhattan.trace [--algorithm astar] [--heuristic manhattan] [--goal blank-last|blank-first]");
+    println!("  tiles diff manhattan.trace displaced.trace [--list]");
+    println!("To narrate the first expansions of A*, as text or JSON:");
+    println!("  tiles narrate --board \"1 2 5 3 4 6 7 8 0\" [--expansions 10] [--heuristic manhattan] [--format text|json]");
+    println!("To write the optimal distance of every board to a table file, then look up boards in it with the table mapped into memory (shared by every process mapping it):");
+    println!("  tiles oracle --output oracle.bin");
+    println!("  tiles oracle --table oracle.bin --board \"1 2 5 3 4 6 7 8 0\"");
+    println!("To write a web page stepping through a search:");
+    println!("  tiles visualize --board \"1 2 5 3 4 6 7 8 0\" --output search.html [--algorithm astar] [--heuristic manhattan] [--goal blank-last|blank-first]");
+}
+
+fn parse_board(value: &str) -> Board {
+    value.parse().unwrap_or_else(|e| panic!("{}", e))
+}
+
+fn parse_goal(value: &str) -> Board {
+    match value {
+        "blank-last" => GOAL,
+        "blank-first" => BLANK_FIRST_GOAL,
+        _ => parse_board(value),
+    }
+}
+
+/// Stops before searching from a board that cannot reach the goal, since the search would only
+/// give up after going through every board it can reach
+fn check_solvable(board: &Board, goal: &Board) {
+    if !board.is_solvable_to(goal) {
+        eprintln!("The board cannot be solved: no sequence of moves reaches the goal from it.");
+        exit(1);
+    }
+}
+
+fn parse_list<T: std::str::FromStr>(list: &str) -> Vec<T> where T::Err: std::fmt::Display {
+    list.split(',')
+        .map(|name| name.trim().parse::<T>().unwrap_or_else(|e| panic!("{}", e)))
+        .collect()
+}
+
+fn run_compare(args: &[String]) {
+    let mut board = None;
+    let mut goal = GOAL;
+    let mut algorithms = Algorithm::all().to_vec();
+    let mut heuristics = HeuristicKind::all().to_vec();
+
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| panic!("Missing value for {}.", option));
+        match option.as_str() {
+            "--board" => board = Some(parse_board(value)),
+            "--goal" => goal = parse_goal(value),
+            "--algorithms" => algorithms = parse_list(value),
+            "--heuristics" => heuristics = parse_list(value),
+            _ => panic!("Unknown option: {}", option),
+        }
+    }
+
+    let board = board.expect("Expecting the board to compare on, with --board.");
+    check_solvable(&board, &goal);
+    println!("{}", board);
+    println!("{}", compare_to(board, &goal, &configurations(&algorithms, &heuristics)));
+}
+
+/// The options of the subcommands that run a single search
+struct SearchOptions {
+    board: Board,
+    solver: Solver,
+    output: Option<String>,
+}
+
+fn parse_search_options(args: &[String]) -> SearchOptions {
+    let mut board = None;
+    let mut output = None;
+    let mut solver = Solver::new(Algorithm::AStar);
+
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| panic!("Missing value for {}.", option));
+        match option.as_str() {
+            "--board" => board = Some(parse_board(value)),
+            "--output" => output = Some(value.clone()),
+            "--goal" => solver = solver.with_goal(parse_goal(value)),
+            "--algorithm" => {
+                let algorithm = value.parse().unwrap_or_else(|e| panic!("{}", e));
+                solver = Solver::new(algorithm).with_heuristic(solver.heuristic()).with_goal(*solver.goal());
+            }
+            "--heuristic" => solver = solver.with_heuristic(value.parse().unwrap_or_else(|e| panic!("{}", e))),
+            _ => panic!("Unknown option: {}", option),
+        }
+    }
+
+    let board = board.expect("Expecting the board to search from, with --board.");
+    check_solvable(&board, solver.goal());
+    SearchOptions { board, solver, output }
+}
+
+fn run_benchmark(args: &[String]) {
+    let mut instances = 100;
+    let mut seed = 0;
+    let mut algorithms = Algorithm::all().to_vec();
+    let mut heuristics = HeuristicKind::all().to_vec();
+    let mut format = "csv".to_string();
+    let mut queue = QueueKind::BinaryHeap;
+    let mut limits = Limits::none();
+    let mut input = None;
+    let mut budget = None;
+
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| panic!("Missing value for {}.", option));
+        match option.as_str() {
+            "--instances" => instances = value.parse().unwrap_or_else(|_| panic!("Invalid number of instances: {}", value)),
+            "--seed" => seed = value.parse().unwrap_or_else(|_| panic!("Invalid seed: {}", value)),
+            "--algorithms" => algorithms = parse_list(value),
+            "--heuristics" => heuristics = parse_list(value),
+            "--queue" => queue = value.parse().unwrap_or_else(|e| panic!("{}", e)),
+            "--progress" => {
+                let interval = value.parse().unwrap_or_else(|_| panic!("Invalid progress interval: {}", value));
+                limits.progress = Some(ProgressHook::new(Duration::from_millis(interval), |progress| eprintln!("{}", progress.to_json())));
+            }
+            "--format" => format = value.clone(),
+            "--input" => input = Some(value.clone()),
+            "--budget" => budget = Some(parse_budget(value)),
+            _ => panic!("Unknown option: {}", option),
+        }
+    }
+
+    let boards = match input {
+        Some(path) => load_instances(&path).boards,
+        None => InstanceSet::generate(Sampling::Uniform, seed, instances).boards,
+    };
+    let configurations = configurations(&algorithms, &heuristics);
+    let template = Solver::new(Algorithm::AStar).with_queue(queue).with_limits(limits);
+    let experiment = match budget {
+        Some(budget) => Experiment::run_scheduled(&boards, &configurations, &template, budget),
+        None => Experiment::run(&boards, &configurations, &template),
+    };
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match format.as_str() {
+        "csv" => write_csv(&mut out, &experiment.summarize()),
+        "json" => write_json(&mut out, &experiment.summarize()),
+        _ => panic!("Unknown format: {} - Expecting csv or json.", format),
+    }.expect("Unable to write the summary.");
+}
+
+fn parse_budget(value: &str) -> Duration {
+    value.parse().ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .unwrap_or_else(|| panic!("Invalid budget: {} - Expecting a number of seconds.", value))
+}
+
+fn load_instances(path: &str) -> InstanceSet {
+    InstanceSet::load(path).unwrap_or_else(|e| panic!("Unable to read {}: {}", path, e))
+}
+
+fn run_generate(args: &[String]) {
+    let mut instances = 100;
+    let mut seed = 0;
+    let mut sampling = Sampling::Uniform;
+    let mut output = None;
+
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| panic!("Missing value for {}.", option));
+        match option.as_str() {
+            "--instances" => instances = value.parse().unwrap_or_else(|_| panic!("Invalid number of instances: {}", value)),
+            "--seed" => seed = value.parse().unwrap_or_else(|_| panic!("Invalid seed: {}", value)),
+            "--sampling" => sampling = value.parse().unwrap_or_else(|e| panic!("{}", e)),
+            "--output" => output = Some(value.clone()),
+            _ => panic!("Unknown option: {}", option),
+        }
+    }
+
+    let output = output.expect("Expecting the instance file to write, with --output.");
+    InstanceSet::generate(sampling, seed, instances).save(&output).unwrap_or_else(|e| panic!("Unable to write {}: {}", output, e));
+    println!("Wrote {} boards to {}", instances, output);
+}
+
+fn run_solve(args: &[String]) {
+    let mut batch = None;
+    let mut solver = Solver::new(Algorithm::AStar);
+    let mut budget = None;
+    let mut cache_path = None;
+
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| panic!("Missing value for {}.", option));
+        match option.as_str() {
+            "--batch" => batch = Some(value.clone()),
+            "--algorithm" => solver = Solver::new(value.parse().unwrap_or_else(|e| panic!("{}", e))).with_heuristic(solver.heuristic()),
+            "--heuristic" => solver = solver.with_heuristic(value.parse().unwrap_or_else(|e| panic!("{}", e))),
+            "--budget" => budget = Some(parse_budget(value)),
+            "--cache" => cache_path = Some(value.clone()),
+            _ => panic!("Unknown option: {}", option),
+        }
+    }
+
+    //the cache is created when the file does not exist yet
+    let cache = cache_path.as_ref().map(|path| {
+        let cache = match PlanCache::load(path) {
+            Ok(cache) => cache,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => PlanCache::new(DEFAULT_CAPACITY),
+            Err(e) => panic!("Unable to read {}: {}", path, e),
+        };
+        Arc::new(Mutex::new(cache))
+    });
+    if let Some(cache) = &cache {
+        solver = solver.with_cache(cache.clone());
+    }
+
+    let batch = batch.expect("Expecting the instance file to solve, with --batch.");
+    let instances = load_instances(&batch);
+    let row = |result: &SolveResult| {
+        let tiles: Vec<String> = result.board.tiles().iter().map(|tile| tile.to_string()).collect();
+        let steps = result.plan.as_ref().map_or(String::new(), |plan| plan.steps().to_string());
+        format!("{},{},{},{}", tiles.join(" "), steps, result.statistics.expanded, result.statistics.duration.as_millis())
+    };
+
+    match budget {
+        Some(budget) => {
+            println!("board,steps,expanded,duration_ms,h,predicted_ms,budget_ms,capped");
+            for scheduled in solve_scheduled(&solver, &instances.boards, budget) {
+                let decision = scheduled.decision;
+                let predicted = decision.predicted.map_or(String::new(), |predicted| predicted.as_millis().to_string());
+                println!("{},{},{},{},{}", row(&scheduled.result), decision.h, predicted, decision.budget.as_millis(), decision.capped);
+            }
+        }
+        None => {
+            println!("board,steps,expanded,duration_ms");
+            for result in solver.solve_all(&instances.boards) {
+                println!("{}", row(&result));
+            }
+        }
+    }
+
+    if let (Some(path), Some(cache)) = (cache_path, cache) {
+        let cache = cache.lock().unwrap();
+        cache.save(&path).unwrap_or_else(|e| panic!("Unable to write {}: {}", path, e));
+        eprintln!("Plan cache: {} hits, {} misses, {} plans kept in {}", cache.hits(), cache.misses(), cache.len(), path);
+    }
+}
+
+fn run_histogram(args: &[String]) {
+    let mut instances = 1000;
+    let mut seed = 0;
+    let mut sampling = Sampling::Uniform;
+
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| panic!("Missing value for {}.", option));
+        match option.as_str() {
+            "--instances" => instances = value.parse().unwrap_or_else(|_| panic!("Invalid number of instances: {}", value)),
+            "--seed" => seed = value.parse().unwrap_or_else(|_| panic!("Invalid seed: {}", value)),
+            "--sampling" => sampling = value.parse().unwrap_or_else(|e| panic!("{}", e)),
+            _ => panic!("Unknown option: {}", option),
+        }
+    }
+
+    println!("{}", LengthHistogram::new(DatasetGenerator::new(sampling, seed).take(instances)));
+}
+
+fn run_heatmap(args: &[String]) {
+    let options = parse_search_options(args);
+    let (_, trace) = options.solver.run_traced(options.board);
+
+    println!("Blank positions among the expanded boards ({} with {}):", options.solver.algorithm(), options.solver.heuristic());
+    println!("{}", ExpansionHeatmap::new(&trace));
+}
+
+fn run_trace(args: &[String]) {
+    let SearchOptions { board, solver, output } = parse_search_options(args);
+    let output = output.expect("Expecting the file to write the trace to, with --output.");
+
+    let (_, trace) = solver.run_traced(board);
+    trace.save(&output).unwrap_or_else(|e| panic!("Unable to write {}: {}", output, e));
+    println!("Wrote {} expansions to {}", trace.len(), output);
+}
+
+fn load_trace(path: &str) -> Trace {
+    Trace::load(path).unwrap_or_else(|e| panic!("Unable to read {}: {}", path, e))
+}
+
+fn run_diff(args: &[String]) {
+    let list = args.iter().any(|arg| arg == "--list");
+    let paths: Vec<&String> = args.iter().filter(|arg| *arg != "--list").collect();
+    assert_eq!(paths.len(), 2, "Expecting the two trace files to compare.");
+
+    let diff = TraceDiff::new(&load_trace(paths[0]), &load_trace(paths[1]));
+    println!("{}", diff);
+    if list {
+        for (title, expansions) in [("first", diff.only_first()), ("second", diff.only_second())] {
+            println!("Only expanded by the {} search (rank h):", title);
+            for (rank, h) in expansions {
+                println!("{} {}", rank, h);
+            }
+        }
+    }
+}
+
+fn run_narrate(args: &[String]) {
+    let mut board = None;
+    let mut expansions = 10;
+    let mut heuristic = HeuristicKind::Manhattan;
+    let mut format = "text".to_string();
+
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| panic!("Missing value for {}.", option));
+        match option.as_str() {
+            "--board" => board = Some(parse_board(value)),
+            "--expansions" => expansions = value.parse().unwrap_or_else(|_| panic!("Invalid number of expansions: {}", value)),
+            "--heuristic" => heuristic = value.parse().unwrap_or_else(|e| panic!("{}", e)),
+            "--format" => format = value.clone(),
+            _ => panic!("Unknown option: {}", option),
+        }
+    }
+
+    let board = board.expect("Expecting the board to search from, with --board.");
+    check_solvable(&board, &GOAL);
+    let narration = narrate_a_star(board, |board: &Board| heuristic.h(board), expansions);
+    match format.as_str() {
+        "text" => println!("{}", narration),
+        "json" => println!("{}", narration.to_json()),
+        _ => panic!("Unknown format: {}, expecting text or json.", format),
+    }
+}
+
+fn run_oracle(args: &[String]) {
+    let mut output = None;
+    let mut table = None;
+    let mut board = None;
+
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| panic!("Missing value for {}.", option));
+        match option.as_str() {
+            "--output" => output = Some(value.clone()),
+            "--table" => table = Some(value.clone()),
+            "--board" => board = Some(parse_board(value)),
+            _ => panic!("Unknown option: {}", option),
+        }
+    }
+
+    if let Some(output) = output {
+        OracleTable::build().save(&output).unwrap_or_else(|e| panic!("Unable to write {}: {}", output, e));
+        println!("Wrote the distances of {} boards to {}", Board::RANKS, output);
+        return;
+    }
+
+    let table = table.expect("Expecting the table file to write, with --output, or to look up boards in, with --table.");
+    let board = board.expect("Expecting the board to look up, with --board.");
+    let oracle = OracleTable::map(&table).unwrap_or_else(|e| panic!("Unable to map {}: {}", table, e));
+    process_plan(oracle.plan(&board));
+}
+
+fn run_visualize(args: &[String]) {
+    let SearchOptions { board, solver, output } = parse_search_options(args);
+    let output = output.expect("Expecting the HTML file to write, with --output.");
+
+    let (result, trace) = solver.run_traced(board);
+    let mut file = File::create(&output).unwrap_or_else(|e| panic!("Unable to create {}: {}", output, e));
+    trace.write_html(&mut file, result.plan.as_ref()).unwrap_or_else(|e| panic!("Unable to write {}: {}", output, e));
+    println!("Wrote {} expansions to {}", trace.len(), output);
 }
 
 fn main() {
@@ -32,14 +452,69 @@ fn main() {
         exit(0);
     }
 
+    if args[1] == "compare" {
+        run_compare(&args[2..]);
+        exit(0);
+    }
+
+    if args[1] == "benchmark" {
+        run_benchmark(&args[2..]);
+        exit(0);
+    }
+
+    if args[1] == "generate" {
+        run_generate(&args[2..]);
+        exit(0);
+    }
+
+    if args[1] == "solve" {
+        run_solve(&args[2..]);
+        exit(0);
+    }
+
+    if args[1] == "histogram" {
+        run_histogram(&args[2..]);
+        exit(0);
+    }
+
+    if args[1] == "heatmap" {
+        run_heatmap(&args[2..]);
+        exit(0);
+    }
+
+    if args[1] == "trace" {
+        run_trace(&args[2..]);
+        exit(0);
+    }
+
+    if args[1] == "diff" {
+        run_diff(&args[2..]);
+        exit(0);
+    }
+
+    if args[1] == "narrate" {
+        run_narrate(&args[2..]);
+        exit(0);
+    }
+
+    if args[1] == "oracle" {
+        run_oracle(&args[2..]);
+        exit(0);
+    }
+
+    if args[1] == "visualize" {
+        run_visualize(&args[2..]);
+        exit(0);
+    }
+
     assert_eq!(args.len(), 10, "Expecting 9 arguments in the range [0..8] (inclusive).");
 
-    let mut tiles: [i8; 9]= [0; 9];
+    let mut tiles: [Tile; 9]= [0; 9];
 
     for (index, arg) in args.iter().enumerate() {
         if index > 0 {
-            match arg.parse::<i8>() {
-                Ok(n) if n >= 0 && n <= 8 => tiles[index-1] = n,
+            match arg.parse::<Tile>() {
+                Ok(n) if n <= 8 => tiles[index-1] = n,
                 _ => panic!("Invalid argument: {} - Expecting 9 numeric arguments in the range [0..8] (inclusive).", arg)
             }
         }
@@ -47,11 +522,19 @@ fn main() {
     //todo: explore using command line parameters such as CLAP https://docs.rs/clap/latest/clap/
 
     let board = Board::new(tiles);
+    check_solvable(&board, &GOAL);
     println!("Using Manhattan Distance heuristic");
     //todo: pass the heuristic as an extra argument
 
+    //stop the search on Ctrl-C, so that it still reports how far it got
+    let cancel = CancellationToken::new();
+    let handler_cancel = cancel.clone();
+    ctrlc::set_handler(move || handler_cancel.cancel()).expect("Unable to install the Ctrl-C handler.");
+
+    let solver = Solver::new(Algorithm::AStar).with_limits(Limits { cancel: Some(cancel), ..Limits::none() });
+
     println!("Starting A* search");
-    process_plan(tiles::a_star_search(board));
+    process_result(solver.run(board));
 
     // println!("Starting EHC search");
     // process_plan(tiles::ehc_search(board));
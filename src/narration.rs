@@ -0,0 +1,142 @@
+//! Step by step accounts of a search for teaching, such as homework walkthroughs: the boards it
+//! expanded, what each expansion added to the open list, and where the search goes on from there.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::board::Board;
+use crate::search::{self, Expanded, Limits, SearchHooks};
+use crate::BoardState;
+
+/// A single expansion of the narrated search
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NarratedExpansion<const R: usize = 3, const C: usize = R> {
+    pub board: Board<R, C>,
+    pub g: u32,
+    pub h: i32,
+    /// The successors added to the open list
+    pub generated: usize,
+    /// The successors left out, as they were already reached with as few moves
+    pub skipped: usize,
+    /// The f = g + h of the node the search takes next, or None if the open list is empty
+    pub best_open_f: Option<i32>,
+}
+
+/// The first expansions of a search, and the plan if it was found by then
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Narration<const R: usize = 3, const C: usize = R> {
+    pub expansions: Vec<NarratedExpansion<R, C>>,
+    /// The moves of the plan, if the goal was the next node taken after these expansions (or sooner)
+    pub plan_steps: Option<usize>,
+}
+
+/// Narrates the first `expansions` expansions of A* guided by the heuristic
+pub fn narrate_a_star<const R: usize, const C: usize, H>(board: Board<R, C>, heuristic: H, expansions: usize) -> Narration<R, C>
+    where H: Fn(&Board<R, C>) -> i32
+{
+    //one more expansion tells which node the last narrated one leaves at the front
+    let mut expanded = Vec::new();
+    let hooks = SearchHooks::on_expand(|expansion: &Expanded<BoardState<R, C>>| expanded.push(expansion.clone()));
+    let limits = Limits { max_expanded: Some(expansions as i32 + 1), ..Limits::none() };
+    let result = search::a_star_search_hooked(&BoardState::new(board, &heuristic), |state| state.board.is_goal(), limits, hooks);
+
+    //the node taken next is the goal when the search ends with a plan
+    let next_f = expanded.iter().skip(1).map(|next| Some(next.g as i32 + next.h)).chain([result.solution_cost.map(|cost| cost as i32)]);
+    let mut expanded: Vec<NarratedExpansion<R, C>> = expanded.iter().zip(next_f)
+        .map(|(expansion, next_f)| NarratedExpansion {
+            board: expansion.state.board,
+            g: expansion.g,
+            h: expansion.h,
+            generated: expansion.generated,
+            skipped: expansion.skipped,
+            best_open_f: if expansion.open > 0 { next_f } else { None },
+        })
+        .collect();
+
+    expanded.truncate(expansions);
+    Narration { expansions: expanded, plan_steps: result.plan.map(|plan| plan.len() - 1) }
+}
+
+impl<const R: usize, const C: usize> Narration<R, C> {
+    /// The narration as a JSON object, with the boards written row by row
+    pub fn to_json(&self) -> String {
+        let expansions: Vec<String> = self.expansions.iter().enumerate()
+            .map(|(index, expansion)| format!("{{\"step\":{},\"board\":\"{}\",\"g\":{},\"h\":{},\"generated\":{},\"skipped\":{},\"best_open_f\":{}}}",
+                                               index + 1, board_line(&expansion.board), expansion.g, expansion.h, expansion.generated, expansion.skipped, json_option(expansion.best_open_f)))
+            .collect();
+
+        format!("{{\"expansions\":[{}],\"plan_steps\":{}}}", expansions.join(","), json_option(self.plan_steps))
+    }
+}
+
+impl<const R: usize, const C: usize> Display for Narration<R, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, expansion) in self.expansions.iter().enumerate() {
+            write!(f, "{}. Expanded board {} (g={}, h={}); generated {} successor{}", index + 1, board_line(&expansion.board), expansion.g, expansion.h,
+                   expansion.generated, if expansion.generated == 1 { "" } else { "s" })?;
+            if expansion.skipped > 0 {
+                write!(f, " ({} already seen)", expansion.skipped)?;
+            }
+            match expansion.best_open_f {
+                Some(best) => writeln!(f, "; best open node now has f={}", best)?,
+                None => writeln!(f, "; the open list is now empty")?,
+            }
+        }
+
+        match self.plan_steps {
+            Some(steps) => write!(f, "The goal is reached with a plan of {} moves.", steps),
+            None => write!(f, "The goal is not reached after {} expansions.", self.expansions.len()),
+        }
+    }
+}
+
+//the rows of the board separated by slashes, as the command line reads them
+fn board_line<const R: usize, const C: usize>(board: &Board<R, C>) -> String {
+    board.rows()
+        .map(|row| row.iter().map(|tile| tile.to_string()).collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+fn json_option<T: Display>(value: Option<T>) -> String {
+    value.map_or("null".to_string(), |value| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::manhattan_heuristic;
+
+    use super::*;
+
+    #[test]
+    fn test_narrate() {
+        let board = Board::new([1, 2, 3, 4, 5, 6, 0, 7, 8]);
+        let narration = narrate_a_star(board, manhattan_heuristic, 5);
+
+        //the two moves to the goal are the only expansions
+        assert_eq!(narration.plan_steps, Some(2));
+        assert_eq!(narration.expansions.len(), 2);
+        assert_eq!(narration.expansions[0], NarratedExpansion { board, g: 0, h: 2, generated: 2, skipped: 0, best_open_f: Some(2) });
+        assert_eq!(narration.expansions[1].best_open_f, Some(2));
+
+        let text = narration.to_string();
+        assert!(text.starts_with("1. Expanded board 1 2 3 / 4 5 6 / 0 7 8 (g=0, h=2); generated 2 successors; best open node now has f=2\n"));
+        assert!(text.ends_with("The goal is reached with a plan of 2 moves."));
+        assert!(narration.to_json().starts_with("{\"expansions\":[{\"step\":1,\"board\":\"1 2 3 / 4 5 6 / 0 7 8\",\"g\":0,\"h\":2,\"generated\":2,\"skipped\":0,\"best_open_f\":2}"));
+    }
+
+    #[test]
+    fn test_first_expansions() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let narration = narrate_a_star(board, manhattan_heuristic, 10);
+
+        assert_eq!(narration.expansions.len(), 10);
+        assert_eq!(narration.plan_steps, None);
+        //A* takes the nodes in order of f, which the manhattan distance never lowers
+        for pair in narration.expansions.windows(2) {
+            assert_eq!(pair[0].best_open_f, Some(pair[1].g as i32 + pair[1].h));
+            assert!(pair[0].g as i32 + pair[0].h <= pair[1].g as i32 + pair[1].h);
+        }
+        assert!(narration.to_string().ends_with("The goal is not reached after 10 expansions."));
+        assert!(narration.to_json().ends_with("],\"plan_steps\":null}"));
+    }
+}
@@ -0,0 +1,146 @@
+//! Moves written as text: one letter per move (`U`, `D`, `L` and `R`, for the direction the tile
+//! slides), optionally preceded by a count of repetitions, such as `3L2UR`.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use crate::board::Move;
+
+/// Reasons why a text is not a valid sequence of moves
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseMovesError {
+    /// The character is neither a move, a digit nor a separator
+    InvalidMove(char),
+    /// The count is zero, too large, or not followed by a move
+    InvalidCount(String),
+}
+
+impl Display for ParseMovesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseMovesError::InvalidMove(letter) => write!(f, "Invalid move '{}', expecting U, D, L or R.", letter),
+            ParseMovesError::InvalidCount(count) => write!(f, "Invalid count {}, expecting a positive number followed by a move.", count),
+        }
+    }
+}
+
+impl Error for ParseMovesError {}
+
+/// The letter of the move, for the direction the tile slides
+pub fn letter(next_move: Move) -> char {
+    match next_move {
+        Move::Up => 'U',
+        Move::Down => 'D',
+        Move::Left => 'L',
+        Move::Right => 'R',
+    }
+}
+
+/// Reads moves written in either case, with or without counts, ignoring whitespace and commas
+pub fn parse_moves(text: &str) -> Result<Vec<Move>, ParseMovesError> {
+    let mut moves = Vec::new();
+    let mut count = String::new();
+    for letter in text.chars().filter(|letter| !letter.is_whitespace() && *letter != ',') {
+        if letter.is_ascii_digit() {
+            count.push(letter);
+            continue;
+        }
+
+        let next_move = match letter.to_ascii_uppercase() {
+            'U' => Move::Up,
+            'D' => Move::Down,
+            'L' => Move::Left,
+            'R' => Move::Right,
+            _ => return Err(ParseMovesError::InvalidMove(letter)),
+        };
+
+        let repetitions = match count.as_str() {
+            "" => 1,
+            _ => count.parse::<usize>().ok().filter(|count| *count > 0).ok_or_else(|| ParseMovesError::InvalidCount(count.clone()))?,
+        };
+        moves.extend(std::iter::repeat_n(next_move, repetitions));
+        count.clear();
+    }
+
+    if count.is_empty() {
+        Ok(moves)
+    } else {
+        Err(ParseMovesError::InvalidCount(count))
+    }
+}
+
+/// Writes one letter per move
+pub fn format_moves(moves: &[Move]) -> String {
+    moves.iter().copied().map(letter).collect()
+}
+
+/// Writes every run of the same move as a single letter, preceded by its length when longer than one
+pub fn compress(moves: &[Move]) -> String {
+    let mut text = String::new();
+    for run in moves.chunk_by(|first, second| first == second) {
+        if run.len() > 1 {
+            text.push_str(&run.len().to_string());
+        }
+        text.push(letter(run[0]));
+    }
+
+    text
+}
+
+/// Removes every move that is immediately undone by the next one, including the pairs that only
+/// become adjacent once those between them are removed. The remaining moves lead to the same board,
+/// when they can all be made.
+pub fn canonicalize(moves: &[Move]) -> Vec<Move> {
+    let mut canonical: Vec<Move> = Vec::with_capacity(moves.len());
+    for next_move in moves {
+        if canonical.last() == Some(&next_move.inverse()) {
+            canonical.pop();
+        } else {
+            canonical.push(*next_move);
+        }
+    }
+
+    canonical
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::a_star_search;
+    use crate::board::Board;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_moves("lu, D r"), Ok(vec![Move::Left, Move::Up, Move::Down, Move::Right]));
+        assert_eq!(parse_moves("3L2UR"), Ok(vec![Move::Left, Move::Left, Move::Left, Move::Up, Move::Up, Move::Right]));
+        assert_eq!(parse_moves(""), Ok(vec![]));
+        assert_eq!(parse_moves("LX"), Err(ParseMovesError::InvalidMove('X')));
+        assert_eq!(parse_moves("0L"), Err(ParseMovesError::InvalidCount("0".to_string())));
+        assert_eq!(parse_moves("L12"), Err(ParseMovesError::InvalidCount("12".to_string())));
+    }
+
+    #[test]
+    fn test_compress() {
+        let moves = parse_moves("LLLUURDDDDDDDDDDDD").unwrap();
+        assert_eq!(compress(&moves), "3L2UR12D");
+        assert_eq!(parse_moves(&compress(&moves)), Ok(moves.clone()));
+        assert_eq!(format_moves(&parse_moves("3L2UR").unwrap()), "LLLUUR");
+        assert_eq!(compress(&[]), "");
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        assert_eq!(format_moves(&canonicalize(&parse_moves("LURDLR").unwrap())), "LURD");
+        //pairs are cancelled again once the moves between them are gone
+        assert_eq!(canonicalize(&parse_moves("LUDRU").unwrap()), vec![Move::Up]);
+
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let plan = a_star_search(board).unwrap();
+        let mut moves = plan.moves().to_vec();
+        moves.insert(5, moves[4].inverse());
+        moves.insert(5, moves[4]);
+        assert_eq!(canonicalize(&moves), plan.moves());
+        assert_eq!(board.apply_moves(&canonicalize(&moves)), Ok(*plan.end()));
+    }
+}
@@ -0,0 +1,253 @@
+//! The optimal distance of every 8-puzzle board to the goal, in a file that processes can map
+//! into memory, so that they all share one copy of the table instead of building their own.
+//!
+//! The file starts with a header of 16 bytes:
+//!
+//! | offset | size | contents                                            |
+//! |--------|------|-----------------------------------------------------|
+//! | 0      | 4    | the magic bytes `TORC`                              |
+//! | 4      | 1    | the version of the layout, currently 1              |
+//! | 5      | 1    | the rows of the boards, 3                           |
+//! | 6      | 1    | the columns of the boards, 3                        |
+//! | 7      | 1    | zero                                                |
+//! | 8      | 4    | the number of entries, 362 880, as little endian    |
+//! | 12     | 4    | zero                                                |
+//!
+//! followed by one byte per board, in the order of `Board::rank`: the fewest moves from the board
+//! to `GOAL`, or 255 for the boards that cannot reach it.
+
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, ErrorKind};
+use std::ops::Deref;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::board::{Board, GOAL};
+use crate::plan::Plan;
+
+const MAGIC: &[u8; 4] = b"TORC";
+//the version of the file format, to change whenever the layout changes
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 16;
+
+/// The entry of the boards that cannot reach the goal
+const UNREACHABLE: u8 = u8::MAX;
+
+enum Storage {
+    Owned(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl Deref for Storage {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Storage::Owned(bytes) => bytes,
+            Storage::Mapped(map) => map,
+        }
+    }
+}
+
+/// Table of the optimal distance to the goal of every 8-puzzle board, built in memory, read from
+/// a file, or mapped from a file so that the processes mapping it share its pages
+pub struct OracleTable {
+    //the whole file, header included
+    bytes: Storage,
+}
+
+impl OracleTable {
+    /// Runs a breadth first search back from the goal through every solvable board, which takes
+    /// a fraction of a second
+    pub fn build() -> OracleTable {
+        let mut bytes = header();
+        bytes.resize(HEADER_LEN + Board::RANKS as usize, UNREACHABLE);
+        bytes[HEADER_LEN + GOAL.rank() as usize] = 0;
+
+        let mut queue = VecDeque::from([GOAL]);
+        while let Some(board) = queue.pop_front() {
+            let distance = bytes[HEADER_LEN + board.rank() as usize];
+            for predecessor in board.successors() {
+                let entry = &mut bytes[HEADER_LEN + predecessor.rank() as usize];
+                if *entry == UNREACHABLE {
+                    *entry = distance + 1;
+                    queue.push_back(predecessor);
+                }
+            }
+        }
+
+        OracleTable { bytes: Storage::Owned(bytes) }
+    }
+
+    /// Writes the table in the layout described above
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, &*self.bytes)
+    }
+
+    /// Reads a table written by `save` into memory owned by this process
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<OracleTable> {
+        let bytes = fs::read(path)?;
+        check(&bytes)?;
+        Ok(OracleTable { bytes: Storage::Owned(bytes) })
+    }
+
+    /// Maps a table written by `save` into memory, read only. The pages are loaded from the file
+    /// as they are first read, and shared with every other process mapping the same file.
+    /// The file must not be changed or truncated while it is mapped, by this process or any other,
+    /// as the lookups would see the changes or crash.
+    pub fn map<P: AsRef<Path>>(path: P) -> io::Result<OracleTable> {
+        let file = File::open(path)?;
+        //the file is only ever read, and callers are told to leave it unchanged while mapped
+        let map = unsafe { Mmap::map(&file)? };
+        check(&map)?;
+        Ok(OracleTable { bytes: Storage::Mapped(map) })
+    }
+
+    /// Whether the table is mapped from a file rather than held in memory of its own
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.bytes, Storage::Mapped(_))
+    }
+
+    /// The number of boards in the table, solvable or not
+    pub fn len(&self) -> usize {
+        self.bytes.len() - HEADER_LEN
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The fewest moves from the board to the goal, if it can reach it
+    pub fn distance(&self, board: &Board) -> Option<u32> {
+        match self.bytes[HEADER_LEN + board.rank() as usize] {
+            UNREACHABLE => None,
+            distance => Some(distance as u32),
+        }
+    }
+
+    /// A plan with the fewest moves, moving to a board one move closer to the goal at every step.
+    /// There is none when the board cannot reach the goal, nor when the table is corrupt and a board
+    /// on the way has no successor closer to the goal.
+    pub fn plan(&self, board: &Board) -> Option<Plan> {
+        let mut distance = self.distance(board)?;
+        let mut boards = vec![*board];
+        while distance > 0 {
+            let next = boards.last().unwrap().successors().into_iter()
+                .find(|successor| self.distance(successor) == Some(distance - 1))?;
+            boards.push(next);
+            distance -= 1;
+        }
+
+        Plan::new(boards).ok()
+    }
+}
+
+fn header() -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&[VERSION, 3, 3, 0]);
+    bytes.extend_from_slice(&Board::RANKS.to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    bytes
+}
+
+//checks the header and the size of the file, and that the goal is where it should be
+fn check(bytes: &[u8]) -> io::Result<()> {
+    if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(invalid("Not an oracle table file.".to_string()));
+    }
+    if bytes[MAGIC.len()] != VERSION {
+        return Err(invalid(format!("Unsupported oracle table version {} - Expecting {}.", bytes[MAGIC.len()], VERSION)));
+    }
+    if bytes[..HEADER_LEN] != header()[..] {
+        return Err(invalid("The oracle table is not for the 8-puzzle.".to_string()));
+    }
+    if bytes.len() != HEADER_LEN + Board::RANKS as usize {
+        return Err(invalid(format!("Expecting {} entries in the oracle table, found {}.", Board::RANKS, bytes.len() - HEADER_LEN)));
+    }
+    if bytes[HEADER_LEN + GOAL.rank() as usize] != 0 {
+        return Err(invalid("The goal is not at distance 0 in the oracle table.".to_string()));
+    }
+
+    Ok(())
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        let table = OracleTable::build();
+
+        assert_eq!(table.len(), 362_880);
+        assert!(!table.is_mapped());
+        assert_eq!(table.distance(&GOAL), Some(0));
+        assert_eq!(table.distance(&Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1])), Some(31));
+        assert_eq!(table.distance(&Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0])), None);
+
+        //half the boards are solvable, and the hardest take 31 moves
+        let distances: Vec<u8> = table.bytes[HEADER_LEN..].to_vec();
+        assert_eq!(distances.iter().filter(|distance| **distance != UNREACHABLE).count(), 181_440);
+        assert_eq!(distances.iter().filter(|distance| **distance != UNREACHABLE).max(), Some(&31));
+
+        let board = Board::new([1, 2, 3, 0, 4, 6, 7, 5, 8]);
+        let plan = table.plan(&board).unwrap();
+        assert_eq!(plan.steps(), crate::a_star_search(board).unwrap().steps());
+        assert_eq!(*plan.end(), GOAL);
+    }
+
+    #[test]
+    fn test_save_map_load() {
+        let table = OracleTable::build();
+        let path = env::temp_dir().join(format!("tiles-oracle-{}.bin", std::process::id()));
+        table.save(&path).unwrap();
+
+        let mapped = OracleTable::map(&path).unwrap();
+        let loaded = OracleTable::load(&path).unwrap();
+        assert!(mapped.is_mapped() && !loaded.is_mapped());
+        assert_eq!(*mapped.bytes, *table.bytes);
+        assert_eq!(*loaded.bytes, *table.bytes);
+
+        let board = Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]);
+        assert_eq!(mapped.distance(&board), table.distance(&board));
+        drop(mapped);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_plan_on_corrupt_table() {
+        let mut bytes = OracleTable::build().bytes.to_vec();
+        let board = Board::new([1, 2, 3, 4, 5, 6, 0, 7, 8]);
+        bytes[HEADER_LEN + board.rank() as usize] = 5;
+        check(&bytes).unwrap();
+
+        let corrupt = OracleTable { bytes: Storage::Owned(bytes) };
+        assert_eq!(corrupt.distance(&board), Some(5));
+        assert!(corrupt.plan(&board).is_none());
+    }
+
+    #[test]
+    fn test_check_invalid() {
+        let table = OracleTable::build();
+        let bytes = table.bytes.to_vec();
+
+        assert_eq!(check(&bytes[..bytes.len() - 1]).unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(check(b"not a table").unwrap_err().kind(), ErrorKind::InvalidData);
+
+        let mut newer = bytes.clone();
+        newer[MAGIC.len()] = VERSION + 1;
+        assert_eq!(check(&newer).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        let mut fifteen = bytes;
+        fifteen[5..7].copy_from_slice(&[4, 4]);
+        assert_eq!(check(&fifteen).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,814 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use crate::board::{Board, Tile};
+use crate::heuristic::{dual_max, probe_boards, probe_total};
+
+const MAGIC: &[u8; 4] = b"TPDB";
+//the version of the file format, to change whenever the layout changes
+const VERSION: u8 = 1;
+
+/// How the distances of a pattern database are stored.
+/// Both compressed forms only ever under-estimate the stored distances, so heuristics looked up
+/// from them remain admissible. Databases are built in the compressed form, so it also shrinks the
+/// memory needed to build them, besides that of the file and of the loaded table.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Compression {
+    /// One byte per entry
+    None,
+    /// Two entries per byte, with distances capped at 15
+    Nibble,
+    /// One byte per bucket of consecutive ranks, storing the minimum distance in the bucket
+    MinBuckets(usize),
+}
+
+/// Table of distances indexed by the rank of an abstract state
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DistanceTable {
+    compression: Compression,
+    len: usize,
+    data: Vec<u8>,
+}
+
+impl DistanceTable {
+    pub fn new(distances: &[u8], compression: Compression) -> DistanceTable {
+        let data = match compression {
+            Compression::None => distances.to_vec(),
+
+            Compression::Nibble => distances.chunks(2)
+                .map(|pair| {
+                    let low = pair[0].min(15);
+                    let high = pair.get(1).map_or(0, |distance| (*distance).min(15));
+                    low | (high << 4)
+                })
+                .collect(),
+
+            Compression::MinBuckets(size) => {
+                assert!(size > 0, "Buckets need to contain at least one entry.");
+                distances.chunks(size)
+                    .map(|bucket| *bucket.iter().min().unwrap())
+                    .collect()
+            }
+        };
+
+        DistanceTable { compression, len: distances.len(), data }
+    }
+
+    /// A table whose entries are all as far as they can be stored, to be lowered by `lower`
+    fn unknown(len: usize, compression: Compression) -> DistanceTable {
+        let stored = match compression {
+            Compression::None => len,
+            Compression::Nibble => len.div_ceil(2),
+            Compression::MinBuckets(size) => {
+                assert!(size > 0, "Buckets need to contain at least one entry.");
+                len.div_ceil(size)
+            }
+        };
+
+        let mut data = vec![u8::MAX; stored];
+        if compression == Compression::Nibble && len % 2 == 1 {
+            //the unused half of the last byte, as left by `new`
+            data[stored - 1] = 0x0f;
+        }
+
+        DistanceTable { compression, len, data }
+    }
+
+    /// Stores the distance of the entry if it is lower than the one stored, or than the one stored
+    /// for the whole bucket, so that the table can be filled in compressed form by a search
+    fn lower(&mut self, rank: usize, distance: u8) {
+        assert!(rank < self.len);
+
+        match self.compression {
+            Compression::None => self.data[rank] = self.data[rank].min(distance),
+            Compression::Nibble => {
+                let shift = (rank % 2) * 4;
+                let byte = &mut self.data[rank / 2];
+                let stored = (*byte >> shift) & 0x0f;
+                let lowered = stored.min(distance.min(15));
+                *byte = (*byte & !(0x0f << shift)) | (lowered << shift);
+            }
+            Compression::MinBuckets(size) => self.data[rank / size] = self.data[rank / size].min(distance),
+        }
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// The number of entries, before compression
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of bytes used to store the entries
+    pub fn memory(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn get(&self, rank: usize) -> u8 {
+        assert!(rank < self.len);
+
+        match self.compression {
+            Compression::None => self.data[rank],
+            Compression::Nibble => (self.data[rank / 2] >> ((rank % 2) * 4)) & 0x0f,
+            Compression::MinBuckets(size) => self.data[rank / size],
+        }
+    }
+
+    /// Hints the processor to start loading the entry into the cache, so that a later `get` of the
+    /// same rank does not stall on memory. It does nothing on processors without such a hint.
+    pub fn prefetch(&self, rank: usize) {
+        let index = match self.compression {
+            Compression::None => rank,
+            Compression::Nibble => rank / 2,
+            Compression::MinBuckets(size) => rank / size,
+        };
+
+        if let Some(byte) = self.data.get(index) {
+            prefetch_byte(byte);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn prefetch_byte(byte: &u8) {
+    use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+    //prefetching never faults, and the pointer comes from a reference anyway
+    unsafe { _mm_prefetch(byte as *const u8 as *const i8, _MM_HINT_T0) }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn prefetch_byte(_byte: &u8) {}
+
+/// The fewest moves of the tiles of a pattern that take them from any placement to their goal
+/// positions, found with a breadth first search backwards from the goal where every other tile
+/// looks the same. Moves of the other tiles are free, so the databases of disjoint patterns can be
+/// added together without over-estimating.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PatternDatabase<const R: usize = 3, const C: usize = R> {
+    tiles: Vec<Tile>,
+    table: DistanceTable,
+    board: PhantomData<Board<R, C>>,
+}
+
+impl<const R: usize, const C: usize> PatternDatabase<R, C> {
+    /// Builds the database with a breadth first search over the placements of the pattern tiles and
+    /// the blank, one layer of distance after the other. Only two bits are kept for each placement,
+    /// and the layers are lists of ranks, so that the search needs a fraction of the memory of a
+    /// table of distances over the placements. The distances are stored straight into the table in
+    /// its compressed form, which is never held uncompressed.
+    pub fn build(tiles: &[Tile], compression: Compression) -> PatternDatabase<R, C> {
+        let cells = R * C;
+        assert!(!tiles.is_empty(), "A pattern needs at least one tile.");
+        assert!(tiles.iter().all(|tile| *tile > 0 && (*tile as usize) < cells), "Invalid tile in the pattern: {:?}", tiles);
+
+        //the states of the search also track the blank, which is the last position
+        let mut goal: Vec<usize> = tiles.iter().map(|tile| *tile as usize - 1).collect();
+        goal.push(cells - 1);
+        let states = placements(cells, goal.len());
+        assert!(states <= u32::MAX as usize + 1, "The pattern {:?} has too many placements to build.", tiles);
+
+        let mut marks = Marks::new(states);
+        let mut table = DistanceTable::unknown(placements(cells, tiles.len()), compression);
+
+        let goal_rank = rank(&goal, cells) as u32;
+        marks.set(goal_rank, Mark::Current);
+        let mut layer = vec![goal_rank];
+        let mut distance = 0;
+        while !layer.is_empty() {
+            let mut next_layer = Vec::new();
+            while let Some(state) = layer.pop() {
+                if marks.get(state) == Mark::Closed {
+                    continue;
+                }
+                marks.set(state, Mark::Closed);
+
+                let positions = unrank(state as usize, goal.len(), cells);
+                table.lower(rank(&positions[..tiles.len()], cells), distance);
+
+                let blank = positions[tiles.len()];
+                for neighbour in neighbours(blank, R, C) {
+                    let mut next = positions.clone();
+                    next[tiles.len()] = neighbour;
+                    let moved = positions[..tiles.len()].iter().position(|position| *position == neighbour);
+                    if let Some(tile) = moved {
+                        next[tile] = blank;
+                    }
+
+                    let next_rank = rank(&next, cells) as u32;
+                    match (moved.is_some(), marks.get(next_rank)) {
+                        //moving any other tile is free, so the placement is in the same layer
+                        (false, Mark::Unseen) | (false, Mark::Next) => {
+                            marks.set(next_rank, Mark::Current);
+                            layer.push(next_rank);
+                        }
+                        (true, Mark::Unseen) => {
+                            marks.set(next_rank, Mark::Next);
+                            next_layer.push(next_rank);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            layer = next_layer;
+            distance = distance.saturating_add(1);
+        }
+
+        PatternDatabase { tiles: tiles.to_vec(), table, board: PhantomData }
+    }
+
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    pub fn table(&self) -> &DistanceTable {
+        &self.table
+    }
+
+    /// The moves of the pattern tiles needed to put them in their goal positions
+    pub fn h(&self, board: &Board<R, C>) -> i32 {
+        self.table.get(self.rank(board)) as i32
+    }
+
+    /// The same values as `h` for every board, such as the successors of an expanded board.
+    /// All the ranks are computed and their entries prefetched before any of them is read, so that
+    /// the cache misses of the lookups overlap instead of stalling one after the other.
+    pub fn h_batch(&self, boards: &[Board<R, C>]) -> Vec<i32> {
+        let ranks: Vec<usize> = boards.iter().map(|board| self.rank(board)).collect();
+        for rank in &ranks {
+            self.table.prefetch(*rank);
+        }
+
+        ranks.iter().map(|rank| self.table.get(*rank) as i32).collect()
+    }
+
+    fn rank(&self, board: &Board<R, C>) -> usize {
+        let positions = board.positions();
+        let pattern: Vec<usize> = self.tiles.iter().map(|tile| positions[*tile as usize]).collect();
+        rank(&pattern, R * C)
+    }
+
+    /// Writes the database to a file, so that it only needs to be built once
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, encode(std::slice::from_ref(self)))
+    }
+
+    /// Reads a database written by `save`, for boards of the same size
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<PatternDatabase<R, C>> {
+        let mut databases = decode(&fs::read(path)?)?;
+        if databases.len() != 1 {
+            return Err(io::Error::new(ErrorKind::InvalidData, format!("Expecting a single pattern database, found {}.", databases.len())));
+        }
+
+        Ok(databases.remove(0))
+    }
+}
+
+/// Pattern databases of disjoint sets of tiles, such as the 4-4 partition of the 8-puzzle or the
+/// 5-5-5 partition of the 15-puzzle, whose distances add up to an admissible heuristic
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DisjointPatternDatabases<const R: usize = 3, const C: usize = R> {
+    databases: Vec<PatternDatabase<R, C>>,
+}
+
+impl<const R: usize, const C: usize> DisjointPatternDatabases<R, C> {
+    pub fn build(partition: &[Vec<Tile>], compression: Compression) -> DisjointPatternDatabases<R, C> {
+        let mut tiles: Vec<Tile> = partition.iter().flatten().copied().collect();
+        tiles.sort_unstable();
+        tiles.dedup();
+        assert_eq!(tiles.len(), partition.iter().map(|pattern| pattern.len()).sum::<usize>(), "The patterns have to be disjoint.");
+
+        DisjointPatternDatabases { databases: partition.iter().map(|pattern| PatternDatabase::build(pattern, compression)).collect() }
+    }
+
+    pub fn databases(&self) -> &[PatternDatabase<R, C>] {
+        &self.databases
+    }
+
+    /// The sum of the lookups in every database
+    pub fn h(&self, board: &Board<R, C>) -> i32 {
+        self.databases.iter().map(|database| database.h(board)).sum()
+    }
+
+    /// The larger of the sums of the lookups on the board and on its dual, which is as far from the
+    /// goal when the blank is in its goal position (see `heuristic::dual_max`)
+    pub fn h_dual(&self, board: &Board<R, C>) -> i32 {
+        dual_max(|board: &Board<R, C>| self.h(board))(board)
+    }
+
+    /// The same values as `h` for every board, with the entries of every database prefetched
+    /// before any of them is read
+    pub fn h_batch(&self, boards: &[Board<R, C>]) -> Vec<i32> {
+        let ranks: Vec<Vec<usize>> = self.databases.iter()
+            .map(|database| boards.iter().map(|board| database.rank(board)).collect())
+            .collect();
+        for (database, ranks) in self.databases.iter().zip(&ranks) {
+            for rank in ranks {
+                database.table.prefetch(*rank);
+            }
+        }
+
+        let mut values = vec![0; boards.len()];
+        for (database, ranks) in self.databases.iter().zip(&ranks) {
+            for (value, rank) in values.iter_mut().zip(ranks) {
+                *value += database.table.get(*rank) as i32;
+            }
+        }
+
+        values
+    }
+
+    /// Writes every database to a single file, so that they only need to be built once
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, encode(&self.databases))
+    }
+
+    /// Reads the databases written by `save`, for boards of the same size
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<DisjointPatternDatabases<R, C>> {
+        Ok(DisjointPatternDatabases { databases: decode(&fs::read(path)?)? })
+    }
+}
+
+/// The file format: the magic bytes and version, the board size and the number of databases, then
+/// for each database its tiles, compression, number of entries and stored bytes (little endian)
+fn encode<const R: usize, const C: usize>(databases: &[PatternDatabase<R, C>]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&[VERSION, R as u8, C as u8]);
+    bytes.extend_from_slice(&(databases.len() as u32).to_le_bytes());
+
+    for database in databases {
+        bytes.push(database.tiles.len() as u8);
+        bytes.extend_from_slice(&database.tiles);
+        let (tag, bucket) = match database.table.compression {
+            Compression::None => (0, 0),
+            Compression::Nibble => (1, 0),
+            Compression::MinBuckets(size) => (2, size),
+        };
+        bytes.push(tag);
+        bytes.extend_from_slice(&(bucket as u64).to_le_bytes());
+        bytes.extend_from_slice(&(database.table.len as u64).to_le_bytes());
+        bytes.extend_from_slice(&(database.table.data.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&database.table.data);
+    }
+
+    bytes
+}
+
+fn decode<const R: usize, const C: usize>(bytes: &[u8]) -> io::Result<Vec<PatternDatabase<R, C>>> {
+    let mut reader = Reader { bytes };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(invalid("Not a pattern database file.".to_string()));
+    }
+
+    let version = reader.u8()?;
+    if version != VERSION {
+        return Err(invalid(format!("Unsupported pattern database version {} - Expecting {}.", version, VERSION)));
+    }
+
+    let (rows, columns) = (reader.u8()? as usize, reader.u8()? as usize);
+    if (rows, columns) != (R, C) {
+        return Err(invalid(format!("The pattern databases are for {}x{} boards, not {}x{}.", rows, columns, R, C)));
+    }
+
+    let count = reader.u32()?;
+    let mut databases = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let tile_count = reader.u8()? as usize;
+        let tiles: Vec<Tile> = reader.take(tile_count)?.to_vec();
+        if tiles.is_empty() || tiles.iter().any(|tile| *tile == 0 || *tile as usize >= R * C) {
+            return Err(invalid(format!("Invalid tiles in the pattern: {:?}", tiles)));
+        }
+
+        let compression = match (reader.u8()?, reader.u64()? as usize) {
+            (0, _) => Compression::None,
+            (1, _) => Compression::Nibble,
+            (2, size) if size > 0 => Compression::MinBuckets(size),
+            (tag, size) => return Err(invalid(format!("Unknown compression {} (bucket size {}).", tag, size))),
+        };
+
+        let len = reader.u64()? as usize;
+        let stored = reader.u64()? as usize;
+        let expected = match compression {
+            Compression::None => len,
+            Compression::Nibble => len.div_ceil(2),
+            Compression::MinBuckets(size) => len.div_ceil(size),
+        };
+        if len != placements(R * C, tiles.len()) || stored != expected {
+            return Err(invalid(format!("Unexpected size of the table of the pattern {:?}.", tiles)));
+        }
+
+        let data = reader.take(stored)?.to_vec();
+        let table = DistanceTable { compression, len, data };
+        databases.push(PatternDatabase { tiles, table, board: PhantomData });
+    }
+
+    Ok(databases)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Reads the bytes of a file in order, failing when they run out
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> io::Result<&'a [u8]> {
+        if self.bytes.len() < count {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "The pattern database file is truncated."));
+        }
+
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
+
+/// The heuristic that sums the lookups in the disjoint pattern databases
+pub fn pattern_db_heuristic<const R: usize, const C: usize>(databases: &DisjointPatternDatabases<R, C>) -> impl Fn(&Board<R, C>) -> i32 + '_ {
+    move |board: &Board<R, C>| databases.h(board)
+}
+
+/// The heuristic that sums the lookups in the disjoint pattern databases, on the board and on its
+/// dual, and takes the larger of the two
+pub fn pattern_db_dual_heuristic<const R: usize, const C: usize>(databases: &DisjointPatternDatabases<R, C>) -> impl Fn(&Board<R, C>) -> i32 + '_ {
+    move |board: &Board<R, C>| databases.h_dual(board)
+}
+
+/// Loads the partitions saved by `DisjointPatternDatabases::save` one after the other, and keeps the
+/// one whose lookups are the highest on the board and on the boards of random walks from it (see
+/// `heuristic::select_strongest`), along with its index among the paths. Only the partition kept
+/// so far and the one being probed are in memory at the same time.
+pub fn select_partition<const R: usize, const C: usize, P: AsRef<Path>>(paths: &[P], board: &Board<R, C>, walks: usize, length: u32, seed: u64) -> io::Result<(usize, DisjointPatternDatabases<R, C>)> {
+    assert!(!paths.is_empty(), "Expecting at least one partition to select from.");
+
+    let probes = probe_boards(board, walks, length, seed);
+    let mut strongest: Option<(usize, i64, DisjointPatternDatabases<R, C>)> = None;
+    for (index, path) in paths.iter().enumerate() {
+        let databases = DisjointPatternDatabases::load(path)?;
+        let total = probe_total(&probes, |board: &Board<R, C>| databases.h(board));
+        if strongest.as_ref().is_none_or(|(_, strongest_total, _)| total > *strongest_total) {
+            strongest = Some((index, total, databases));
+        }
+    }
+
+    let (index, _, databases) = strongest.unwrap();
+    Ok((index, databases))
+}
+
+/// The number of ways to place `count` distinct items in `cells` cells
+fn placements(cells: usize, count: usize) -> usize {
+    (cells - count + 1..=cells).product()
+}
+
+/// The index of a placement of distinct items among all the placements of as many items, counting
+/// each position among the cells that the previous items left free
+fn rank(positions: &[usize], cells: usize) -> usize {
+    let mut rank = 0;
+    for (index, position) in positions.iter().enumerate() {
+        let taken_before = positions[..index].iter().filter(|previous| *previous < position).count();
+        rank = rank * (cells - index) + position - taken_before;
+    }
+
+    rank
+}
+
+/// The placement of `count` distinct items with the given rank, the inverse of `rank`
+fn unrank(mut rank: usize, count: usize, cells: usize) -> Vec<usize> {
+    let mut free_indices = vec![0; count];
+    for index in (0..count).rev() {
+        free_indices[index] = rank % (cells - index);
+        rank /= cells - index;
+    }
+
+    let mut positions: Vec<usize> = Vec::with_capacity(count);
+    for free_index in free_indices {
+        let position = (0..cells)
+            .filter(|cell| !positions.contains(cell))
+            .nth(free_index)
+            .unwrap();
+        positions.push(position);
+    }
+
+    positions
+}
+
+/// How far the search for the distances of a pattern database got with a placement
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Mark {
+    Unseen,
+    /// Queued in the layer being expanded
+    Current,
+    /// Queued in the layer after it, unless reached again by a free move
+    Next,
+    Closed,
+}
+
+/// The marks of every placement, four to a byte
+struct Marks {
+    bits: Vec<u8>,
+}
+
+impl Marks {
+    fn new(len: usize) -> Marks {
+        Marks { bits: vec![0; len.div_ceil(4)] }
+    }
+
+    fn get(&self, rank: u32) -> Mark {
+        let rank = rank as usize;
+        match (self.bits[rank / 4] >> ((rank % 4) * 2)) & 0b11 {
+            0 => Mark::Unseen,
+            1 => Mark::Current,
+            2 => Mark::Next,
+            _ => Mark::Closed,
+        }
+    }
+
+    fn set(&mut self, rank: u32, mark: Mark) {
+        let rank = rank as usize;
+        let shift = (rank % 4) * 2;
+        let byte = &mut self.bits[rank / 4];
+        *byte = (*byte & !(0b11 << shift)) | ((mark as u8) << shift);
+    }
+}
+
+/// The positions the blank can move to from its position on a board of the given size
+fn neighbours(blank: usize, rows: usize, columns: usize) -> Vec<usize> {
+    let (row, col) = (blank / columns, blank % columns);
+    let mut neighbours = Vec::with_capacity(4);
+    if col + 1 < columns {
+        neighbours.push(blank + 1);
+    }
+    if row + 1 < rows {
+        neighbours.push(blank + columns);
+    }
+    if row > 0 {
+        neighbours.push(blank - columns);
+    }
+    if col > 0 {
+        neighbours.push(blank - 1);
+    }
+
+    neighbours
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::env;
+
+    use crate::board::GOAL;
+
+    use super::*;
+
+    fn distances() -> Vec<u8> {
+        (0..101).map(|rank| ((rank * 7) % 23) as u8).collect()
+    }
+
+    #[test]
+    fn test_uncompressed() {
+        let distances = distances();
+        let table = DistanceTable::new(&distances, Compression::None);
+
+        assert_eq!(table.len(), distances.len());
+        assert_eq!(table.memory(), distances.len());
+        for (rank, distance) in distances.iter().enumerate() {
+            assert_eq!(table.get(rank), *distance);
+        }
+    }
+
+    #[test]
+    fn test_nibble() {
+        let distances = distances();
+        let table = DistanceTable::new(&distances, Compression::Nibble);
+
+        assert_eq!(table.memory(), 51);
+        for (rank, distance) in distances.iter().enumerate() {
+            assert_eq!(table.get(rank), (*distance).min(15));
+        }
+    }
+
+    #[test]
+    fn test_min_buckets() {
+        let distances = distances();
+        let table = DistanceTable::new(&distances, Compression::MinBuckets(4));
+
+        assert_eq!(table.memory(), 26);
+        for (rank, distance) in distances.iter().enumerate() {
+            assert!(table.get(rank) <= *distance);
+        }
+        assert_eq!(table.get(100), distances[100]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_range() {
+        DistanceTable::new(&distances(), Compression::Nibble).get(101);
+    }
+
+    #[test]
+    fn test_rank() {
+        let ranks: HashSet<usize> = (0..4).flat_map(|first| (0..4).map(move |second| [first, second]))
+            .filter(|positions| positions[0] != positions[1])
+            .map(|positions| rank(&positions, 4))
+            .collect();
+
+        assert_eq!(placements(4, 2), 12);
+        assert_eq!(ranks, (0..12).collect());
+        for rank_index in 0..placements(9, 3) {
+            assert_eq!(rank(&unrank(rank_index, 3, 9), 9), rank_index);
+        }
+    }
+
+    #[test]
+    fn test_marks() {
+        let mut marks = Marks::new(10);
+        marks.set(5, Mark::Next);
+        marks.set(6, Mark::Closed);
+        marks.set(9, Mark::Current);
+        assert_eq!((marks.get(4), marks.get(5), marks.get(6), marks.get(7), marks.get(9)), (Mark::Unseen, Mark::Next, Mark::Closed, Mark::Unseen, Mark::Current));
+
+        marks.set(5, Mark::Current);
+        assert_eq!((marks.get(5), marks.get(6)), (Mark::Current, Mark::Closed));
+        assert_eq!(marks.bits.len(), 3);
+    }
+
+    #[test]
+    fn test_pattern_database() {
+        let database = PatternDatabase::<3>::build(&[1, 2, 3, 4], Compression::None);
+
+        assert_eq!(database.table().len(), 9 * 8 * 7 * 6);
+        assert_eq!(database.h(&GOAL), 0);
+        assert_eq!(database.h(&Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8])), 0);
+        assert_eq!(database.h(&Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0])), 8);
+    }
+
+    #[test]
+    fn test_disjoint_databases() {
+        let databases = DisjointPatternDatabases::<3>::build(&[vec![1, 2, 3, 4], vec![5, 6, 7, 8]], Compression::None);
+        let heuristic = pattern_db_heuristic(&databases);
+
+        for tiles in [[2, 3, 1, 4, 5, 6, 7, 8, 0], [8, 6, 7, 2, 5, 4, 3, 1, 0], [8, 6, 7, 2, 5, 4, 3, 0, 1]].iter() {
+            let board = Board::new(*tiles);
+            let plan = crate::a_star_search(board).unwrap();
+            assert!(heuristic(&board) >= board.manhattan_dist());
+            assert!(heuristic(&board) <= plan.steps() as i32);
+
+            let pdb_plan = crate::a_star_search_with(board, &heuristic).unwrap();
+            assert_eq!(pdb_plan.steps(), plan.steps());
+            assert!(pdb_plan.statistics().unwrap().expanded <= plan.statistics().unwrap().expanded);
+        }
+    }
+
+    #[test]
+    fn test_compressed_databases_are_admissible() {
+        let exact = DisjointPatternDatabases::<3>::build(&[vec![1, 2, 3, 4], vec![5, 6, 7, 8]], Compression::None);
+        let nibble = DisjointPatternDatabases::<3>::build(&[vec![1, 2, 3, 4], vec![5, 6, 7, 8]], Compression::Nibble);
+
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        assert!(nibble.h(&board) <= exact.h(&board));
+        assert_eq!(nibble.databases()[0].table().memory(), exact.databases()[0].table().memory() / 2);
+    }
+
+    #[test]
+    fn test_built_compressed() {
+        //the tables built in compressed form are those compressed from the exact distances
+        let exact = PatternDatabase::<3>::build(&[1, 2, 5], Compression::None);
+        let distances: Vec<u8> = (0..exact.table().len()).map(|rank| exact.table().get(rank)).collect();
+        assert_eq!(distances.len() % 2, 0);
+
+        for compression in [Compression::Nibble, Compression::MinBuckets(5), Compression::MinBuckets(7)].iter() {
+            let database = PatternDatabase::<3>::build(&[1, 2, 5], *compression);
+            assert_eq!(*database.table(), DistanceTable::new(&distances, *compression));
+        }
+
+        let mut odd = DistanceTable::unknown(5, Compression::Nibble);
+        for (rank, distance) in [3, 20, 0, 7, 9].iter().enumerate() {
+            odd.lower(rank, *distance);
+        }
+        assert_eq!(odd, DistanceTable::new(&[3, 20, 0, 7, 9], Compression::Nibble));
+    }
+
+    #[test]
+    fn test_h_batch() {
+        let databases = DisjointPatternDatabases::<3>::build(&[vec![1, 2, 3, 4], vec![5, 6, 7, 8]], Compression::Nibble);
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let successors = board.successors();
+
+        let expected: Vec<i32> = successors.iter().map(|successor| databases.h(successor)).collect();
+        assert_eq!(databases.h_batch(&successors), expected);
+        assert_eq!(databases.databases()[1].h_batch(&successors), successors.iter().map(|successor| databases.databases()[1].h(successor)).collect::<Vec<i32>>());
+        assert!(databases.h_batch(&[]).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_overlapping_patterns() {
+        DisjointPatternDatabases::<3>::build(&[vec![1, 2, 3], vec![3, 4, 5]], Compression::None);
+    }
+
+    #[test]
+    fn test_fifteen_puzzle() {
+        let partition = [vec![1, 2, 3], vec![4, 7, 8], vec![5, 6, 9], vec![10, 13, 14], vec![11, 12, 15]];
+        let databases = DisjointPatternDatabases::<4>::build(&partition, Compression::None);
+
+        let board: Board<4> = "5 1 3 4 / 9 2 7 8 / 13 6 10 12 / 14 0 11 15".parse().unwrap();
+        let plan = crate::a_star_search_with(board, pattern_db_heuristic(&databases)).unwrap();
+        assert!(databases.h(&board) >= board.manhattan_dist());
+        assert_eq!(plan.steps(), crate::a_star_search(board).unwrap().steps());
+    }
+
+    #[test]
+    fn test_dual_lookups() {
+        let partition = [vec![1, 2, 3], vec![4, 7, 8], vec![5, 6, 9], vec![10, 13, 14], vec![11, 12, 15]];
+        let databases = DisjointPatternDatabases::<4>::build(&partition, Compression::None);
+
+        let board: Board<4> = "1 2 3 4 / 13 5 8 12 / 9 11 7 10 / 14 15 6 0".parse().unwrap();
+        assert_eq!((databases.h(&board), databases.h_dual(&board)), (16, 18));
+        //the dual is only looked up with the blank in its goal position
+        let moved = board.successors()[0];
+        assert_eq!(databases.h_dual(&moved), databases.h(&moved));
+
+        let plan = crate::a_star_search_with(board, pattern_db_heuristic(&databases)).unwrap();
+        let dual_plan = crate::a_star_search_with(board, pattern_db_dual_heuristic(&databases)).unwrap();
+        assert_eq!(dual_plan.steps(), plan.steps());
+        assert!(dual_plan.statistics().unwrap().expanded < plan.statistics().unwrap().expanded);
+    }
+
+    #[test]
+    fn test_save_load() {
+        let databases = DisjointPatternDatabases::<3>::build(&[vec![1, 2, 3, 4], vec![5, 6, 7, 8]], Compression::MinBuckets(3));
+        let path = env::temp_dir().join(format!("tiles-pdb-{}.bin", std::process::id()));
+
+        databases.save(&path).unwrap();
+        let loaded = DisjointPatternDatabases::<3>::load(&path).unwrap();
+        assert_eq!(loaded, databases);
+
+        //the same file does not fit boards of another size, nor a single database
+        assert_eq!(DisjointPatternDatabases::<4>::load(&path).unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(PatternDatabase::<3>::load(&path).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        databases.databases()[1].save(&path).unwrap();
+        assert_eq!(PatternDatabase::<3>::load(&path).unwrap(), databases.databases()[1]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_select_partition() {
+        let paths: Vec<_> = (0..3).map(|index| env::temp_dir().join(format!("tiles-partition-{}-{}.bin", std::process::id(), index))).collect();
+        let partitions = [vec![vec![1, 2], vec![3, 4]], vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]], vec![vec![5, 6, 7], vec![8]]];
+        for (partition, path) in partitions.iter().zip(&paths) {
+            DisjointPatternDatabases::<3>::build(partition, Compression::None).save(path).unwrap();
+        }
+
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let (index, databases) = select_partition(&paths, &board, 10, 20, 7).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(databases.databases()[0].tiles(), &[1, 2, 3, 4]);
+
+        let missing = env::temp_dir().join(format!("tiles-partition-{}-missing.bin", std::process::id()));
+        assert_eq!(select_partition::<3, 3, _>(&[paths[0].clone(), missing], &board, 1, 1, 7).unwrap_err().kind(), ErrorKind::NotFound);
+        for path in &paths {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let database = PatternDatabase::<3>::build(&[1, 2], Compression::Nibble);
+        let bytes = encode(&[database]);
+
+        assert_eq!(decode::<3, 3>(&bytes[..bytes.len() - 1]).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+        assert_eq!(decode::<3, 3>(b"not a database").unwrap_err().kind(), ErrorKind::InvalidData);
+
+        let mut newer = bytes.clone();
+        newer[MAGIC.len()] = VERSION + 1;
+        assert_eq!(decode::<3, 3>(&newer).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+}
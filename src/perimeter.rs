@@ -0,0 +1,174 @@
+//! Perimeter search: the boards within a few moves of the goal are stored with their distance to
+//! it, and a search from the board only has to reach one of them, so it saves the deepest (and
+//! largest) iterations near the goal. The boards outside the perimeter are at least one move further
+//! than its depth, which also raises the heuristic of the boards close to it.
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+
+use crate::board::{Board, Move};
+use crate::layers::parallel_layers;
+use crate::plan::Plan;
+use crate::search::{self, Limits, MutableState};
+
+/// The boards within a number of moves of the goal, with their distance to it
+#[derive(Debug, Clone)]
+pub struct Perimeter<const R: usize = 3, const C: usize = R> {
+    depth: u32,
+    distances: HashMap<Board<R, C>, u32>,
+}
+
+impl<const R: usize, const C: usize> Perimeter<R, C> {
+    /// Walks breadth first from the goal for `depth` moves, with `parallel_layers`. As every move
+    /// can be undone, the moves away from the goal are as many as those towards it.
+    pub fn build(depth: u32) -> Perimeter<R, C> {
+        let layers = parallel_layers(&[Board::goal()], Board::successors, Some(depth));
+        let distances = layers.iter().enumerate()
+            .flat_map(|(distance, layer)| layer.iter().map(move |board| (*board, distance as u32)))
+            .collect();
+
+        Perimeter { depth, distances }
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// The number of boards in the perimeter
+    pub fn len(&self) -> usize {
+        self.distances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.distances.is_empty()
+    }
+
+    /// The number of moves from the board to the goal, if the board is in the perimeter
+    pub fn distance(&self, board: &Board<R, C>) -> Option<u32> {
+        self.distances.get(board).copied()
+    }
+
+    /// A lower bound on the moves to the goal, exact within the perimeter and at least one more than
+    /// its depth outside of it, where the heuristic may know better
+    pub fn h<H: Fn(&Board<R, C>) -> i32>(&self, board: &Board<R, C>, heuristic: H) -> i32 {
+        match self.distance(board) {
+            Some(distance) => distance as i32,
+            None => heuristic(board).max(self.depth as i32 + 1),
+        }
+    }
+
+    /// The moves from a board of the perimeter to the goal, each going to a board one move closer
+    pub fn moves_to_goal(&self, board: &Board<R, C>) -> Option<Vec<Move>> {
+        let mut distance = self.distance(board)?;
+        let mut board = *board;
+        let mut moves = Vec::with_capacity(distance as usize);
+        while distance > 0 {
+            let (next_move, closer) = board.successors_with_moves().into_iter()
+                .find(|(_, successor)| self.distance(successor) == Some(distance - 1))
+                .unwrap();
+            moves.push(next_move);
+            board = closer;
+            distance -= 1;
+        }
+
+        Some(moves)
+    }
+}
+
+/// IDA* from the board to the perimeter, guided by the heuristic, then along the perimeter to the
+/// goal. The plan is optimal when the heuristic is admissible, as the boards of the perimeter are
+/// given their exact distance.
+pub fn perimeter_search<const R: usize, const C: usize, H>(board: Board<R, C>, perimeter: &Perimeter<R, C>, heuristic: H) -> Option<Plan<R, C>>
+    where H: Fn(&Board<R, C>) -> i32
+{
+    if !board.is_solvable() {
+        //without the seen boards, the bound would keep growing forever
+        return None;
+    }
+
+    let path = PerimeterPath { board, h: perimeter.h(&board, &heuristic), perimeter, heuristic: &heuristic };
+    let result = search::ida_star_search(path, |path| perimeter.distance(&path.board).is_some(), Limits::none());
+    let (statistics, reached) = (result.statistics, result.state.board);
+    result.actions.map(|mut moves| {
+        moves.extend(perimeter.moves_to_goal(&reached).unwrap());
+        let mut boards = vec![board];
+        for next_move in &moves {
+            boards.push(boards.last().unwrap().apply_move(*next_move).unwrap());
+        }
+        Plan::from_search_moves(boards, moves, statistics)
+    })
+}
+
+/// A board moved in place by IDA*, whose heuristic is raised by the perimeter
+struct PerimeterPath<'a, const R: usize, const C: usize, H> {
+    board: Board<R, C>,
+    h: i32,
+    perimeter: &'a Perimeter<R, C>,
+    heuristic: &'a H,
+}
+
+impl<const R: usize, const C: usize, H> Debug for PerimeterPath<'_, R, C, H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PerimeterPath").field("board", &self.board).field("h", &self.h).finish()
+    }
+}
+
+impl<const R: usize, const C: usize, H: Fn(&Board<R, C>) -> i32> MutableState for PerimeterPath<'_, R, C, H> {
+    type Action = Move;
+
+    fn actions(&self) -> Vec<Move> {
+        self.board.possible_moves().collect()
+    }
+
+    fn apply(&mut self, action: Move) {
+        self.board.apply_move_in_place(action);
+        self.h = self.perimeter.h(&self.board, self.heuristic);
+    }
+
+    fn undo(&mut self, action: Move) {
+        self.apply(action.inverse());
+    }
+
+    fn reverses(&self, action: Move, previous: Move) -> bool {
+        action == previous.inverse()
+    }
+
+    fn h(&self) -> i32 {
+        self.h
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::GOAL;
+    use crate::{a_star_search, manhattan_heuristic};
+
+    use super::*;
+
+    #[test]
+    fn test_perimeter() {
+        let perimeter = Perimeter::<3>::build(4);
+        assert_eq!(perimeter.depth(), 4);
+        assert_eq!(perimeter.len(), 1 + 2 + 4 + 8 + 16);
+        assert_eq!(perimeter.distance(&GOAL), Some(0));
+
+        let board = Board::new([1, 2, 3, 4, 0, 5, 7, 8, 6]);
+        assert_eq!(perimeter.distance(&board), Some(2));
+        assert_eq!(board.apply_moves(&perimeter.moves_to_goal(&board).unwrap()), Ok(GOAL));
+        assert_eq!(perimeter.distance(&Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1])), None);
+        assert_eq!(perimeter.h(&Board::new([1, 2, 3, 4, 5, 6, 0, 7, 8]), manhattan_heuristic), 2);
+        assert_eq!(perimeter.h(&Board::new([1, 2, 3, 4, 5, 6, 8, 7, 0]), |_| 0), 5);
+    }
+
+    #[test]
+    fn test_optimal() {
+        let perimeter = Perimeter::build(8);
+        for board in [Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]), Board::new([1, 2, 3, 4, 0, 5, 7, 8, 6]), GOAL] {
+            let plan = perimeter_search(board, &perimeter, manhattan_heuristic).unwrap();
+            assert_eq!(*plan.end(), GOAL);
+            assert_eq!(plan.steps(), a_star_search(board).unwrap().steps());
+        }
+
+        assert!(perimeter_search(Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]), &perimeter, manhattan_heuristic).is_none());
+    }
+}
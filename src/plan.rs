@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use crate::board::{Board, Move};
+use crate::goal::GoalSet;
+use crate::search::Statistics;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PlanError {
+    /// A plan needs at least the board it starts from
+    Empty,
+    /// The board at this index cannot be reached from the previous one in a single move
+    InvalidStep(usize),
+    /// The second plan does not start where the first one ends
+    Disconnected,
+}
+
+/// A sequence of boards where each one is reached from the previous one with a single move,
+/// together with the moves taken and the statistics of the search that found it (if any)
+#[derive(Debug, Clone)]
+pub struct Plan<const R: usize = 3, const C: usize = R> {
+    boards: Vec<Board<R, C>>,
+    moves: Vec<Move>,
+    cost: u32,
+    statistics: Option<Statistics>,
+}
+
+impl<const R: usize, const C: usize> Plan<R, C> {
+    pub fn new(boards: Vec<Board<R, C>>) -> Result<Plan<R, C>, PlanError> {
+        if boards.is_empty() {
+            return Err(PlanError::Empty);
+        }
+
+        let mut moves = Vec::with_capacity(boards.len() - 1);
+        for (index, step) in boards.windows(2).enumerate() {
+            match step[0].move_to(&step[1]) {
+                Some(next_move) => moves.push(next_move),
+                None => return Err(PlanError::InvalidStep(index + 1))
+            }
+        }
+
+        let cost = moves.len() as u32;
+        Ok(Plan { boards, moves, cost, statistics: None })
+    }
+
+    /// Builds the plan found by a search, whose steps are known to be valid moves
+    pub(crate) fn from_search(boards: Vec<Board<R, C>>, statistics: Statistics) -> Plan<R, C> {
+        let mut plan = Plan::new(boards).unwrap();
+        plan.statistics = Some(statistics);
+        plan
+    }
+
+    /// Builds the plan found by a search that reported the move taken at each step
+    pub(crate) fn from_search_moves(boards: Vec<Board<R, C>>, moves: Vec<Move>, statistics: Statistics) -> Plan<R, C> {
+        debug_assert_eq!(moves.len() + 1, boards.len());
+        let cost = moves.len() as u32;
+        Plan { boards, moves, cost, statistics: Some(statistics) }
+    }
+
+    /// Builds the plan found by a search over moves of different costs, which add up to `cost`
+    pub(crate) fn from_search_costed(boards: Vec<Board<R, C>>, moves: Vec<Move>, cost: u32, statistics: Statistics) -> Plan<R, C> {
+        Plan { cost, ..Plan::from_search_moves(boards, moves, statistics) }
+    }
+
+    pub fn boards(&self) -> &[Board<R, C>] {
+        &self.boards
+    }
+
+    pub fn into_boards(self) -> Vec<Board<R, C>> {
+        self.boards
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The number of moves in the plan
+    pub fn steps(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    /// The statistics of the search that found this plan, if it was found by a search
+    pub fn statistics(&self) -> Option<&Statistics> {
+        self.statistics.as_ref()
+    }
+
+    pub fn start(&self) -> &Board<R, C> {
+        self.boards.first().unwrap()
+    }
+
+    pub fn end(&self) -> &Board<R, C> {
+        self.boards.last().unwrap()
+    }
+
+    /// The plan going from the end back to the start, undoing every move
+    pub fn reverse(&self) -> Plan<R, C> {
+        let boards = self.boards.iter().rev().copied().collect();
+        Plan { boards, moves: self.undo_moves(), cost: self.cost, statistics: None }
+    }
+
+    /// The moves that restore the start of the plan from its end, such as the scramble that was
+    /// solved: every move of the plan undone, from the last to the first
+    pub fn undo_moves(&self) -> Vec<Move> {
+        self.moves.iter().rev().map(|step| step.inverse()).collect()
+    }
+
+    /// The same plan, found by a search with these statistics
+    pub(crate) fn with_statistics(self, statistics: Statistics) -> Plan<R, C> {
+        Plan { statistics: Some(statistics), ..self }
+    }
+
+    /// The plan that follows this one and then the other, which has to start where this one ends
+    pub fn concat(&self, other: &Plan<R, C>) -> Result<Plan<R, C>, PlanError> {
+        if self.end() != other.start() {
+            return Err(PlanError::Disconnected);
+        }
+
+        let mut boards = self.boards.clone();
+        boards.extend_from_slice(&other.boards[1..]);
+        let mut moves = self.moves.clone();
+        moves.extend_from_slice(&other.moves);
+
+        Ok(Plan { boards, moves, cost: self.cost + other.cost, statistics: None })
+    }
+}
+
+impl Plan {
+    /// Shortens a (typically suboptimal) plan by cutting out loops that revisit the same board, and
+    /// then replacing every window of `window` consecutive moves with an optimal A* plan between
+    /// its ends, until no further improvement is found.
+    pub fn optimize(&self, window: usize) -> Plan {
+        assert!(window > 1, "Optimizing needs a window of at least two moves.");
+
+        let mut boards = remove_loops(&self.boards);
+        loop {
+            let before = boards.len();
+
+            let mut start = 0;
+            while start + 1 < boards.len() {
+                let end = (start + window).min(boards.len() - 1);
+                let goal = GoalSet::single(boards[end]);
+                let shortcut = crate::a_star_search_goals(boards[start], &goal).unwrap();
+
+                if shortcut.steps() < end - start {
+                    boards.splice(start..=end, shortcut.into_boards());
+                }
+                start += 1;
+            }
+
+            boards = remove_loops(&boards);
+            if boards.len() == before {
+                break;
+            }
+        }
+
+        Plan::new(boards).unwrap()
+    }
+}
+
+impl<const R: usize, const C: usize> PartialEq for Plan<R, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.boards == other.boards
+    }
+}
+
+impl<const R: usize, const C: usize> Eq for Plan<R, C> {}
+
+fn remove_loops<const R: usize, const C: usize>(boards: &[Board<R, C>]) -> Vec<Board<R, C>> {
+    let mut without_loops: Vec<Board<R, C>> = Vec::with_capacity(boards.len());
+    let mut seen = HashMap::new();
+
+    for board in boards {
+        if let Some(index) = seen.get(board) {
+            let index = *index;
+            for removed in without_loops.drain(index + 1..) {
+                seen.remove(&removed);
+            }
+        } else {
+            seen.insert(*board, without_loops.len());
+            without_loops.push(*board);
+        }
+    }
+
+    without_loops
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::GOAL;
+
+    use super::*;
+
+    fn plan() -> Plan {
+        Plan::new(vec![
+            Board::new([1, 2, 3, 4, 0, 6, 7, 5, 8]),
+            Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8]),
+            GOAL,
+        ]).unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let plan = plan();
+        assert_eq!(plan.steps(), 2);
+        assert_eq!(*plan.end(), GOAL);
+    }
+
+    #[test]
+    fn test_moves() {
+        let plan = plan();
+        assert_eq!(plan.moves(), &[Move::Up, Move::Left]);
+        assert_eq!(plan.cost(), 2);
+        assert!(plan.statistics().is_none());
+    }
+
+    #[test]
+    fn test_invalid_plans() {
+        assert_eq!(Plan::<3>::new(vec![]), Err(PlanError::Empty));
+        assert_eq!(Plan::new(vec![Board::new([1, 2, 3, 4, 0, 6, 7, 5, 8]), GOAL]), Err(PlanError::InvalidStep(1)));
+    }
+
+    #[test]
+    fn test_reverse() {
+        let plan = plan();
+        let reversed = plan.reverse();
+
+        assert_eq!(reversed.start(), plan.end());
+        assert_eq!(reversed.end(), plan.start());
+        assert_eq!(reversed.moves(), &[Move::Right, Move::Down]);
+        assert_eq!(reversed.reverse(), plan);
+    }
+
+    #[test]
+    fn test_undo_moves() {
+        let plan = crate::a_star_search(Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1])).unwrap();
+        let undo = plan.undo_moves();
+
+        assert_eq!(undo.len(), plan.steps());
+        assert_eq!(undo.as_slice(), plan.reverse().moves());
+        //each undo move goes back to the board before the corresponding move of the plan
+        for (index, undo_move) in undo.iter().enumerate() {
+            let boards = plan.boards();
+            let (from, to) = (boards[boards.len() - 1 - index], boards[boards.len() - 2 - index]);
+            assert_eq!(from.move_to(&to), Some(*undo_move));
+        }
+    }
+
+    #[test]
+    fn test_concat() {
+        let plan = plan();
+        let round_trip = plan.concat(&plan.reverse()).unwrap();
+
+        assert_eq!(round_trip.steps(), 4);
+        assert_eq!(round_trip.cost(), 4);
+        assert_eq!(round_trip.start(), round_trip.end());
+        assert!(Plan::new(round_trip.boards().to_vec()).is_ok());
+    }
+
+    #[test]
+    fn test_remove_loops() {
+        let plan = plan();
+        let round_trip = plan.concat(&plan.reverse()).unwrap().concat(&plan).unwrap();
+
+        assert_eq!(remove_loops(round_trip.boards()), plan.boards());
+    }
+
+    #[test]
+    fn test_optimize() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let plan = crate::ehc_search(board).unwrap();
+        let optimized = plan.optimize(20);
+
+        assert!(optimized.steps() < plan.steps());
+        assert!(optimized.steps() >= 31);
+        assert_eq!(optimized.start(), plan.start());
+        assert_eq!(optimized.end(), plan.end());
+        assert!(Plan::new(optimized.boards().to_vec()).is_ok());
+    }
+
+    #[test]
+    fn test_concat_disconnected() {
+        let plan = plan();
+        assert_eq!(plan.concat(&plan), Err(PlanError::Disconnected));
+    }
+}
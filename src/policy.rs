@@ -0,0 +1,150 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+use crate::board::{Board, Move};
+use crate::plan::Plan;
+use crate::search::State;
+use crate::BoardState;
+
+/// The best state to move to next, and how far the current state is from the goal
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PolicyEntry<S> {
+    pub next: S,
+    pub distance: u32,
+}
+
+/// Runs a breadth first search backwards from the goal, up to the given distance, and records for
+/// every state reached the move that brings it one step closer to the goal.
+/// Transitions are assumed to be reversible, so the successors of a state are also its predecessors.
+/// The goal itself maps to itself with a distance of 0.
+pub fn extract_policy<S: State>(goal: &S, radius: u32) -> HashMap<S, PolicyEntry<S>> {
+    let mut policy = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    policy.insert(*goal, PolicyEntry { next: *goal, distance: 0 });
+    queue.push_back(*goal);
+
+    while let Some(state) = queue.pop_front() {
+        let distance = policy[&state].distance;
+        if distance >= radius {
+            continue;
+        }
+
+        for predecessor in state.successors() {
+            if let Entry::Vacant(entry) = policy.entry(predecessor) {
+                entry.insert(PolicyEntry { next: state, distance: distance + 1 });
+                queue.push_back(predecessor);
+            }
+        }
+    }
+
+    policy
+}
+
+/// The move that brings a board one step closer to the goal, none for the goal itself, and how far
+/// the board is from the goal
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MoveEntry {
+    pub best_move: Option<Move>,
+    pub distance: u32,
+}
+
+/// Lookup table of optimal moves for every board within a radius of the goal
+#[derive(Debug)]
+pub struct Policy<const R: usize = 3, const C: usize = R> {
+    entries: HashMap<Board<R, C>, MoveEntry>,
+}
+
+impl<const R: usize, const C: usize> Policy<R, C> {
+    pub fn new(goal: Board<R, C>, radius: u32) -> Policy<R, C> {
+        let initial_state = BoardState::new(goal, &crate::manhattan_heuristic);
+        let entries = extract_policy(&initial_state, radius)
+            .iter()
+            .map(|(state, entry)| (state.board, MoveEntry { best_move: state.board.move_to(&entry.next.board), distance: entry.distance }))
+            .collect();
+
+        Policy { entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The move to make next, if the board is within the radius of the policy and not the goal
+    pub fn best_move(&self, board: &Board<R, C>) -> Option<Move> {
+        self.entries.get(board).and_then(|entry| entry.best_move)
+    }
+
+    /// The optimal distance to the goal, if the board is within the radius of the policy
+    pub fn distance(&self, board: &Board<R, C>) -> Option<u32> {
+        self.entries.get(board).map(|entry| entry.distance)
+    }
+
+    /// Follows the policy from the board to the goal
+    pub fn plan(&self, board: &Board<R, C>) -> Option<Plan<R, C>> {
+        let mut boards = vec![*board];
+        let mut entry = self.entries.get(board)?;
+        while let Some(best_move) = entry.best_move {
+            let next = boards.last().unwrap().apply_move(best_move)?;
+            boards.push(next);
+            entry = &self.entries[&next];
+        }
+
+        Plan::new(boards).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::board::GOAL;
+
+    use super::*;
+
+    #[test]
+    fn test_policy_radius() {
+        let policy = Policy::new(GOAL, 2);
+
+        //goal, 2 boards at distance 1 and 4 at distance 2
+        assert_eq!(policy.len(), 7);
+        assert_eq!(policy.distance(&GOAL), Some(0));
+        assert_eq!(policy.distance(&Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8])), Some(1));
+        assert_eq!(policy.distance(&Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1])), None);
+    }
+
+    #[test]
+    fn test_policy_plan_is_optimal() {
+        let policy = Policy::new(GOAL, 12);
+        let board = Board::new([1, 2, 3, 0, 4, 6, 7, 5, 8]);
+
+        let plan = policy.plan(&board).unwrap();
+        assert_eq!(plan.cost(), policy.distance(&board).unwrap());
+        assert_eq!(plan.steps(), crate::a_star_search(board).unwrap().steps());
+        assert_eq!(*plan.end(), GOAL);
+    }
+
+    #[test]
+    fn test_best_move() {
+        let policy = Policy::new(GOAL, 3);
+        assert_eq!(policy.best_move(&Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8])), Some(Move::Left));
+        assert_eq!(policy.best_move(&GOAL), None);
+        assert_eq!(policy.best_move(&Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1])), None);
+    }
+
+    #[test]
+    fn test_rectangular_policy() {
+        let policy = Policy::new(Board::<3, 4>::goal(), 6);
+        let board = Board::<3, 4>::try_from([[1, 2, 3, 4], [5, 6, 0, 8], [9, 10, 7, 11]]).unwrap();
+
+        let plan = policy.plan(&board).unwrap();
+        assert_eq!(plan.cost(), policy.distance(&board).unwrap());
+        assert_eq!(plan.steps(), crate::a_star_search(board).unwrap().steps());
+        assert_eq!(policy.best_move(&board), Some(plan.moves()[0]));
+        assert!(plan.end().is_goal());
+    }
+}
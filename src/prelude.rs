@@ -0,0 +1,29 @@
+//! The types and functions most programs using the crate need, in a single import:
+//! `use tiles::prelude::*;` brings the boards and moves, the solver and its results, the
+//! heuristics, and the errors they return.
+
+pub use crate::board::{Board, BoardError, Move, MoveError, MoveOrder, ParseBoardError, Tile, GOAL};
+pub use crate::goal::GoalSet;
+pub use crate::heuristic::HeuristicKind;
+pub use crate::pdb::{pattern_db_dual_heuristic, pattern_db_heuristic, DisjointPatternDatabases};
+pub use crate::plan::{Plan, PlanError};
+pub use crate::search::{CancellationToken, Limits, Statistics, StopReason};
+pub use crate::solver::{Algorithm, SolveResult, Solver, UnknownName};
+pub use crate::{displaced_tiles_heuristic, manhattan_heuristic};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_import() {
+        let board: Board = "1 2 3 4 5 6 7 0 8".parse().unwrap();
+        let result: SolveResult = Solver::new(Algorithm::AStar).with_heuristic(HeuristicKind::Manhattan).run(board);
+        let plan: Plan = result.plan.unwrap();
+
+        assert_eq!(plan.moves(), [Move::Left]);
+        assert_eq!(*plan.end(), GOAL);
+        assert_eq!(manhattan_heuristic(&board), 1);
+        assert!("2 1 3".parse::<Board>().is_err());
+    }
+}
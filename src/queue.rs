@@ -1,11 +1,15 @@
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Display, Formatter};
 use std::ops::Deref;
 use std::rc::Rc;
+use std::str::FromStr;
 
 use binary_heap_plus::BinaryHeap;
 use compare::Compare;
 
+use crate::solver::UnknownName;
+
 /// Adaptors to create a common interface for different queue implementations, such as FIFO Queue
 /// and Priority Queue.
 
@@ -15,6 +19,11 @@ pub trait Queue<T>: Sized {
     fn is_empty(&self) -> bool;
     fn len(&self) -> usize;
     fn clear(&mut self);
+    /// Drops the items `keep` rejects, such as those that cannot beat a plan already found, and
+    /// returns how many were dropped
+    fn retain<P: FnMut(&T) -> bool>(&mut self, keep: P) -> usize;
+    /// Empties the queue and returns its storage, so that another queue can reuse the allocation
+    fn into_buffer(self) -> Vec<Rc<T>>;
 }
 
 // Classic FIFO queue
@@ -26,6 +35,12 @@ impl<T> Fifo<T> {
     pub fn new() -> Fifo<T> {
         Fifo { queue: VecDeque::new() }
     }
+
+    /// An empty queue that grows into the storage of a previous one
+    pub fn from_buffer(mut buffer: Vec<Rc<T>>) -> Fifo<T> {
+        buffer.clear();
+        Fifo { queue: VecDeque::from(buffer) }
+    }
 }
 
 impl<T> Queue<T> for Fifo<T> {
@@ -48,6 +63,17 @@ impl<T> Queue<T> for Fifo<T> {
     fn clear(&mut self) {
         self.queue.clear();
     }
+
+    fn retain<P: FnMut(&T) -> bool>(&mut self, mut keep: P) -> usize {
+        let len = self.queue.len();
+        self.queue.retain(|item| keep(item));
+        len - self.queue.len()
+    }
+
+    fn into_buffer(mut self) -> Vec<Rc<T>> {
+        self.queue.clear();
+        Vec::from(self.queue)
+    }
 }
 
 //Priority Queue with Ord comparison
@@ -59,6 +85,12 @@ impl<T: Ord> Priority<T> {
     pub fn new() -> Priority<T> {
         Priority { queue: BinaryHeap::new() }
     }
+
+    /// An empty queue that grows into the storage of a previous one
+    pub fn from_buffer(mut buffer: Vec<Rc<T>>) -> Priority<T> {
+        buffer.clear();
+        Priority { queue: BinaryHeap::from_vec(buffer) }
+    }
 }
 
 impl<T: Ord> Queue<T> for Priority<T> {
@@ -81,6 +113,15 @@ impl<T: Ord> Queue<T> for Priority<T> {
     fn clear(&mut self) {
         self.queue.clear();
     }
+
+    fn retain<P: FnMut(&T) -> bool>(&mut self, keep: P) -> usize {
+        retain_heap(&mut self.queue, keep)
+    }
+
+    fn into_buffer(mut self) -> Vec<Rc<T>> {
+        self.queue.clear();
+        self.queue.into_vec()
+    }
 }
 
 //Priority Queue with customisable comparator
@@ -106,7 +147,15 @@ impl<T, F> PriorityCmp<T, F>
     pub fn new(cmp: F) -> Self
         where F: Fn(&T, &T) -> Ordering,
     {
-        let queue = BinaryHeap::from_vec_cmp(Vec::new(), RcFnComparator(cmp));
+        PriorityCmp::from_buffer(Vec::new(), cmp)
+    }
+
+    /// An empty queue that grows into the storage of a previous one
+    pub fn from_buffer(mut buffer: Vec<Rc<T>>, cmp: F) -> Self
+        where F: Fn(&T, &T) -> Ordering,
+    {
+        buffer.clear();
+        let queue = BinaryHeap::from_vec_cmp(buffer, RcFnComparator(cmp));
 
         PriorityCmp { queue }
     }
@@ -134,4 +183,569 @@ impl<T, F> Queue<T> for PriorityCmp<T, F>
     fn clear(&mut self) {
         self.queue.clear();
     }
+
+    fn retain<P: FnMut(&T) -> bool>(&mut self, keep: P) -> usize {
+        retain_heap(&mut self.queue, keep)
+    }
+
+    fn into_buffer(mut self) -> Vec<Rc<T>> {
+        self.queue.clear();
+        self.queue.into_vec()
+    }
+}
+
+//the kept items are pushed back one at a time, in O(n log n): the heap does not give its comparator
+//back, which rebuilding it from a vector in linear time would take
+fn retain_heap<T, C, P>(heap: &mut BinaryHeap<Rc<T>, C>, mut keep: P) -> usize
+    where C: Compare<Rc<T>>,
+          P: FnMut(&T) -> bool,
+{
+    let items: Vec<Rc<T>> = heap.drain().collect();
+    let len = items.len();
+    for item in items.into_iter().filter(|item| keep(item)) {
+        heap.push(item);
+    }
+
+    len - heap.len()
+}
+
+/// The priority queues that the informed searches can order their states with. They all pop the
+/// states in the same order, but differ in how fast they are on different workloads.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum QueueKind {
+    /// A binary heap, the default
+    #[default]
+    BinaryHeap,
+    /// A heap where every node has this many children, which is shallower than a binary heap
+    DAryHeap(usize),
+    /// A pairing heap, with constant time insertion
+    PairingHeap,
+    /// A queue of the states of each priority in the order they were added, which suits the small
+    /// integer priorities of the searches
+    Buckets,
+}
+
+impl QueueKind {
+    /// Names of the kinds, except for the d-ary heaps which are named after their arity, like `4-ary`
+    pub fn name(&self) -> String {
+        match self {
+            QueueKind::BinaryHeap => "binary".to_string(),
+            QueueKind::DAryHeap(arity) => format!("{}-ary", arity),
+            QueueKind::PairingHeap => "pairing".to_string(),
+            QueueKind::Buckets => "buckets".to_string(),
+        }
+    }
+}
+
+impl Display for QueueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for QueueKind {
+    type Err = UnknownName;
+
+    fn from_str(name: &str) -> Result<QueueKind, UnknownName> {
+        match name {
+            "binary" => Ok(QueueKind::BinaryHeap),
+            "pairing" => Ok(QueueKind::PairingHeap),
+            "buckets" => Ok(QueueKind::Buckets),
+            _ => name.strip_suffix("-ary")
+                .and_then(|arity| arity.parse::<usize>().ok())
+                .filter(|arity| *arity >= 2)
+                .map(QueueKind::DAryHeap)
+                .ok_or_else(|| UnknownName(name.to_string())),
+        }
+    }
+}
+
+/// Heap with `arity` children per node, popping the greatest item according to the comparator
+pub struct DAryHeap<T, F> {
+    items: Vec<Rc<T>>,
+    arity: usize,
+    cmp: F,
+}
+
+impl<T, F: Fn(&T, &T) -> Ordering> DAryHeap<T, F> {
+    pub fn new(arity: usize, cmp: F) -> DAryHeap<T, F> {
+        DAryHeap::from_buffer(Vec::new(), arity, cmp)
+    }
+
+    /// An empty queue that grows into the storage of a previous one
+    pub fn from_buffer(mut buffer: Vec<Rc<T>>, arity: usize, cmp: F) -> DAryHeap<T, F> {
+        assert!(arity >= 2, "A d-ary heap needs at least two children per node.");
+        buffer.clear();
+        DAryHeap { items: buffer, arity, cmp }
+    }
+
+    fn greater(&self, i: usize, j: usize) -> bool {
+        (self.cmp)(&self.items[i], &self.items[j]) == Ordering::Greater
+    }
+}
+
+impl<T, F: Fn(&T, &T) -> Ordering> Queue<T> for DAryHeap<T, F> {
+    fn enqueue(&mut self, item: Rc<T>) {
+        self.items.push(item);
+
+        let mut child = self.items.len() - 1;
+        while child > 0 {
+            let parent = (child - 1) / self.arity;
+            if !self.greater(child, parent) {
+                break;
+            }
+            self.items.swap(child, parent);
+            child = parent;
+        }
+    }
+
+    fn dequeue(&mut self) -> Option<Rc<T>> {
+        if self.items.is_empty() {
+            return None;
+        }
+
+        let top = self.items.swap_remove(0);
+        self.sift_down(0);
+
+        Some(top)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    fn retain<P: FnMut(&T) -> bool>(&mut self, mut keep: P) -> usize {
+        let len = self.items.len();
+        self.items.retain(|item| keep(item));
+        //restore the heap order from the last parent up
+        for parent in (0..self.items.len().saturating_sub(1) / self.arity + 1).rev() {
+            self.sift_down(parent);
+        }
+
+        len - self.items.len()
+    }
+
+    fn into_buffer(mut self) -> Vec<Rc<T>> {
+        self.items.clear();
+        self.items
+    }
+}
+
+impl<T, F: Fn(&T, &T) -> Ordering> DAryHeap<T, F> {
+    //moves the item down until none of its children is greater
+    fn sift_down(&mut self, mut parent: usize) {
+        loop {
+            let first_child = parent * self.arity + 1;
+            let last_child = (first_child + self.arity).min(self.items.len());
+            let greatest = (first_child..last_child).fold(parent, |greatest, child| {
+                if self.greater(child, greatest) { child } else { greatest }
+            });
+            if greatest == parent {
+                break;
+            }
+            self.items.swap(parent, greatest);
+            parent = greatest;
+        }
+    }
+}
+
+struct PairingNode<T> {
+    item: Option<Rc<T>>,
+    child: Option<usize>,
+    sibling: Option<usize>,
+}
+
+/// Pairing heap popping the greatest item according to the comparator, with its nodes kept in a
+/// vector and linked by index. The slots of popped nodes are reused by the next items.
+pub struct PairingHeap<T, F> {
+    nodes: Vec<PairingNode<T>>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    len: usize,
+    cmp: F,
+}
+
+impl<T, F: Fn(&T, &T) -> Ordering> PairingHeap<T, F> {
+    pub fn new(cmp: F) -> PairingHeap<T, F> {
+        PairingHeap { nodes: Vec::new(), free: Vec::new(), root: None, len: 0, cmp }
+    }
+
+    fn item(&self, node: usize) -> &T {
+        self.nodes[node].item.as_ref().unwrap()
+    }
+
+    //makes the root with the lower priority the first child of the other one
+    fn merge(&mut self, first: usize, second: usize) -> usize {
+        let (parent, child) = if (self.cmp)(self.item(first), self.item(second)) == Ordering::Less {
+            (second, first)
+        } else {
+            (first, second)
+        };
+
+        self.nodes[child].sibling = self.nodes[parent].child;
+        self.nodes[parent].child = Some(child);
+        parent
+    }
+}
+
+impl<T, F: Fn(&T, &T) -> Ordering> Queue<T> for PairingHeap<T, F> {
+    fn enqueue(&mut self, item: Rc<T>) {
+        let node = PairingNode { item: Some(item), child: None, sibling: None };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+
+        self.root = Some(match self.root {
+            Some(root) => self.merge(root, index),
+            None => index,
+        });
+        self.len += 1;
+    }
+
+    fn dequeue(&mut self) -> Option<Rc<T>> {
+        let root = self.root?;
+        let item = self.nodes[root].item.take();
+        self.free.push(root);
+        self.len -= 1;
+
+        let mut children = Vec::new();
+        let mut next = self.nodes[root].child.take();
+        while let Some(child) = next {
+            next = self.nodes[child].sibling.take();
+            children.push(child);
+        }
+
+        //two pass merge: pair up the children from left to right, then merge the pairs from right to left
+        let mut pairs = Vec::with_capacity(children.len() / 2 + 1);
+        for pair in children.chunks(2) {
+            pairs.push(if pair.len() == 2 { self.merge(pair[0], pair[1]) } else { pair[0] });
+        }
+        self.root = pairs.pop();
+        while let Some(pair) = pairs.pop() {
+            self.root = Some(self.merge(pair, self.root.unwrap()));
+        }
+
+        item
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// Merges the kept items into a new heap
+    fn retain<P: FnMut(&T) -> bool>(&mut self, mut keep: P) -> usize {
+        let len = self.len;
+        let items: Vec<Rc<T>> = self.nodes.iter_mut().filter_map(|node| node.item.take()).collect();
+        self.clear();
+        for item in items.into_iter().filter(|item| keep(item)) {
+            self.enqueue(item);
+        }
+
+        len - self.len
+    }
+
+    /// The nodes are not stored as items, so there is no storage to hand over
+    fn into_buffer(self) -> Vec<Rc<T>> {
+        Vec::new()
+    }
+}
+
+/// Priority queue that pops the items with the smallest key first, and items with the same key in
+/// the order they were added
+pub struct Buckets<T, K, F> {
+    buckets: BTreeMap<K, VecDeque<Rc<T>>>,
+    len: usize,
+    key: F,
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> Buckets<T, K, F> {
+    pub fn new(key: F) -> Buckets<T, K, F> {
+        Buckets { buckets: BTreeMap::new(), len: 0, key }
+    }
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> Queue<T> for Buckets<T, K, F> {
+    fn enqueue(&mut self, item: Rc<T>) {
+        self.buckets.entry((self.key)(&item)).or_default().push_back(item);
+        self.len += 1;
+    }
+
+    fn dequeue(&mut self) -> Option<Rc<T>> {
+        let mut bucket = self.buckets.first_entry()?;
+        let item = bucket.get_mut().pop_front();
+        if bucket.get().is_empty() {
+            bucket.remove();
+        }
+        self.len -= 1;
+
+        item
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        self.buckets.clear();
+        self.len = 0;
+    }
+
+    fn retain<P: FnMut(&T) -> bool>(&mut self, mut keep: P) -> usize {
+        let len = self.len;
+        for bucket in self.buckets.values_mut() {
+            bucket.retain(|item| keep(item));
+        }
+        self.buckets.retain(|_, bucket| !bucket.is_empty());
+        self.len = self.buckets.values().map(VecDeque::len).sum();
+
+        len - self.len
+    }
+
+    /// The items are spread over the buckets, so there is no single storage to hand over
+    fn into_buffer(self) -> Vec<Rc<T>> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    fn drain<Q: Queue<i32>>(mut queue: Q, items: &[i32]) -> Vec<i32> {
+        for item in items {
+            queue.enqueue(Rc::new(*item));
+        }
+        assert_eq!(queue.len(), items.len());
+
+        let mut drained = Vec::new();
+        while let Some(item) = queue.dequeue() {
+            drained.push(*item);
+        }
+        assert!(queue.is_empty());
+        drained
+    }
+
+    fn items() -> Vec<i32> {
+        (0..200).map(|item| (item * 37) % 101).collect()
+    }
+
+    #[test]
+    fn test_heaps_pop_greatest_first() {
+        let mut sorted = items();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+
+        assert_eq!(drain(PriorityCmp::new(|a: &i32, b: &i32| a.cmp(b)), &items()), sorted);
+        assert_eq!(drain(DAryHeap::new(2, |a: &i32, b: &i32| a.cmp(b)), &items()), sorted);
+        assert_eq!(drain(DAryHeap::new(4, |a: &i32, b: &i32| a.cmp(b)), &items()), sorted);
+        assert_eq!(drain(PairingHeap::new(|a: &i32, b: &i32| a.cmp(b)), &items()), sorted);
+    }
+
+    #[test]
+    fn test_buckets_in_order_of_insertion() {
+        //the key is the last digit, so items with the same last digit keep their order
+        let drained = drain(Buckets::new(|item: &i32| item % 10), &[21, 10, 3, 11, 30, 1]);
+        assert_eq!(drained, vec![10, 30, 21, 11, 1, 3]);
+    }
+
+    fn retain_odd<Q: Queue<i32>>(mut queue: Q) -> Vec<i32> {
+        for item in items() {
+            queue.enqueue(Rc::new(item));
+        }
+        //pop a few first, so that the heaps are not freshly built
+        let mut drained: Vec<i32> = (0..5).map(|_| *queue.dequeue().unwrap()).collect();
+        let removed = queue.retain(|item| item % 2 == 1);
+        assert_eq!(removed + queue.len() + 5, items().len());
+
+        while let Some(item) = queue.dequeue() {
+            assert_eq!(item.as_ref() % 2, 1);
+            drained.push(*item);
+        }
+        drained
+    }
+
+    #[test]
+    fn test_retain() {
+        let odd_after = |items: Vec<i32>| -> Vec<i32> {
+            items[..5].iter().copied().chain(items[5..].iter().copied().filter(|item| item % 2 == 1)).collect()
+        };
+        let mut sorted = items();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+        let kept = odd_after(sorted);
+
+        assert_eq!(retain_odd(Priority::new()), kept);
+        assert_eq!(retain_odd(PriorityCmp::new(|a: &i32, b: &i32| a.cmp(b))), kept);
+        assert_eq!(retain_odd(DAryHeap::new(2, |a: &i32, b: &i32| a.cmp(b))), kept);
+        assert_eq!(retain_odd(DAryHeap::new(4, |a: &i32, b: &i32| a.cmp(b))), kept);
+        assert_eq!(retain_odd(PairingHeap::new(|a: &i32, b: &i32| a.cmp(b))), kept);
+        assert_eq!(retain_odd(Buckets::new(|item: &i32| -item)), kept);
+        assert_eq!(retain_odd(Fifo::new()), odd_after(items()));
+    }
+
+    #[test]
+    fn test_pairing_heap_reuses_nodes() {
+        let mut queue = PairingHeap::new(|a: &i32, b: &i32| b.cmp(a));
+        for item in 0..10 {
+            queue.enqueue(Rc::new(item));
+            queue.enqueue(Rc::new(item + 100));
+            assert_eq!(queue.dequeue().map(|item| *item), Some(item));
+        }
+
+        assert_eq!(queue.len(), 10);
+        assert_eq!(queue.nodes.len(), 11);
+    }
+
+    #[test]
+    fn test_queue_kind_names() {
+        for kind in [QueueKind::BinaryHeap, QueueKind::DAryHeap(4), QueueKind::PairingHeap, QueueKind::Buckets].iter() {
+            assert_eq!(kind.name().parse::<QueueKind>(), Ok(*kind));
+        }
+        assert!("1-ary".parse::<QueueKind>().is_err());
+        assert!("fibonacci".parse::<QueueKind>().is_err());
+    }
+
+    //an item is a priority and the order it was added in
+    type Item = (i32, usize);
+
+    /// Reference queue: the items sorted by key, and the oldest first among equal keys, so that
+    /// the next item to pop is the last one
+    struct Model<K> {
+        items: Vec<Item>,
+        key: K,
+        //whether the queue must pop the oldest of the items with the greatest key, rather than any of them
+        stable: bool,
+    }
+
+    impl<K: Fn(&Item) -> i32> Model<K> {
+        fn enqueue(&mut self, item: Item) {
+            let order = |item: &Item| ((self.key)(item), std::cmp::Reverse(item.1));
+            let index = self.items.partition_point(|other| order(other) < order(&item));
+            self.items.insert(index, item);
+        }
+
+        //checks that the queue popped an item the model could have popped, and removes it
+        fn dequeue(&mut self, popped: Option<Item>) {
+            let (popped, expected) = match (popped, self.items.last()) {
+                (Some(popped), Some(expected)) => (popped, *expected),
+                (popped, expected) => return assert_eq!(popped, expected.copied()),
+            };
+
+            if self.stable {
+                assert_eq!(popped, expected);
+            } else {
+                assert_eq!((self.key)(&popped), (self.key)(&expected), "popped {:?} before {:?}", popped, expected);
+            }
+            let index = self.items.iter().rposition(|item| *item == popped).expect("popped an item that was never added");
+            self.items.remove(index);
+        }
+    }
+
+    /// Runs random operations on the queue and the model side by side, clearing the queue now and
+    /// then as enforced hill-climbing does when it finds a better state
+    fn check_against_model<Q, K>(mut queue: Q, key: K, stable: bool, seed: u64)
+        where Q: Queue<Item>,
+              K: Fn(&Item) -> i32,
+    {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut model = Model { items: Vec::new(), key, stable };
+
+        for added in 0..2000 {
+            match rng.gen_range(0..100) {
+                0..=49 => {
+                    let item = (rng.gen_range(0..20), added);
+                    queue.enqueue(Rc::new(item));
+                    model.enqueue(item);
+                }
+                50..=89 => model.dequeue(queue.dequeue().map(|item| *item)),
+                90..=97 => {
+                    let divisor = rng.gen_range(2..5);
+                    let keep = |item: &Item| item.0 % divisor != 0;
+                    let len = model.items.len();
+                    model.items.retain(keep);
+                    assert_eq!(queue.retain(keep), len - model.items.len());
+                }
+                _ => {
+                    queue.clear();
+                    model.items.clear();
+                }
+            }
+
+            assert_eq!(queue.len(), model.items.len());
+            assert_eq!(queue.is_empty(), model.items.is_empty());
+        }
+
+        while !model.items.is_empty() {
+            model.dequeue(queue.dequeue().map(|item| *item));
+        }
+        assert!(queue.dequeue().is_none());
+    }
+
+    #[test]
+    fn test_equivalent_to_model() {
+        let priority = |item: &Item| item.0;
+        let cmp = |a: &Item, b: &Item| a.0.cmp(&b.0);
+
+        for seed in 0..20 {
+            check_against_model(Fifo::new(), |_: &Item| 0, true, seed);
+            check_against_model(Priority::new(), priority, false, seed);
+            check_against_model(PriorityCmp::new(cmp), priority, false, seed);
+            for arity in 2..=5 {
+                check_against_model(DAryHeap::new(arity, cmp), priority, false, seed);
+            }
+            check_against_model(PairingHeap::new(cmp), priority, false, seed);
+            //the buckets pop the smallest key first, and the oldest of the items with that key
+            check_against_model(Buckets::new(|item: &Item| -item.0), priority, true, seed);
+        }
+    }
+
+    #[test]
+    fn test_reused_buffers_equivalent_to_model() {
+        let priority = |item: &Item| item.0;
+        let cmp = |a: &Item, b: &Item| a.0.cmp(&b.0);
+        //a buffer left with items in it, as a search stopped by its limits hands over
+        let buffer = || {
+            let mut queue = Fifo::new();
+            (0..50).for_each(|item| queue.enqueue(Rc::new((item, item as usize))));
+            queue.queue.into_iter().collect::<Vec<_>>()
+        };
+
+        for seed in 0..5 {
+            check_against_model(Fifo::from_buffer(buffer()), |_: &Item| 0, true, seed);
+            check_against_model(Priority::from_buffer(buffer()), priority, false, seed);
+            check_against_model(PriorityCmp::from_buffer(buffer(), cmp), priority, false, seed);
+            check_against_model(DAryHeap::from_buffer(buffer(), 4, cmp), priority, false, seed);
+        }
+    }
 }
@@ -0,0 +1,188 @@
+//! Real-time search, for agents that have to make a move at a fixed rate whether or not they found
+//! a plan, such as a robot sliding the tiles of a physical puzzle.
+//! Every call looks ahead from the board for as long as its deadline allows, commits to a single
+//! move, and learns a better estimate of the board it leaves (LRTA*), so that later calls do not
+//! keep going around the same boards.
+//! The embedded profile has no clock to keep the deadlines by, and looks ahead a fixed number of
+//! moves instead.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::board::{Board, Move};
+use crate::clock::Instant;
+
+/// The moves of lookahead of the embedded profile, unless the solver is given its own
+pub const EMBEDDED_LOOKAHEAD: usize = 6;
+
+/// Picks the moves towards the goal one at a time, learning from the boards it went through
+#[derive(Debug, Clone, Default)]
+pub struct RealTimeSolver<const R: usize = 3, const C: usize = R> {
+    //the estimates raised above the manhattan distance of the boards left so far
+    learned: HashMap<Board<R, C>, i32>,
+    //the most moves to look ahead, whatever the deadline
+    max_depth: Option<usize>,
+}
+
+impl<const R: usize, const C: usize> RealTimeSolver<R, C> {
+    pub fn new() -> RealTimeSolver<R, C> {
+        RealTimeSolver { learned: HashMap::new(), max_depth: None }
+    }
+
+    /// Looks ahead at most this many moves, however long the deadline, so that the work of every
+    /// move is bounded by the board rather than the clock
+    pub fn with_max_depth(mut self, max_depth: usize) -> RealTimeSolver<R, C> {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// The move to make from the board, picked within the deadline, or None if the board is the
+    /// goal or cannot reach it.
+    /// The lookahead deepens one move at a time until the deadline, and the move is picked by the
+    /// last depth searched in full, up to the maximum depth. A single move of lookahead is always
+    /// searched, even once the deadline has passed. With the `embedded` feature the deadline is not
+    /// kept, and the lookahead goes to the maximum depth, `EMBEDDED_LOOKAHEAD` unless set.
+    pub fn next_move(&mut self, board: Board<R, C>, deadline: Duration) -> Option<Move> {
+        if board.is_goal() || !board.is_solvable() {
+            return None;
+        }
+
+        let (end, max_depth) = if cfg!(feature = "embedded") {
+            (None, self.max_depth.unwrap_or(EMBEDDED_LOOKAHEAD))
+        } else {
+            (Some(Instant::now() + deadline), self.max_depth.unwrap_or(usize::MAX))
+        };
+        let successors = board.successors_with_moves();
+        let mut values: Vec<i32> = successors.iter().map(|(_, successor)| 1 + self.h(successor)).collect();
+        //the successors are a move of lookahead already
+        for depth in 1..max_depth {
+            let deeper: Option<Vec<i32>> = successors.iter()
+                .map(|(_, successor)| self.lookahead(successor, &board, depth, end).map(|value| 1 + value))
+                .collect();
+            match deeper {
+                Some(deeper) => values = deeper,
+                None => break,
+            }
+        }
+
+        let (best, value) = values.iter().enumerate().min_by_key(|(_, value)| **value).unwrap();
+        //the board is at least as far from the goal as its best successor, plus the move
+        if *value > self.h(&board) {
+            self.learned.insert(board, *value);
+        }
+
+        Some(successors[best].0)
+    }
+
+    /// The number of boards whose estimate was raised
+    pub fn learned(&self) -> usize {
+        self.learned.len()
+    }
+
+    /// Forgets what was learned, such as when the goal changes
+    pub fn reset(&mut self) {
+        self.learned.clear();
+    }
+
+    fn h(&self, board: &Board<R, C>) -> i32 {
+        self.learned.get(board).copied().unwrap_or_else(|| board.manhattan_dist())
+    }
+
+    /// The lowest moves plus estimate among the boards `depth` moves away (or the goal if reached
+    /// sooner), without going back to the previous board, or None if the deadline passed first
+    fn lookahead(&self, board: &Board<R, C>, previous: &Board<R, C>, depth: usize, end: Option<Instant>) -> Option<i32> {
+        if depth == 0 || board.is_goal() {
+            return Some(self.h(board));
+        }
+        if end.is_some_and(|end| Instant::now() >= end) {
+            return None;
+        }
+
+        let mut best = i32::MAX;
+        for successor in board.successors().iter().filter(|successor| *successor != previous) {
+            best = best.min(1 + self.lookahead(successor, board, depth - 1, end)?);
+        }
+
+        //a board inside the lookahead is never closer than what was learned about it
+        Some(best.max(self.h(board)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::GOAL;
+
+    use super::*;
+
+    #[test]
+    fn test_goal_and_unsolvable() {
+        let mut solver = RealTimeSolver::new();
+        assert_eq!(solver.next_move(GOAL, Duration::from_millis(10)), None);
+        assert_eq!(solver.next_move(Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]), Duration::from_millis(10)), None);
+        assert_eq!(solver.next_move(Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8]), Duration::ZERO), Some(Move::Left));
+    }
+
+    #[test]
+    fn test_reaches_goal_within_deadlines() {
+        let mut solver = RealTimeSolver::new();
+        let mut board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let mut moves = 0;
+        while let Some(next_move) = solver.next_move(board, Duration::from_millis(2)) {
+            board = board.apply_move(next_move).unwrap();
+            moves += 1;
+            assert!(moves < 10_000, "The solver did not reach the goal.");
+        }
+
+        assert!(board.is_goal());
+        assert!(moves >= 31);
+    }
+
+    #[test]
+    fn test_short_deadlines() {
+        //lookaheads of varying depth used to ignore what was learned about the boards inside them,
+        //and keep going back to the same ones
+        for deadline in [20, 50, 100, 200] {
+            let mut solver = RealTimeSolver::new();
+            let mut board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+            let mut moves = 0;
+            while let Some(next_move) = solver.next_move(board, Duration::from_micros(deadline)) {
+                board = board.apply_move(next_move).unwrap();
+                moves += 1;
+                assert!(moves < 10_000, "The solver did not reach the goal with deadlines of {} microseconds.", deadline);
+            }
+        }
+    }
+
+    #[test]
+    fn test_deadline() {
+        let mut solver = RealTimeSolver::<4>::new();
+        let board: Board<4> = "5 1 3 4 / 9 2 7 8 / 13 6 10 12 / 14 0 11 15".parse().unwrap();
+        let start = Instant::now();
+        assert!(solver.next_move(board, Duration::from_millis(20)).is_some());
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_max_depth() {
+        //the depth ends the lookahead long before the deadline would
+        let board: Board<4> = "5 1 3 4 / 9 2 7 8 / 13 6 10 12 / 14 0 11 15".parse().unwrap();
+        let mut solver = RealTimeSolver::<4>::new().with_max_depth(4);
+        let next_move = solver.next_move(board, Duration::from_secs(3600));
+        assert_eq!(next_move, RealTimeSolver::<4>::new().with_max_depth(4).next_move(board, Duration::from_secs(1)));
+        assert!(next_move.is_some());
+    }
+
+    #[test]
+    fn test_learns_from_dead_ends() {
+        let mut solver = RealTimeSolver::new();
+        //without lookahead, the greedy moves go around a local minimum of the manhattan distance
+        let mut board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        for _ in 0..50 {
+            board = board.apply_move(solver.next_move(board, Duration::ZERO).unwrap()).unwrap();
+        }
+
+        assert!(solver.learned() > 0);
+        solver.reset();
+        assert_eq!(solver.learned(), 0);
+    }
+}
@@ -0,0 +1,212 @@
+//! Time budgets for solving a suite of boards within a total time, so that a few boards that would
+//! take far longer than the others cannot use up the time of the rest.
+
+use std::time::Duration;
+
+use crate::board::Board;
+use crate::clock::Instant;
+use crate::search::Limits;
+use crate::solver::{SolveResult, Solver};
+
+/// How much longer than predicted a board may search before it gives up
+pub const SLACK: u32 = 2;
+
+/// How many fair shares of the remaining time a board may borrow when it is predicted to need
+/// more than its own
+pub const MAX_SHARES: u32 = 4;
+
+/// How many times longer the searches take per unit of heuristic value, until the solve times
+/// recorded show how they grow
+pub const DEFAULT_GROWTH: f64 = 2.0;
+
+/// The time given to one board, and what it was based on
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleDecision {
+    /// Index of the board in the suite
+    pub instance: usize,
+    /// The heuristic value of the board, which the boards are solved in increasing order of
+    pub h: i32,
+    /// The time the board was expected to take, once a board of the suite was solved
+    pub predicted: Option<Duration>,
+    /// The remaining time divided by the number of boards left
+    pub fair_share: Duration,
+    pub budget: Duration,
+    /// Whether the board was predicted to need more than it could borrow, and was held to its
+    /// fair share
+    pub capped: bool,
+}
+
+/// A board solved within its budget
+#[derive(Debug, Clone)]
+pub struct ScheduledResult {
+    pub decision: ScheduleDecision,
+    pub result: SolveResult,
+}
+
+/// Predicts how long the boards take from their heuristic value, fitting the logarithm of the
+/// solve times recorded to a line
+#[derive(Debug, Clone, Default)]
+pub struct Scheduler {
+    //the heuristic value and the logarithm of the solve time in seconds
+    history: Vec<(f64, f64)>,
+}
+
+impl Scheduler {
+    pub fn new() -> Scheduler {
+        Scheduler { history: Vec::new() }
+    }
+
+    /// Records how long a board that was solved took
+    pub fn record(&mut self, h: i32, duration: Duration) {
+        self.history.push((h as f64, duration.as_secs_f64().max(1e-6).ln()));
+    }
+
+    /// The expected solve time of a board, if any solve time was recorded. The times grow by
+    /// `DEFAULT_GROWTH` per unit of heuristic value until boards of different values were solved,
+    /// and the fitted times do not decrease.
+    pub fn predict(&self, h: i32) -> Option<Duration> {
+        if self.history.is_empty() {
+            return None;
+        }
+
+        let count = self.history.len() as f64;
+        let mean_h = self.history.iter().map(|(h, _)| h).sum::<f64>() / count;
+        let mean_log = self.history.iter().map(|(_, log)| log).sum::<f64>() / count;
+        let variance: f64 = self.history.iter().map(|(h, _)| (h - mean_h).powi(2)).sum();
+        let covariance: f64 = self.history.iter().map(|(h, log)| (h - mean_h) * (log - mean_log)).sum();
+        let slope = if variance > 0.0 && covariance > 0.0 { covariance / variance } else { DEFAULT_GROWTH.ln() };
+
+        let seconds = (mean_log + slope * (h as f64 - mean_h)).exp();
+        Some(Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX))
+    }
+
+    /// Decides the budget of a board given the time left for the suite and the number of boards
+    /// left, including this one.
+    /// A board gets at least its fair share of the remaining time, as the time it does not use
+    /// goes back to the boards after it, and up to `MAX_SHARES` fair shares when it is predicted
+    /// to need them. A board predicted to need even more is held to its fair share.
+    pub fn decide(&self, instance: usize, h: i32, remaining: Duration, boards_left: usize) -> ScheduleDecision {
+        assert!(boards_left > 0, "There are no boards left to schedule.");
+
+        let fair_share = remaining / boards_left.min(u32::MAX as usize) as u32;
+        let predicted = self.predict(h);
+        let needed = predicted.map(|predicted| predicted.saturating_mul(SLACK));
+        let capped = needed.is_some_and(|needed| needed > fair_share.saturating_mul(MAX_SHARES));
+        let budget = match needed {
+            Some(needed) if !capped => needed.max(fair_share).min(remaining),
+            _ => fair_share,
+        };
+
+        ScheduleDecision { instance, h, predicted, fair_share, budget, capped }
+    }
+}
+
+/// Solves the boards one after the other within `total`, the easiest first according to the
+/// heuristic of the solver, each with the budget decided by a `Scheduler` that learns from the
+/// boards solved before it. A budget never exceeds the duration limit of the solver, if it has one.
+/// The results are in the same order as the boards.
+pub fn solve_scheduled(solver: &Solver, boards: &[Board], total: Duration) -> Vec<ScheduledResult> {
+    let start = Instant::now();
+    let heuristic = solver.heuristic();
+    let h: Vec<i32> = boards.iter().map(|board| heuristic.h_to(board, solver.goal())).collect();
+    let mut order: Vec<usize> = (0..boards.len()).collect();
+    order.sort_by_key(|index| h[*index]);
+
+    let mut scheduler = Scheduler::new();
+    let mut results = vec![None; boards.len()];
+    for (solved, index) in order.into_iter().enumerate() {
+        let remaining = total.saturating_sub(start.elapsed());
+        let decision = scheduler.decide(index, h[index], remaining, boards.len() - solved);
+
+        let max_duration = solver.limits().max_duration.map_or(decision.budget, |max| max.min(decision.budget));
+        let limits = Limits { max_duration: Some(max_duration), ..solver.limits().clone() };
+        let result = solver.clone().with_limits(limits).run(boards[index]);
+        if result.plan.is_some() {
+            scheduler.record(h[index], result.statistics.duration);
+        }
+
+        results[index] = Some(ScheduledResult { decision, result });
+    }
+
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solver::Algorithm;
+
+    use super::*;
+
+    #[test]
+    fn test_predict() {
+        let mut scheduler = Scheduler::new();
+        assert_eq!(scheduler.predict(10), None);
+
+        //a single time grows by the default rate
+        scheduler.record(10, Duration::from_millis(1));
+        let predicted = scheduler.predict(12).unwrap().as_secs_f64();
+        assert!((predicted - 0.004).abs() < 1e-6, "{}", predicted);
+
+        //the times double with every unit
+        scheduler.record(20, Duration::from_millis(1024));
+        let predicted = scheduler.predict(15).unwrap().as_secs_f64();
+        assert!((predicted - 0.032).abs() < 1e-6, "{}", predicted);
+        assert_eq!(scheduler.predict(1000), Some(Duration::MAX));
+    }
+
+    #[test]
+    fn test_decide() {
+        let mut scheduler = Scheduler::new();
+        let remaining = Duration::from_secs(10);
+        let fair_share = Duration::from_secs(1);
+
+        let decision = scheduler.decide(0, 10, remaining, 10);
+        assert_eq!((decision.predicted, decision.budget, decision.capped), (None, fair_share, false));
+
+        scheduler.record(10, Duration::from_millis(1));
+        scheduler.record(20, Duration::from_millis(1024));
+        //easy boards keep their fair share, as they give back what they do not use
+        assert_eq!(scheduler.decide(1, 15, remaining, 10).budget, fair_share);
+
+        //a slower board borrows from the others
+        let decision = scheduler.decide(2, 20, remaining, 10);
+        assert!(decision.budget > fair_share && decision.budget < fair_share * MAX_SHARES);
+        assert!(!decision.capped);
+
+        //but not one that would need too much of their time
+        let decision = scheduler.decide(3, 30, remaining, 10);
+        assert_eq!((decision.budget, decision.capped), (fair_share, true));
+    }
+
+    #[test]
+    fn test_solve_scheduled() {
+        let boards = [
+            Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]),
+            Board::new([1, 2, 3, 0, 4, 6, 7, 5, 8]),
+            Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]),
+        ];
+        let solver = Solver::new(Algorithm::AStar);
+        let results = solve_scheduled(&solver, &boards, Duration::from_secs(60));
+
+        assert_eq!(results.len(), 3);
+        for (index, scheduled) in results.iter().enumerate() {
+            assert_eq!(scheduled.decision.instance, index);
+            assert_eq!(scheduled.result.board, boards[index]);
+            assert!(scheduled.result.plan.is_some());
+        }
+        //the easiest board is solved first, before there is anything to predict from
+        assert_eq!(results[1].decision.predicted, None);
+        assert!(results[0].decision.predicted.is_some());
+    }
+
+    #[test]
+    #[cfg(not(feature = "embedded"))]
+    fn test_solve_scheduled_out_of_time() {
+        let boards = [Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1])];
+        let results = solve_scheduled(&Solver::new(Algorithm::BreadthFirst), &boards, Duration::ZERO);
+
+        assert_eq!(results[0].decision.budget, Duration::ZERO);
+        assert!(results[0].result.limit_reached());
+        assert!(results[0].result.plan.is_none());
+    }
+}
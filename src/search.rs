@@ -1,63 +1,573 @@
+use std::cell::Cell;
 use std::cmp::Ordering;
 use std::cmp::Ordering::Equal;
-use std::collections::{HashMap, VecDeque};
-use std::fmt::Debug;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt::{self, Debug, Display, Formatter};
 use std::hash::Hash;
+use std::mem::size_of;
 use std::rc::Rc;
-use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::queue::{Fifo, PriorityCmp, Queue};
+use crate::clock::Instant;
+use crate::queue::{Buckets, DAryHeap, Fifo, PairingHeap, PriorityCmp, Queue, QueueKind};
 use crate::search::Transition::{Intermediate, Initial};
 
+/// Shared flag to stop running searches from another thread (or a signal handler).
+/// Clones refer to the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken { cancelled: Arc::new(AtomicBool::new(false)) }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, AtomicOrdering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(AtomicOrdering::SeqCst)
+    }
+}
+
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.cancelled, &other.cancelled)
+    }
+}
+
+impl Eq for CancellationToken {}
+
+/// A snapshot of a running search
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Progress {
+    pub expanded: i32,
+    pub created: i32,
+    /// The lowest heuristic value found so far
+    pub best_h: i32,
+    /// The number of states waiting in the queue
+    pub open: usize,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// The progress as a JSON object on a single line, for the tools that follow long searches
+    pub fn to_json(&self) -> String {
+        format!("{{\"event\":\"progress\",\"expanded\":{},\"created\":{},\"best_h\":{},\"open\":{},\"elapsed_ms\":{}}}",
+                self.expanded, self.created, self.best_h, self.open, self.elapsed.as_millis())
+    }
+}
+
+/// Callback receiving the progress of a search at most once per interval, such as to print it.
+/// Clones refer to the same callback.
+#[derive(Clone)]
+pub struct ProgressHook {
+    interval: Duration,
+    report: Arc<dyn Fn(&Progress) + Send + Sync>,
+}
+
+impl ProgressHook {
+    pub fn new<F: Fn(&Progress) + Send + Sync + 'static>(interval: Duration, report: F) -> ProgressHook {
+        ProgressHook { interval, report: Arc::new(report) }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+impl Debug for ProgressHook {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressHook").field("interval", &self.interval).finish()
+    }
+}
+
+impl PartialEq for ProgressHook {
+    fn eq(&self, other: &Self) -> bool {
+        self.interval == other.interval && Arc::ptr_eq(&self.report, &other.report)
+    }
+}
+
+impl Eq for ProgressHook {}
+
+/// Bounds on the effort spent by a single search, after which it gives up without a plan
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Limits {
+    pub max_expanded: Option<i32>,
+    /// Has no effect with the `embedded` feature, which has no clock: the expansion limit and the
+    /// cancellation are the only limits kept there
+    pub max_duration: Option<Duration>,
+    pub cancel: Option<CancellationToken>,
+    /// Reports how far the search got at regular intervals, without limiting it
+    pub progress: Option<ProgressHook>,
+}
+
+impl Limits {
+    pub fn none() -> Limits {
+        Limits { max_expanded: None, max_duration: None, cancel: None, progress: None }
+    }
+
+    pub(crate) fn reached(&self, statistics: &Statistics, start: Instant) -> bool {
+        self.stop_reason(statistics, start).is_some()
+    }
+
+    /// The first of the limits the search reached, checking the cancellation first
+    pub(crate) fn stop_reason(&self, statistics: &Statistics, start: Instant) -> Option<StopReason> {
+        if self.cancel.as_ref().is_some_and(|cancel| cancel.is_cancelled()) {
+            Some(StopReason::Cancelled)
+        } else if self.max_expanded.is_some_and(|max| statistics.expanded >= max) {
+            Some(StopReason::MaxExpanded)
+        } else if !cfg!(feature = "embedded") && self.max_duration.is_some_and(|max| start.elapsed() >= max) {
+            Some(StopReason::Timeout)
+        } else {
+            None
+        }
+    }
+}
+
+/// Which of its limits a search stopped on
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StopReason {
+    /// The cancellation token was cancelled
+    Cancelled,
+    /// The search expanded `max_expanded` states
+    MaxExpanded,
+    /// The search ran for `max_duration`
+    Timeout,
+}
+
+impl Display for StopReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StopReason::Cancelled => write!(f, "cancelled"),
+            StopReason::MaxExpanded => write!(f, "expansion limit reached"),
+            StopReason::Timeout => write!(f, "timed out"),
+        }
+    }
+}
+
+/// What a search did with a state it expanded, as reported to `SearchHooks::on_expand`
+#[derive(Debug, Clone)]
+pub struct Expanded<S> {
+    pub state: S,
+    pub g: u32,
+    pub h: i32,
+    /// The successors queued
+    pub generated: usize,
+    /// The successors left out, as they were already reached at no higher cost
+    pub skipped: usize,
+    /// The number of states waiting in the queue after the expansion
+    pub open: usize,
+}
+
+/// Called after every expansion
+pub type ExpandHook<'a, S> = Box<dyn FnMut(&Expanded<S>) + 'a>;
+
+/// Whether to take the action from the state, called before the successor it leads to is made and
+/// evaluated
+pub type SuccessorFilter<'a, S> = Box<dyn Fn(&S, <S as State>::Action) -> bool + 'a>;
+
+/// Callbacks into a running search, for the tools that follow it state by state or constrain it
+pub struct SearchHooks<'a, S: State> {
+    pub on_expand: Option<ExpandHook<'a, S>>,
+    /// Vetoes successors, such as those that move a tile already in place, which the statistics
+    /// count as vetoed
+    pub filter: Option<SuccessorFilter<'a, S>>,
+}
+
+impl<'a, S: State> SearchHooks<'a, S> {
+    pub fn none() -> SearchHooks<'a, S> {
+        SearchHooks { on_expand: None, filter: None }
+    }
+
+    pub fn on_expand<F: FnMut(&Expanded<S>) + 'a>(on_expand: F) -> SearchHooks<'a, S> {
+        SearchHooks { on_expand: Some(Box::new(on_expand)), ..SearchHooks::none() }
+    }
+
+    pub fn filter<F: Fn(&S, S::Action) -> bool + 'a>(filter: F) -> SearchHooks<'a, S> {
+        SearchHooks { filter: Some(Box::new(filter)), ..SearchHooks::none() }
+    }
+}
+
 #[derive(Debug)]
 pub struct SearchConfig {
     compute_heuristic: bool,
     ehc: bool,
     best_first_successors: bool,
+    limits: Limits,
 }
 
 impl SearchConfig {
-    fn default() -> SearchConfig {
-        SearchConfig { compute_heuristic: true, ehc: false, best_first_successors: false }
+    fn default(limits: Limits) -> SearchConfig {
+        SearchConfig { compute_heuristic: true, ehc: false, best_first_successors: false, limits }
     }
 
-    fn blind() -> SearchConfig {
-        SearchConfig { compute_heuristic: false, ehc: false, best_first_successors: false }
+    fn blind(limits: Limits) -> SearchConfig {
+        SearchConfig { compute_heuristic: false, ehc: false, best_first_successors: false, limits }
     }
 
-    fn ehc() -> SearchConfig {
-        SearchConfig { compute_heuristic: true, ehc: true, best_first_successors: false }
+    fn ehc(limits: Limits) -> SearchConfig {
+        SearchConfig { compute_heuristic: true, ehc: true, best_first_successors: false, limits }
     }
 
-    fn ehc_steepest_ascent() -> SearchConfig {
-        SearchConfig { compute_heuristic: true, ehc: true, best_first_successors: true }
+    fn ehc_steepest_ascent(limits: Limits) -> SearchConfig {
+        SearchConfig { compute_heuristic: true, ehc: true, best_first_successors: true, limits }
     }
 }
 
+/// The outcome of a search. Whether or not it stopped early, the statistics are complete and the
+/// search has let go of every state it queued or saw, except for those of `plan` and `best_path`
+/// (and of the seen states kept by retaining buffers).
 #[derive(Debug)]
 pub struct SearchResult<S: State> {
-    //todo: change the plan to contain transitions of S to know what the action was
     pub plan: Option<VecDeque<S>>,
+    /// The action taken at each step of the plan, one less than its states
+    pub plan_actions: Option<Vec<S::Action>>,
     pub statistics: Statistics,
+    /// The limit the search gave up on, if it did
+    pub stop_reason: Option<StopReason>,
+    /// The state with the lowest heuristic value found (the initial state for blind searches)
+    pub best: S,
+    pub best_h: i32,
+    /// How the search reached the best state from the initial one, which is the plan when found
+    pub best_path: VecDeque<S>,
+    /// The heuristic value of the initial state (0 for blind searches), a lower bound on the
+    /// solution cost for admissible heuristics
+    pub initial_h: i32,
+    /// The number of moves of the plan, if one was found
+    pub solution_cost: Option<u32>,
+    /// The highest f = g + h among the expanded states (and the goal), which is the last f-layer
+    /// reached by A*
+    pub max_f: i32,
 }
 
-#[derive(Debug)]
+impl<S: State> SearchResult<S> {
+    /// Whether the search gave up because it reached one of its limits
+    pub fn limit_reached(&self) -> bool {
+        self.stop_reason.is_some()
+    }
+}
+
+#[cfg_attr(not(feature = "embedded"), derive(Debug))]
+#[derive(Clone, Default)]
 pub struct Statistics {
-    created: i32,
-    queued: i32,
-    expanded: i32,
-    duration: Duration,
+    pub created: i32,
+    pub queued: i32,
+    /// Queued states dropped before being expanded, since they could not lead to a cheaper plan
+    /// than one already found
+    pub purged: i32,
+    /// Successors left out by the filter of the search
+    pub vetoed: i32,
+    pub expanded: i32,
+    pub seen: usize,
+    /// Estimated bytes held by the seen states, which only grow, so this is also the peak
+    pub memory: usize,
+    pub duration: Duration,
+    /// The effective branching factor, if a plan of at least one step was found
+    pub branching_factor: Option<f64>,
+}
+
+impl Statistics {
+    /// The statistics of a search that created its initial state and queued it
+    pub(crate) fn initial() -> Statistics {
+        let mut statistics = Statistics::default();
+        statistics.count_created(1);
+        statistics.count_queued(1);
+        statistics
+    }
+
+    //the counters other than the expansions, which the expansion limits need, are left out of the
+    //embedded profile
+
+    pub(crate) fn count_created(&mut self, created: i32) {
+        if !cfg!(feature = "embedded") {
+            self.created += created;
+        }
+    }
+
+    pub(crate) fn count_queued(&mut self, queued: i32) {
+        if !cfg!(feature = "embedded") {
+            self.queued += queued;
+        }
+    }
+
+    pub(crate) fn count_purged(&mut self, purged: i32) {
+        if !cfg!(feature = "embedded") {
+            self.purged += purged;
+        }
+    }
+
+    pub(crate) fn count_vetoed(&mut self, vetoed: i32) {
+        if !cfg!(feature = "embedded") {
+            self.vetoed += vetoed;
+        }
+    }
+
+    pub(crate) fn set_seen(&mut self, seen: usize) {
+        if !cfg!(feature = "embedded") {
+            self.seen = seen;
+        }
+    }
+
+    /// The totals of both searches, with the durations added up as if they ran one after the other,
+    /// and the memory of the larger one
+    pub fn combine(&self, other: &Statistics) -> Statistics {
+        Statistics {
+            created: self.created + other.created,
+            queued: self.queued + other.queued,
+            purged: self.purged + other.purged,
+            vetoed: self.vetoed + other.vetoed,
+            expanded: self.expanded + other.expanded,
+            seen: self.seen + other.seen,
+            memory: self.memory.max(other.memory),
+            duration: self.duration + other.duration,
+            branching_factor: None,
+        }
+    }
+}
+
+//the embedded profile only formats the expansions, the one counter it keeps
+#[cfg(feature = "embedded")]
+impl Debug for Statistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Statistics").field("expanded", &self.expanded).finish_non_exhaustive()
+    }
+}
+
+/// The branching factor b* that a uniform tree of the given depth would need to contain the
+/// nodes created by a search (including the initial one), so that 1 + b* + b*^2 + ... + b*^depth
+/// equals the nodes. It is None for a depth of 0, where every branching factor fits, and with the
+/// `embedded` feature, which does not spend the time solving for it.
+pub fn effective_branching_factor(nodes: usize, depth: usize) -> Option<f64> {
+    if depth == 0 || cfg!(feature = "embedded") {
+        return None;
+    }
+
+    let tree_size = |b: f64| (0..=depth).map(|level| b.powi(level as i32)).sum::<f64>();
+    let mut low = 0.0;
+    let mut high = nodes as f64;
+    //the tree size grows with the branching factor, so bisect until the bounds meet
+    for _ in 0..100 {
+        let middle = (low + high) / 2.0;
+        if tree_size(middle) < nodes as f64 {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    Some((low + high) / 2.0)
 }
 
 pub trait State: PartialEq + Eq + Hash + Sized + Copy + Debug {
-    fn successors(&self) -> Vec<Self>;
+    /// What takes a state to one of its successors, such as the move of a tile
+    type Action: Copy + Debug;
+
+    /// A small payload kept with the node of the state from when it is generated until it is
+    /// expanded, such as features worked out by the heuristic or the moves it prefers, so that the
+    /// state does not need a map of its own. `()` when there is none.
+    type Data: Clone + Debug + Default;
+
+    /// The successors of the state, each with the action that reaches it
+    fn successors_with_actions(&self) -> Vec<(Self::Action, Self)>;
+
+    fn successors(&self) -> Vec<Self> {
+        self.successors_with_actions().into_iter().map(|(_, successor)| successor).collect()
+    }
+
+    /// The successors with their actions, in the same order, made one at a time as they are
+    /// consumed, so that a search that stops at the first improving one (such as EHC) does not
+    /// make the others. Unless the state says otherwise, they are all made up front.
+    fn successors_with_actions_iter(&self) -> impl Iterator<Item = (Self::Action, Self)> {
+        self.successors_with_actions().into_iter()
+    }
+
+    fn successors_iter(&self) -> impl Iterator<Item = Self> {
+        self.successors_with_actions_iter().map(|(_, successor)| successor)
+    }
+
+    /// The payload of the node of this state, given that of its parent, or None for the initial
+    /// state
+    fn data(&self, _parent: Option<&Self::Data>) -> Self::Data {
+        Self::Data::default()
+    }
+
+    /// The successors the search expands the state into, given the payload of its node.
+    /// Unless the state says otherwise, they are those of `successors_with_actions_iter`.
+    fn successors_with_data(&self, _data: &Self::Data) -> impl Iterator<Item = (Self::Action, Self)> {
+        self.successors_with_actions_iter()
+    }
+
+    /// The successors of `successors_with_data` reached by the actions `keep` takes, the others
+    /// being left out before their state is made. Unless the state says otherwise, every successor
+    /// is made, and those of the actions left out dropped.
+    fn successors_with_data_kept<K: FnMut(Self::Action) -> bool>(&self, data: &Self::Data, mut keep: K) -> impl Iterator<Item = (Self::Action, Self)> {
+        self.successors_with_data(data).filter(move |(action, _)| keep(*action))
+    }
+
+    fn h(&self) -> i32;
+
+    /// The cost of taking the action from this state, which the plans add up. Every action costs
+    /// one unless the state says otherwise.
+    fn cost(&self, _action: Self::Action) -> u32 {
+        1
+    }
+
+    /// The states that reach this one with a single action, each with that action, for the
+    /// searches that also go backwards from the goal. Unless the state says otherwise, the actions
+    /// are taken to be reversible: the predecessors are the successors, with the action that takes
+    /// each of them back to this state.
+    fn predecessors_with_actions(&self) -> Vec<(Self::Action, Self)> {
+        self.successors()
+            .into_iter()
+            .filter_map(|predecessor| {
+                let action = predecessor.successors_with_actions().into_iter().find(|(_, state)| state == self)?.0;
+                Some((action, predecessor))
+            })
+            .collect()
+    }
+}
+
+/// A state changed in place by its actions, for the searches that follow a single path, such as
+/// IDA*, which apply an action on the way down and undo it on the way back instead of keeping a
+/// copy of every state
+pub trait MutableState: Debug {
+    type Action: Copy + Debug;
+
+    /// The actions possible from the current state
+    fn actions(&self) -> Vec<Self::Action>;
+
+    fn apply(&mut self, action: Self::Action);
+
+    /// Reverts the action, which was the last one applied
+    fn undo(&mut self, action: Self::Action);
+
+    /// Whether the action undoes the previous one, so that the search does not go straight back
+    fn reverses(&self, _action: Self::Action, _previous: Self::Action) -> bool {
+        false
+    }
+
     fn h(&self) -> i32;
 }
 
-#[derive(Debug, Eq)]
+/// The outcome of a search on a mutable state
+#[derive(Debug)]
+pub struct PathResult<S: MutableState> {
+    /// The actions that take the initial state to the goal, if found
+    pub actions: Option<Vec<S::Action>>,
+    pub statistics: Statistics,
+    /// The limit the search gave up on, if it did
+    pub stop_reason: Option<StopReason>,
+    /// The state the search ended on, which is the goal when the actions were found
+    pub state: S,
+}
+
+impl<S: MutableState> PathResult<S> {
+    /// Whether the search gave up because it reached one of its limits, in which case the actions
+    /// found, if any, are not proven optimal
+    pub fn limit_reached(&self) -> bool {
+        self.stop_reason.is_some()
+    }
+}
+
+/// The seen states and the queue of a search, kept between consecutive searches so that their
+/// storage is reused instead of growing again from empty. Every search still starts out empty.
+pub struct SearchBuffers<S: State> {
+    seen: HashMap<Rc<S>, Rc<Transition<S>>>,
+    queue: Vec<Rc<Transition<S>>>,
+    //whether the seen states of the last search are kept for `closed` until the next one starts
+    retain_seen: bool,
+}
+
+/// A state seen by the last search, with the values the search gave it
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ClosedEntry<S> {
+    pub state: S,
+    pub g: u32,
+    pub h: i32,
+    /// The order in which the search created the state, 0 for the initial state
+    pub id: u32,
+    /// The id of the state it was reached from, which may be missing from the seen states when a
+    /// cheaper path to that state was found later on
+    pub parent: Option<u32>,
+}
+
+/// How many entries the containers of a search can hold before they need to grow
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Capacity {
+    pub seen: usize,
+    pub queue: usize,
+}
+
+impl<S: State> SearchBuffers<S> {
+    pub fn new() -> SearchBuffers<S> {
+        SearchBuffers { seen: HashMap::new(), queue: Vec::new(), retain_seen: false }
+    }
+
+    /// Buffers that keep the seen states of each search once it is over, until the next one
+    /// starts, so that they can be gone through with `closed`
+    pub fn retaining() -> SearchBuffers<S> {
+        SearchBuffers { retain_seen: true, ..SearchBuffers::new() }
+    }
+
+    /// The states seen by the last search, in no particular order, when the buffers retain them
+    pub fn closed(&self) -> impl Iterator<Item = ClosedEntry<S>> + '_ {
+        self.seen.values().map(|transition| ClosedEntry {
+            state: *transition.state(),
+            g: transition.g(),
+            h: transition.h(),
+            id: transition.index(),
+            parent: transition.parent().map(|parent| parent.index()),
+        })
+    }
+
+    /// Drops the contents, keeping the storage
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.queue.clear();
+    }
+
+    /// Drops the contents and their storage, such as after a search that grew far larger than the
+    /// next ones will
+    pub fn release(&mut self) {
+        self.seen = HashMap::new();
+        self.queue = Vec::new();
+    }
+
+    pub fn capacity(&self) -> Capacity {
+        Capacity { seen: self.seen.capacity(), queue: self.queue.capacity() }
+    }
+
+    fn take_queue(&mut self) -> Vec<Rc<Transition<S>>> {
+        std::mem::take(&mut self.queue)
+    }
+
+    //keeps the storage of a finished search for the next one
+    fn recycle<Q: Queue<Transition<S>>>(&mut self, queue: Q) {
+        self.queue = queue.into_buffer();
+        self.queue.clear();
+        if !self.retain_seen {
+            self.seen.clear();
+        }
+    }
+}
+
+impl<S: State> Default for SearchBuffers<S> {
+    fn default() -> SearchBuffers<S> {
+        SearchBuffers::new()
+    }
+}
+
+#[derive(Debug)]
 enum Transition<S: State> {
-    Initial { state: Rc<S>, h: i32 },
-    Intermediate { state: Rc<S>, parent: Rc<Transition<S>>, g: u32, index: u32, h: i32 },
+    Initial { state: Rc<S>, h: i32, data: S::Data },
+    Intermediate { state: Rc<S>, action: S::Action, parent: Rc<Transition<S>>, g: u32, index: u32, h: i32, data: S::Data },
 }
 
 impl<S: State> Transition<S> {
@@ -69,116 +579,731 @@ impl<S: State> Transition<S> {
             0
         };
 
-        Initial { state: initial, h }
+        let data = initial.data(None);
+        Initial { state: initial, h, data }
+    }
+
+    fn state(&self) -> &S {
+        match self {
+            Initial { state, .. } => &state,
+            Intermediate { state, .. } => &state
+        }
+    }
+
+    fn action(&self) -> Option<S::Action> {
+        match self {
+            Intermediate { action, .. } => Some(*action),
+            Initial { .. } => None,
+        }
+    }
+
+    fn parent(&self) -> Option<&Transition<S>> {
+        match self {
+            Intermediate { parent, .. } => Some(parent.as_ref()),
+            Initial { .. } => None,
+        }
+    }
+
+    fn data(&self) -> &S::Data {
+        match self {
+            Initial { data, .. } => data,
+            Intermediate { data, .. } => data,
+        }
+    }
+
+    fn h(&self) -> i32 {
+        match self {
+            Initial { h, ..} => *h,
+            Intermediate { h, .. } => *h
+        }
+    }
+
+    fn g(&self) -> u32 {
+        match self {
+            Intermediate { g, .. } => *g,
+            Initial { .. } => 0,
+        }
+    }
+
+    fn index(&self) -> u32 {
+        match self {
+            Intermediate { index, .. } => *index,
+            Initial { .. } => 0,
+        }
+    }
+
+    fn successor(state: Rc<S>, action: S::Action, parent: Rc<Transition<S>>, cost: u32, index: u32, compute_heuristic: bool) -> Transition<S> {
+        let h = if compute_heuristic {
+            state.h()
+        } else {
+            parent.h()
+        };
+
+        let data = state.data(Some(parent.data()));
+        Intermediate { state, action, g: parent.g() + cost, parent, index, h, data }
+    }
+}
+
+impl<S: State> PartialOrd for Transition<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: State> Ord for Transition<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let other_f = other.g() as i32 + other.h();
+        let self_f = self.g() as i32 + self.h();
+
+        other_f.partial_cmp(&self_f).unwrap_or_else(|| Equal)
+    }
+}
+
+impl<S: State> PartialEq for Transition<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.state() == other.state()
+    }
+}
+
+impl<S: State> Eq for Transition<S> {}
+
+pub fn breadth_first_search<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F) -> SearchResult<S> {
+    breadth_first_search_limited(initial, goal, Limits::none())
+}
+
+pub fn breadth_first_search_limited<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits) -> SearchResult<S> {
+    breadth_first_search_buffered(initial, goal, limits, &mut SearchBuffers::new())
+}
+
+pub fn breadth_first_search_buffered<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits, buffers: &mut SearchBuffers<S>) -> SearchResult<S> {
+    let mut queue = Fifo::from_buffer(buffers.take_queue());
+    let result = search(initial, goal, &mut queue, SearchConfig::blind(limits), &mut buffers.seen, &mut SearchHooks::none());
+    buffers.recycle(queue);
+    result
+}
+
+/// A layer of a breadth first search, once every state in it has been reached
+#[derive(Debug)]
+pub struct Layer<'a, S> {
+    /// The number of actions from the initial state to every state of the layer
+    pub depth: u32,
+    pub size: usize,
+    /// The states of the layer, if the search was asked to keep them
+    pub states: Option<&'a [S]>,
+}
+
+struct LayerNode<S: State> {
+    state: S,
+    parent: usize,
+    action: Option<S::Action>,
+}
+
+/// Breadth first search that goes one layer at a time, handing every layer to `on_layer` once it
+/// is complete, starting with the initial state at depth 0, such as to count the states at every
+/// distance or to build a perimeter around the goal. The search stops as soon as it reaches the
+/// goal, without reporting the layer it is in, so a goal that is never reached walks every layer.
+pub fn breadth_first_layers<S, F, L>(initial: &S, goal: F, keep_states: bool, limits: Limits, mut on_layer: L) -> SearchResult<S>
+    where S: State,
+          F: Fn(&S) -> bool,
+          L: FnMut(&Layer<S>)
+{
+    let start = Instant::now();
+    let mut statistics = Statistics::initial();
+    let mut nodes = vec![LayerNode { state: *initial, parent: 0, action: None }];
+    let mut seen = HashSet::new();
+    seen.insert(*initial);
+
+    let mut found = goal(initial).then_some(0);
+    let mut stop_reason = None;
+    let mut layer = 0..1;
+    let mut depth = 0;
+    'layers: while found.is_none() && !layer.is_empty() {
+        let states: Option<Vec<S>> = keep_states.then(|| nodes[layer.clone()].iter().map(|node| node.state).collect());
+        on_layer(&Layer { depth, size: layer.len(), states: states.as_deref() });
+
+        let next = nodes.len();
+        for index in layer.clone() {
+            stop_reason = limits.stop_reason(&statistics, start);
+            if stop_reason.is_some() {
+                break 'layers;
+            }
+
+            statistics.expanded += 1;
+            let state = nodes[index].state;
+            for (action, successor) in state.successors_with_actions_iter() {
+                statistics.count_created(1);
+                if !seen.insert(successor) {
+                    continue;
+                }
+
+                statistics.count_queued(1);
+                nodes.push(LayerNode { state: successor, parent: index, action: Some(action) });
+                if goal(&successor) {
+                    found = Some(nodes.len() - 1);
+                    break 'layers;
+                }
+            }
+        }
+
+        layer = next..nodes.len();
+        depth += 1;
+    }
+
+    statistics.set_seen(seen.len());
+    statistics.memory = nodes.capacity() * size_of::<LayerNode<S>>() + seen.capacity() * size_of::<S>();
+    statistics.duration = start.elapsed();
+
+    let mut best_path = VecDeque::from([*initial]);
+    let mut plan_actions = None;
+    if let Some(mut index) = found {
+        let mut actions = Vec::new();
+        best_path.clear();
+        while let LayerNode { state, parent, action: Some(action) } = nodes[index] {
+            best_path.push_front(state);
+            actions.push(action);
+            index = parent;
+        }
+        best_path.push_front(*initial);
+        actions.reverse();
+
+        statistics.branching_factor = effective_branching_factor(statistics.created as usize, actions.len());
+        plan_actions = Some(actions);
+    }
+
+    let solution_cost = plan_actions.as_ref().map(|actions: &Vec<S::Action>| actions.len() as u32);
+    SearchResult {
+        plan: found.map(|_| best_path.clone()),
+        plan_actions,
+        statistics,
+        stop_reason,
+        best: *best_path.back().unwrap(),
+        best_h: 0,
+        best_path,
+        initial_h: 0,
+        solution_cost,
+        max_f: solution_cost.unwrap_or(depth) as i32,
+    }
+}
+
+/// Uniform cost search (Dijkstra): the states in order of the cost of reaching them, without a
+/// heuristic, so the plan is the cheapest even when actions cost differently, unlike breadth first
+/// search, which only finds the fewest actions
+pub fn uniform_cost_search<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F) -> SearchResult<S> {
+    uniform_cost_search_limited(initial, goal, Limits::none())
+}
+
+pub fn uniform_cost_search_limited<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits) -> SearchResult<S> {
+    uniform_cost_search_buffered(initial, goal, limits, &mut SearchBuffers::new())
+}
+
+pub fn uniform_cost_search_buffered<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits, buffers: &mut SearchBuffers<S>) -> SearchResult<S> {
+    //the blind search leaves every h at 0, so the order of A* is that of g
+    search_in(initial, goal, PriorityCmp::from_buffer(buffers.take_queue(), a_star_order), SearchConfig::blind(limits), buffers)
+}
+
+pub fn ehc_search<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F) -> SearchResult<S> {
+    ehc_search_limited(initial, goal, Limits::none())
+}
+
+pub fn ehc_search_limited<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits) -> SearchResult<S> {
+    ehc_search_buffered(initial, goal, limits, &mut SearchBuffers::new())
+}
+
+pub fn ehc_search_buffered<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits, buffers: &mut SearchBuffers<S>) -> SearchResult<S> {
+    let mut queue = Fifo::from_buffer(buffers.take_queue());
+    let result = search(initial, goal, &mut queue, SearchConfig::ehc(limits), &mut buffers.seen, &mut SearchHooks::none());
+    buffers.recycle(queue);
+    result
+}
+
+pub fn ehc_steepest_search<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F) -> SearchResult<S> {
+    ehc_steepest_search_limited(initial, goal, Limits::none())
+}
+
+pub fn ehc_steepest_search_limited<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits) -> SearchResult<S> {
+    ehc_steepest_search_buffered(initial, goal, limits, &mut SearchBuffers::new())
+}
+
+pub fn ehc_steepest_search_buffered<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits, buffers: &mut SearchBuffers<S>) -> SearchResult<S> {
+    let mut queue = Fifo::from_buffer(buffers.take_queue());
+    let result = search(initial, goal, &mut queue, SearchConfig::ehc_steepest_ascent(limits), &mut buffers.seen, &mut SearchHooks::none());
+    buffers.recycle(queue);
+    result
+}
+
+pub fn greedy_best_first_search<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F) -> SearchResult<S> {
+    greedy_best_first_search_limited(initial, goal, Limits::none())
+}
+
+pub fn greedy_best_first_search_limited<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits) -> SearchResult<S> {
+    greedy_best_first_search_buffered(initial, goal, limits, &mut SearchBuffers::new())
+}
+
+pub fn greedy_best_first_search_buffered<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits, buffers: &mut SearchBuffers<S>) -> SearchResult<S> {
+    greedy_best_first_search_queued(initial, goal, limits, buffers, QueueKind::BinaryHeap)
+}
+
+/// Greedy best first search ordering the states with the chosen kind of priority queue
+pub fn greedy_best_first_search_queued<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits, buffers: &mut SearchBuffers<S>, queue: QueueKind) -> SearchResult<S> {
+    //greedy best first search only considers the heuristic value (h)
+    let config = SearchConfig::default(limits);
+    match queue {
+        QueueKind::BinaryHeap => search_in(initial, goal, PriorityCmp::from_buffer(buffers.take_queue(), greedy_order), config, buffers),
+        QueueKind::DAryHeap(arity) => search_in(initial, goal, DAryHeap::from_buffer(buffers.take_queue(), arity, greedy_order), config, buffers),
+        QueueKind::PairingHeap => search_in(initial, goal, PairingHeap::new(greedy_order), config, buffers),
+        QueueKind::Buckets => search_in(initial, goal, Buckets::new(|transition: &Transition<S>| transition.h()), config, buffers),
+    }
+}
+
+pub fn a_star_search<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F) -> SearchResult<S> {
+    a_star_search_limited(initial, goal, Limits::none())
+}
+
+pub fn a_star_search_limited<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits) -> SearchResult<S> {
+    a_star_search_buffered(initial, goal, limits, &mut SearchBuffers::new())
+}
+
+pub fn a_star_search_buffered<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits, buffers: &mut SearchBuffers<S>) -> SearchResult<S> {
+    a_star_search_queued(initial, goal, limits, buffers, QueueKind::BinaryHeap)
+}
+
+/// A* search ordering the states with the chosen kind of priority queue
+pub fn a_star_search_queued<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits, buffers: &mut SearchBuffers<S>, queue: QueueKind) -> SearchResult<S> {
+    let config = SearchConfig::default(limits);
+    match queue {
+        QueueKind::BinaryHeap => search_in(initial, goal, PriorityCmp::from_buffer(buffers.take_queue(), a_star_order), config, buffers),
+        QueueKind::DAryHeap(arity) => search_in(initial, goal, DAryHeap::from_buffer(buffers.take_queue(), arity, a_star_order), config, buffers),
+        QueueKind::PairingHeap => search_in(initial, goal, PairingHeap::new(a_star_order), config, buffers),
+        //the buckets pop the states with the same f and h in the order they were created, like the heaps
+        QueueKind::Buckets => search_in(initial, goal, Buckets::new(|transition: &Transition<S>| (a_star_eval(transition), transition.h())), config, buffers),
+    }
+}
+
+/// A* search calling the hooks as it goes, such as to narrate the first expansions
+pub fn a_star_search_hooked<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F, limits: Limits, mut hooks: SearchHooks<S>) -> SearchResult<S> {
+    let mut queue = PriorityCmp::new(a_star_order);
+    search(initial, goal, &mut queue, SearchConfig::default(limits), &mut HashMap::new(), &mut hooks)
+}
+
+/// MM, a bidirectional heuristic search that meets in the middle: a forward search from the initial
+/// state guided by `h`, and a backward search from the goal state through the predecessors, guided
+/// by `backward_h`, an estimate of the cost from the initial state. Both expand their states in
+/// order of max(f, 2g), so neither goes past half the cost of the plan, and the search stops once
+/// the cheapest plan through the states where they met cannot be beaten.
+/// The plan is optimal when both heuristics are admissible and every action costs at least one.
+/// When the search stops on a limit, the plan is the cheapest one found so far, if any, which may
+/// not be optimal.
+pub fn mm_search<S: State, H: Fn(&S) -> i32>(initial: &S, goal: &S, backward_h: H, limits: Limits) -> SearchResult<S> {
+    let start = Instant::now();
+    //the initial state and the goal, one for each direction
+    let mut statistics = Statistics::default();
+    statistics.count_created(2);
+    statistics.count_queued(2);
+    let mut forward = Frontier::new(*initial, initial.h());
+    let mut backward = Frontier::new(*goal, backward_h(goal));
+
+    //the cheapest plan found so far, and the state where the two searches met on it
+    let mut incumbent = (initial == goal).then_some((0, *initial));
+    let mut max_f = 0;
+    let mut stop_reason = None;
+    loop {
+        if let Some(reason) = limits.stop_reason(&statistics, start) {
+            stop_reason = Some(reason);
+            break;
+        }
+
+        let (forward_pr, backward_pr) = match (forward.min_priority(), backward.min_priority()) {
+            (Some(forward_pr), Some(backward_pr)) => (forward_pr, backward_pr),
+            //one side has no states left, so every plan went through a state both of them reached
+            _ => break,
+        };
+        if let Some((cost, _)) = incumbent {
+            let gap = forward.min_g() + backward.min_g() + 1;
+            let bound = forward_pr.min(backward_pr).max(forward.min_f()).max(backward.min_f()).max(gap);
+            if cost <= bound {
+                break;
+            }
+        }
+
+        statistics.expanded += 1;
+        let (expanding, other, reversed) = if forward_pr <= backward_pr {
+            (&mut forward, &backward, false)
+        } else {
+            (&mut backward, &forward, true)
+        };
+
+        let (state, g, f) = expanding.pop();
+        max_f = max_f.max(f);
+        let neighbours = if reversed { state.predecessors_with_actions() } else { state.successors_with_actions() };
+        for (action, neighbour) in neighbours {
+            let cost = if reversed { neighbour.cost(action) } else { state.cost(action) };
+            let neighbour_g = g + cost;
+            let h = if reversed { backward_h(&neighbour) } else { neighbour.h() };
+            statistics.count_created(1);
+            if !expanding.reach(neighbour, neighbour_g, h, state, action) {
+                continue;
+            }
+
+            statistics.count_queued(1);
+            if let Some(other_g) = other.g(&neighbour) {
+                if incumbent.is_none_or(|(cost, _)| neighbour_g + other_g < cost) {
+                    incumbent = Some((neighbour_g + other_g, neighbour));
+                }
+            }
+        }
+    }
+
+    statistics.set_seen(forward.nodes.len() + backward.nodes.len());
+    statistics.memory = statistics.seen * size_of::<(S, MeetNode<S>)>() + (forward.states.len() + backward.states.len()) * (size_of::<S>() + 3 * size_of::<MeetEntry>());
+    statistics.duration = start.elapsed();
+
+    let plan = incumbent.map(|(_, meeting)| {
+        let mut plan: VecDeque<(S, Option<S::Action>)> = forward.path(meeting).into_iter().rev().collect();
+        //the backward search reached the states on the way to the goal from their successor
+        let mut state = meeting;
+        while let Some((next, action)) = backward.nodes[&state].parent {
+            plan.back_mut().unwrap().1 = Some(action);
+            plan.push_back((next, None));
+            state = next;
+        }
+        plan
+    });
+
+    //the states on the plan may have been reached again more cheaply after the searches met there
+    let solution_cost = plan.as_ref().map(|plan| plan.iter().filter_map(|(state, action)| action.map(|action| state.cost(action))).sum());
+    if let Some(plan) = &plan {
+        statistics.branching_factor = effective_branching_factor(statistics.created as usize, plan.len() - 1);
+    }
+    let plan_actions = plan.as_ref().map(|plan| plan.iter().filter_map(|(_, action)| *action).collect());
+    let plan: Option<VecDeque<S>> = plan.map(|plan| plan.into_iter().map(|(state, _)| state).collect());
+    let (best, best_path) = match &plan {
+        Some(plan) => (*goal, plan.clone()),
+        None => (*initial, VecDeque::from([*initial])),
+    };
+
+    SearchResult { plan, plan_actions, statistics, stop_reason, best, best_h: best.h(), best_path, initial_h: initial.h(), solution_cost, max_f: max_f.min(i32::MAX as u32) as i32 }
+}
+
+struct MeetNode<S: State> {
+    g: u32,
+    h: u32,
+    open: bool,
+    //the state it was reached from, and the action between them, taken from the state that is
+    //closer to the initial state
+    parent: Option<(S, S::Action)>,
+}
+
+//a key to order the open states by, the g of the node when it was queued, and the index of the state
+type MeetEntry = Reverse<(u32, u32, usize)>;
+
+/// The states reached by one direction of `mm_search`, with the open ones ordered by priority, f
+/// and g. A queued entry is stale once its state is closed or reached again with a lower g.
+struct Frontier<S: State> {
+    nodes: HashMap<S, MeetNode<S>>,
+    states: Vec<S>,
+    by_priority: BinaryHeap<MeetEntry>,
+    by_f: BinaryHeap<MeetEntry>,
+    by_g: BinaryHeap<MeetEntry>,
+}
+
+impl<S: State> Frontier<S> {
+    fn new(root: S, h: i32) -> Frontier<S> {
+        let mut frontier = Frontier { nodes: HashMap::new(), states: Vec::new(), by_priority: BinaryHeap::new(), by_f: BinaryHeap::new(), by_g: BinaryHeap::new() };
+        frontier.queue(root, 0, h.max(0) as u32, None);
+        frontier
+    }
+
+    fn queue(&mut self, state: S, g: u32, h: u32, parent: Option<(S, S::Action)>) {
+        let f = g.saturating_add(h);
+        let index = self.states.len();
+        self.states.push(state);
+        self.by_priority.push(Reverse((f.max(g.saturating_mul(2)), g, index)));
+        self.by_f.push(Reverse((f, g, index)));
+        self.by_g.push(Reverse((g, g, index)));
+        self.nodes.insert(state, MeetNode { g, h, open: true, parent });
+    }
+
+    /// Queues the state unless it was already reached at no higher cost
+    fn reach(&mut self, state: S, g: u32, h: i32, parent: S, action: S::Action) -> bool {
+        if self.nodes.get(&state).is_some_and(|node| node.g <= g) {
+            return false;
+        }
+
+        self.queue(state, g, h.max(0) as u32, Some((parent, action)));
+        true
     }
 
-    fn state(&self) -> &S {
-        match self {
-            Initial { state, .. } => &state,
-            Intermediate { state, .. } => &state
-        }
+    fn g(&self, state: &S) -> Option<u32> {
+        self.nodes.get(state).map(|node| node.g)
     }
 
-    fn parent(&self) -> Option<&Transition<S>> {
-        match self {
-            Intermediate { parent, .. } => Some(parent.as_ref()),
-            Initial { .. } => None,
+    //drops the stale entries from the top of the heap, and returns the key of the first live one
+    fn min(heap: &mut BinaryHeap<MeetEntry>, nodes: &HashMap<S, MeetNode<S>>, states: &[S]) -> Option<u32> {
+        while let Some(Reverse((key, g, index))) = heap.peek() {
+            let node = &nodes[&states[*index]];
+            if node.open && node.g == *g {
+                return Some(*key);
+            }
+            heap.pop();
         }
+
+        None
     }
 
-    fn h(&self) -> i32 {
-        match self {
-            Initial { h, ..} => *h,
-            Intermediate { h, .. } => *h
-        }
+    fn min_priority(&mut self) -> Option<u32> {
+        Frontier::min(&mut self.by_priority, &self.nodes, &self.states)
     }
 
-    fn g(&self) -> u32 {
-        match self {
-            Intermediate { g, .. } => *g,
-            Initial { .. } => 0,
-        }
+    fn min_f(&mut self) -> u32 {
+        Frontier::min(&mut self.by_f, &self.nodes, &self.states).unwrap_or(u32::MAX)
     }
 
-    fn index(&self) -> u32 {
-        match self {
-            Intermediate { index, .. } => *index,
-            Initial { .. } => 0,
-        }
+    fn min_g(&mut self) -> u32 {
+        Frontier::min(&mut self.by_g, &self.nodes, &self.states).unwrap_or(u32::MAX)
     }
 
-    fn successor(state: Rc<S>, parent: Rc<Transition<S>>, index: u32, compute_heuristic: bool) -> Transition<S> {
-        let h = if compute_heuristic {
-            state.h()
-        } else {
-            parent.h()
-        };
+    /// Closes the open state with the lowest priority, and returns it with its g and f
+    fn pop(&mut self) -> (S, u32, u32) {
+        self.min_priority();
+        let Reverse((_, g, index)) = self.by_priority.pop().expect("Popping from an empty frontier.");
+        let state = self.states[index];
+        let node = self.nodes.get_mut(&state).unwrap();
+        node.open = false;
+        (state, g, g.saturating_add(node.h))
+    }
 
-        Intermediate { state, g: parent.g() + 1, parent, index, h }
+    /// The states from this one back to the root
+    fn path(&self, mut state: S) -> Vec<(S, Option<S::Action>)> {
+        let mut path = vec![(state, None)];
+        while let Some((parent, action)) = self.nodes[&state].parent {
+            path.push((parent, Some(action)));
+            state = parent;
+        }
+        path
     }
 }
 
-impl<S: State> PartialOrd for Transition<S> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+/// Iterative deepening A*: depth first searches that give up on the states whose f = g + h is over a
+/// bound, raised to the lowest f that went over it until the goal is reached. It only keeps the
+/// current path, changing a single state in place, so memory stays linear in the depth of the plan.
+/// The plan is optimal when the heuristic is admissible.
+pub fn ida_star_search<S: MutableState, F: Fn(&S) -> bool>(initial: S, goal: F, limits: Limits) -> PathResult<S> {
+    deepening_search(initial, goal, limits, true)
 }
 
-impl<S: State> Ord for Transition<S> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        let other_f = other.g() as i32 + other.h();
-        let self_f = self.g() as i32 + self.h();
+/// Iterative deepening depth first search: depth limited searches with the limit raised by one
+/// until the goal is reached, ignoring the heuristic. Like breadth first search, the plan has the
+/// fewest actions, but only the current path is kept in memory.
+/// The search does not end on its own when the goal cannot be reached.
+pub fn iddfs_search<S: MutableState, F: Fn(&S) -> bool>(initial: S, goal: F, limits: Limits) -> PathResult<S> {
+    deepening_search(initial, goal, limits, false)
+}
 
-        other_f.partial_cmp(&self_f).unwrap_or_else(|| Equal)
+/// Depth first branch and bound: a single depth first search that keeps the cheapest plan found so
+/// far, and gives up on the states whose f = g + h is not below its cost. Once nothing is left, the
+/// plan is optimal when the heuristic is admissible. Only the current path and the best plan are
+/// kept in memory.
+/// Only plans of fewer than `bound` actions are searched, which also bounds the depth before the
+/// first plan is found, so the plan of a faster search (plus one) makes a good bound. The
+/// successors are tried in increasing order of h, to find a cheap plan early.
+/// When the search stops on a limit, the actions are those of the best plan found so far, if any.
+pub fn dfbnb_search<S: MutableState, F: Fn(&S) -> bool>(initial: S, goal: F, bound: i32, limits: Limits) -> PathResult<S> {
+    let start = Instant::now();
+    let mut search = BranchAndBound { state: initial, goal, limits, start, bound, path: Vec::new(), best: None, statistics: Statistics { queued: 0, ..Statistics::initial() }, stop_reason: None };
+    search.visit(0);
+
+    let mut statistics = search.statistics;
+    statistics.memory = (search.path.capacity() + search.best.as_ref().map_or(0, Vec::capacity)) * size_of::<S::Action>() + size_of::<S>();
+    statistics.duration = start.elapsed();
+
+    //the search ends on the initial state when it goes through every branch
+    let mut state = search.state;
+    for action in search.path.iter().rev() {
+        state.undo(*action);
+    }
+    if let Some(best) = &search.best {
+        statistics.branching_factor = effective_branching_factor(statistics.created as usize, best.len());
+        for action in best {
+            state.apply(*action);
+        }
     }
+
+    let stop_reason = search.stop_reason;
+    PathResult { actions: search.best, statistics, stop_reason, state }
 }
 
-impl<S: State> PartialEq for Transition<S> {
-    fn eq(&self, other: &Self) -> bool {
-        self.state() == other.state()
+struct BranchAndBound<S: MutableState, F: Fn(&S) -> bool> {
+    state: S,
+    goal: F,
+    limits: Limits,
+    start: Instant,
+    //the cost of the best plan so far, which the plans searched have to be below
+    bound: i32,
+    path: Vec<S::Action>,
+    best: Option<Vec<S::Action>>,
+    statistics: Statistics,
+    stop_reason: Option<StopReason>,
+}
+
+impl<S: MutableState, F: Fn(&S) -> bool> BranchAndBound<S, F> {
+    /// Searches below the current state, and returns whether the search should go on
+    fn visit(&mut self, g: i32) -> bool {
+        if g.saturating_add(self.state.h()) >= self.bound {
+            return true;
+        }
+        if (self.goal)(&self.state) {
+            self.bound = g;
+            self.best = Some(self.path.clone());
+            return true;
+        }
+        self.stop_reason = self.limits.stop_reason(&self.statistics, self.start);
+        if self.stop_reason.is_some() {
+            return false;
+        }
+
+        self.statistics.expanded += 1;
+        let mut successors = Vec::new();
+        for action in self.state.actions() {
+            if self.path.last().is_some_and(|previous| self.state.reverses(action, *previous)) {
+                continue;
+            }
+
+            self.statistics.count_created(1);
+            self.state.apply(action);
+            successors.push((self.state.h(), action));
+            self.state.undo(action);
+        }
+        successors.sort_by_key(|(h, _)| *h);
+
+        for (_, action) in successors {
+            self.state.apply(action);
+            self.path.push(action);
+            if !self.visit(g + 1) {
+                //the state and the path are left where the search stopped
+                return false;
+            }
+            self.path.pop();
+            self.state.undo(action);
+        }
+
+        true
     }
 }
 
-pub fn breadth_first_search<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F) -> SearchResult<S> {
-    let mut queue = Fifo::new();
-    search(initial, goal, &mut queue, SearchConfig::blind())
+//depth first searches with an increasing bound on f, or on the depth when it is not informed
+fn deepening_search<S: MutableState, F: Fn(&S) -> bool>(initial: S, goal: F, limits: Limits, informed: bool) -> PathResult<S> {
+    let start = Instant::now();
+    let mut search = PathSearch { state: initial, goal, limits, start, informed, path: Vec::new(), statistics: Statistics { queued: 0, ..Statistics::initial() }, stop_reason: None };
+
+    let mut bound = search.h();
+    let found = loop {
+        match search.visit(0, bound) {
+            Visit::Found => break true,
+            Visit::Stopped => break false,
+            //nothing went over the bound, so the goal cannot be reached
+            Visit::Exceeded(i32::MAX) => break false,
+            Visit::Exceeded(next) => bound = next,
+        }
+    };
+
+    let mut statistics = search.statistics;
+    statistics.memory = search.path.capacity() * size_of::<S::Action>() + size_of::<S>();
+    statistics.duration = start.elapsed();
+    let stop_reason = search.stop_reason;
+    if found {
+        statistics.branching_factor = effective_branching_factor(statistics.created as usize, search.path.len());
+    }
+
+    PathResult { actions: if found { Some(search.path) } else { None }, statistics, stop_reason, state: search.state }
 }
 
-pub fn ehc_search<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F) -> SearchResult<S> {
-    let mut queue = Fifo::new();
-    search(initial, goal, &mut queue, SearchConfig::ehc())
+enum Visit {
+    Found,
+    /// The lowest f over the bound among the states reached
+    Exceeded(i32),
+    Stopped,
 }
 
-pub fn ehc_steepest_search<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F) -> SearchResult<S> {
-    let mut queue = Fifo::new();
-    search(initial, goal, &mut queue, SearchConfig::ehc_steepest_ascent())
+struct PathSearch<S: MutableState, F: Fn(&S) -> bool> {
+    state: S,
+    goal: F,
+    limits: Limits,
+    start: Instant,
+    //whether the bound is on f rather than on the depth
+    informed: bool,
+    path: Vec<S::Action>,
+    statistics: Statistics,
+    stop_reason: Option<StopReason>,
 }
 
-pub fn greedy_best_first_search<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F) -> SearchResult<S> {
+impl<S: MutableState, F: Fn(&S) -> bool> PathSearch<S, F> {
+    fn h(&self) -> i32 {
+        if self.informed { self.state.h() } else { 0 }
+    }
 
-    //greedy best first search only considers the heuristic value (h)
-    let mut queue = PriorityCmp::new(|s1: &Transition<S>, s2: &Transition<S>| {
-        //reverse comparison to get min heap
-        s2.h().partial_cmp(&s1.h())
-            .unwrap_or_else(|| Equal)
-            .then_with(|| s2.index().cmp(&s1.index()))
-    });
+    fn visit(&mut self, g: i32, bound: i32) -> Visit {
+        let f = g.saturating_add(self.h());
+        if f > bound {
+            return Visit::Exceeded(f);
+        }
+        if (self.goal)(&self.state) {
+            return Visit::Found;
+        }
+        self.stop_reason = self.limits.stop_reason(&self.statistics, self.start);
+        if self.stop_reason.is_some() {
+            return Visit::Stopped;
+        }
+
+        self.statistics.expanded += 1;
+        let mut next_bound = i32::MAX;
+        for action in self.state.actions() {
+            if self.path.last().is_some_and(|previous| self.state.reverses(action, *previous)) {
+                continue;
+            }
+
+            self.statistics.count_created(1);
+            self.state.apply(action);
+            self.path.push(action);
+            match self.visit(g + 1, bound) {
+                //the state and the path are left at the goal, or where the search stopped
+                Visit::Found => return Visit::Found,
+                Visit::Stopped => return Visit::Stopped,
+                Visit::Exceeded(f) => next_bound = next_bound.min(f),
+            }
+            self.path.pop();
+            self.state.undo(action);
+        }
+
+        Visit::Exceeded(next_bound)
+    }
+}
 
-    search(initial, goal, &mut queue, SearchConfig::default())
+fn greedy_order<S: State>(s1: &Transition<S>, s2: &Transition<S>) -> Ordering {
+    //reverse comparison to get min heap
+    s2.h().partial_cmp(&s1.h())
+        .unwrap_or_else(|| Equal)
+        .then_with(|| s2.index().cmp(&s1.index()))
 }
 
-pub fn a_star_search<S: State, F: Fn(&S) -> bool>(initial: &S, goal: F) -> SearchResult<S> {
-    let mut queue = PriorityCmp::new(|s1: &Transition<S>, s2: &Transition<S>| {
-        let s1_f = a_star_eval(s1);
-        let s2_f = a_star_eval(s2);
-        //reverse comparison to get min heap
-        s2_f.partial_cmp(&s1_f)
-            .unwrap_or_else(|| Equal)
-            .then_with(|| s2.h().partial_cmp(&s1.h()).unwrap_or_else(|| Equal))
-            .then_with(|| s2.index().cmp(&s1.index()))
-    });
+fn a_star_order<S: State>(s1: &Transition<S>, s2: &Transition<S>) -> Ordering {
+    let s1_f = a_star_eval(s1);
+    let s2_f = a_star_eval(s2);
+    //reverse comparison to get min heap
+    s2_f.partial_cmp(&s1_f)
+        .unwrap_or_else(|| Equal)
+        .then_with(|| s2.h().partial_cmp(&s1.h()).unwrap_or_else(|| Equal))
+        .then_with(|| s2.index().cmp(&s1.index()))
+}
 
-    search(initial, goal, &mut queue, SearchConfig::default())
+/// Runs the search with the queue, and keeps the storage of the queue in the buffers
+fn search_in<S, F, Q>(initial: &S, goal: F, mut queue: Q, config: SearchConfig, buffers: &mut SearchBuffers<S>) -> SearchResult<S>
+    where S: State,
+          F: Fn(&S) -> bool,
+          Q: Queue<Transition<S>>
+{
+    let result = search(initial, goal, &mut queue, config, &mut buffers.seen, &mut SearchHooks::none());
+    buffers.recycle(queue);
+    result
 }
 
 fn a_star_eval<S: State>(state_transition: &Transition<S>) -> i32 {
@@ -192,61 +1317,103 @@ fn a_star_eval<S: State>(state_transition: &Transition<S>) -> i32 {
     }
 }
 
-fn search<S, F, Q>(initial: &S, goal: F, queue: &mut Q, config: SearchConfig) -> SearchResult<S>
+fn search<S, F, Q>(initial: &S, goal: F, queue: &mut Q, config: SearchConfig, seen: &mut HashMap<Rc<S>, Rc<Transition<S>>>, hooks: &mut SearchHooks<S>) -> SearchResult<S>
     where S: State,
           F: Fn(&S) -> bool,
           Q: Queue<Transition<S>>
 {
-    let mut seen = HashMap::new();
+
+    //the buffers may still hold the seen states of the previous search
+    seen.clear();
 
     // the initial state
-    let mut statistics = Statistics { created: 1, queued: 1, expanded: 0, duration: Duration::new(0, 0) };
+    let mut statistics = Statistics::initial();
     let start = Instant::now();
+    let mut next_report = start + config.limits.progress.as_ref().map_or(Duration::ZERO, |progress| progress.interval);
     let mut index: u32 = 0;
 
     let initial_state = Rc::new(*initial);
     let initial_transition = Rc::new(Transition::new(Rc::clone(&initial_state),  config.compute_heuristic));
-    println!("Starting search with Initial h value {}", initial_transition.h());
 
     let mut best_h = initial_transition.h();
-    if config.compute_heuristic {
-        print!("Current best H: {:?} ", best_h);
-    }
+    let mut best = Rc::clone(&initial_transition);
+    let initial_h = initial_transition.h();
+    let mut max_f = initial_h;
 
     seen.insert(initial_state, Rc::clone(&initial_transition));
     queue.enqueue(initial_transition);
 
     while let Some(transition) = queue.dequeue() {
+        max_f = max_f.max((transition.g() as i32).saturating_add(transition.h()));
+
         if goal(&transition.state()) {
             let plan = extract_plan(&transition);
+            statistics.branching_factor = effective_branching_factor(statistics.created as usize, plan.len() - 1);
             statistics.duration = start.elapsed();
-            println!("\nFound plan after seeing {} unique states", seen.len());
-            return SearchResult { plan: Some(plan), statistics };
+            statistics.set_seen(seen.len());
+            statistics.memory = seen_memory(seen);
+            return SearchResult { plan: Some(plan.clone()), plan_actions: Some(extract_actions(&transition)), statistics, stop_reason: None, best: *transition.state(), best_h: transition.h(), best_path: plan, initial_h, solution_cost: Some(transition.g()), max_f };
+        } else if let Some(stop_reason) = config.limits.stop_reason(&statistics, start) {
+            statistics.duration = start.elapsed();
+            statistics.set_seen(seen.len());
+            statistics.memory = seen_memory(seen);
+            //the queued states hold on to the paths that led to them, so let go of them right away
+            queue.clear();
+            return SearchResult { plan: None, plan_actions: None, statistics, stop_reason: Some(stop_reason), best: *best.state(), best_h, best_path: extract_plan(&best), initial_h, solution_cost: None, max_f };
         } else {
+            //without a clock, the embedded profile has nothing to report progress by
+            if let Some(progress) = config.limits.progress.as_ref().filter(|_| !cfg!(feature = "embedded")) {
+                let now = Instant::now();
+                if now >= next_report {
+                    (progress.report)(&Progress { expanded: statistics.expanded, created: statistics.created, best_h, open: queue.len(), elapsed: now - start });
+                    next_report = now + progress.interval;
+                }
+            }
+
             statistics.expanded += 1;
             let mut skip_siblings = false;
 
-            let mut successors: Vec<S> = transition.state().successors()
-                .into_iter()
-                .filter(|successor| !seen_and_better(&seen, &successor, transition.g() + 1))
-                .collect();
-
-            if config.compute_heuristic && config.best_first_successors {
+            //the actions the filter vetoes are left out before their successors are made
+            let state = *transition.state();
+            let (filter, vetoed) = (&hooks.filter, Cell::new(0));
+            let keep = |action| {
+                let kept = filter.as_ref().is_none_or(|keep| keep(&state, action));
+                if !kept {
+                    vetoed.set(vetoed.get() + 1);
+                }
+                kept
+            };
+
+            //the successors are only sorted up front when the search has to see them all first
+            let sort = config.compute_heuristic && config.best_first_successors;
+            let mut sorted = Vec::new();
+            let mut lazy = None;
+            if sort {
+                sorted = state.successors_with_data_kept(transition.data(), keep).collect();
                 //todo: we are computing this again in the Transition twice, can we avoid it?
-                successors.sort_by(|a, b| a.h().partial_cmp(&b.h()).unwrap());
+                sorted.sort_by(|(_, a), (_, b)| a.h().partial_cmp(&b.h()).unwrap());
+            } else {
+                lazy = Some(state.successors_with_data_kept(transition.data(), keep));
             }
 
-            for successor_state in successors {
-                statistics.created += 1;
+            let (mut generated, mut skipped) = (0, 0);
+            for (action, successor_state) in sorted.into_iter().chain(lazy.into_iter().flatten()) {
+                let cost = state.cost(action);
+                if seen_and_better(seen, &successor_state, transition.g() + cost) {
+                    skipped += 1;
+                    continue;
+                }
+
+                statistics.count_created(1);
                 index += 1;
                 let successor_state_rc = Rc::new(successor_state);
-                let succ_transition = Rc::new(Transition::successor(Rc::clone(&successor_state_rc), Rc::clone(&transition), index, config.compute_heuristic));
+                let succ_transition = Rc::new(Transition::successor(Rc::clone(&successor_state_rc), action, Rc::clone(&transition), cost, index, config.compute_heuristic));
                 seen.insert(successor_state_rc, Rc::clone(&succ_transition));
 
                 let current_h = succ_transition.h();
                 if current_h < best_h {
-                    print!("{:?} ", current_h);
                     best_h = current_h;
+                    best = Rc::clone(&succ_transition);
 
                     if config.ehc {
                         queue.clear();
@@ -255,21 +1422,39 @@ fn search<S, F, Q>(initial: &S, goal: F, queue: &mut Q, config: SearchConfig) ->
                 }
 
                 queue.enqueue(succ_transition);
-                statistics.queued += 1;
+                statistics.count_queued(1);
+                generated += 1;
 
                 if skip_siblings {
                     break;
                 }
             }
+
+            statistics.count_vetoed(vetoed.get());
+            if let Some(on_expand) = &mut hooks.on_expand {
+                on_expand(&Expanded { state, g: transition.g(), h: transition.h(), generated, skipped, open: queue.len() });
+            }
         }
     }
 
     statistics.duration = start.elapsed();
-    println!("No plan found. At time {:?} after seeing {} unique states", Instant::now(), seen.len());
-    SearchResult { plan: None, statistics }
+    statistics.set_seen(seen.len());
+    statistics.memory = seen_memory(seen);
+    SearchResult { plan: None, plan_actions: None, statistics, stop_reason: None, best: *best.state(), best_h, best_path: extract_plan(&best), initial_h, solution_cost: None, max_f }
 }
 
 
+fn seen_memory<S: State>(seen: &HashMap<Rc<S>, Rc<Transition<S>>>) -> usize {
+    if cfg!(feature = "embedded") {
+        return 0;
+    }
+
+    //the two pointers in the map, and the state and transition each allocated with two counters
+    let per_state = size_of::<Rc<S>>() + size_of::<Rc<Transition<S>>>()
+        + size_of::<S>() + size_of::<Transition<S>>() + 4 * size_of::<usize>();
+    seen.len() * per_state
+}
+
 fn seen_and_better<S: State>(seen: &HashMap<Rc<S>, Rc<Transition<S>>>, state: &S, g: u32) -> bool {
     match seen.get(state) {
         Some(seen_transition) if seen_transition.g() <= g => true,
@@ -291,6 +1476,19 @@ fn extract_plan<S: State>(goal_transition: &Transition<S>) -> VecDeque<S> {
     plan
 }
 
+fn extract_actions<S: State>(goal_transition: &Transition<S>) -> Vec<S::Action> {
+    let mut actions = Vec::new();
+
+    let mut current = goal_transition;
+    while let (Some(action), Some(previous)) = (current.action(), current.parent()) {
+        actions.push(action);
+        current = previous;
+    }
+
+    actions.reverse();
+    actions
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -303,8 +1501,12 @@ mod tests {
     }
 
     impl State for TestState {
-        fn successors(&self) -> Vec<Self> {
-            vec![TestState { value: self.value + 1 }, TestState { value: self.value + 2 }, TestState { value: self.value + 3 }]
+        //the amount added to the value
+        type Action = i32;
+        type Data = ();
+
+        fn successors_with_actions(&self) -> Vec<(i32, Self)> {
+            (1..=3).map(|step| (step, TestState { value: self.value + step })).collect()
         }
 
         fn h(&self) -> i32 {
@@ -316,6 +1518,42 @@ mod tests {
         }
     }
 
+    #[derive(Hash, Debug, Copy, Clone, Eq, PartialEq)]
+    struct DepthState {
+        value: i32,
+    }
+
+    impl State for DepthState {
+        type Action = i32;
+        //the number of actions taken to reach the state
+        type Data = u32;
+
+        fn successors_with_actions(&self) -> Vec<(i32, Self)> {
+            (1..=3).map(|step| (step, DepthState { value: self.value + step })).collect()
+        }
+
+        fn h(&self) -> i32 {
+            (GOAL - self.value).max(0)
+        }
+
+        fn data(&self, parent: Option<&u32>) -> u32 {
+            parent.map_or(0, |depth| depth + 1)
+        }
+
+        //the states are not expanded past two actions
+        fn successors_with_data(&self, depth: &u32) -> impl Iterator<Item = (i32, Self)> {
+            let depth = *depth;
+            self.successors_with_actions().into_iter().filter(move |_| depth < 2)
+        }
+    }
+
+    #[test]
+    fn test_data_handed_back_on_expansion() {
+        let initial = DepthState { value: 0 };
+        assert_eq!(breadth_first_search(&initial, |state| state.value == 6).plan.map(|plan| plan.len()), Some(3));
+        assert!(breadth_first_search(&initial, |state| state.value == 7).plan.is_none());
+        assert!(a_star_search(&initial, |state| state.value == 7).plan.is_none());
+    }
 
     #[test]
     fn test_breadth_first_search() {
@@ -335,6 +1573,100 @@ mod tests {
         assert_eq!(goal.value, GOAL);
     }
 
+    #[test]
+    fn test_breadth_first_layers() {
+        let initial = TestState { value: 0 };
+        let mut layers = Vec::new();
+        let result = breadth_first_layers(&initial, |state| state.value == GOAL, true, Limits::none(), |layer| {
+            layers.push((layer.depth, layer.size, layer.states.unwrap().iter().map(|state| state.value).collect::<Vec<_>>()));
+        });
+
+        //the goal is reached while making the third layer, which is not reported
+        assert_eq!(layers, vec![(0, 1, vec![0]), (1, 3, vec![1, 2, 3])]);
+        assert_eq!(result.plan.unwrap().len(), breadth_first_search(&initial, |state| state.value == GOAL).plan.unwrap().len());
+        assert_eq!(result.solution_cost, Some(2));
+
+        let limited = breadth_first_layers(&initial, |state| state.value == GOAL, false, Limits { max_expanded: Some(2), ..Limits::none() }, |layer| assert!(layer.states.is_none()));
+        assert!(limited.limit_reached());
+        assert!(limited.plan.is_none());
+    }
+
+    #[derive(Hash, Debug, Copy, Clone, Eq, PartialEq)]
+    struct CostState {
+        value: i32,
+    }
+
+    impl State for CostState {
+        type Action = i32;
+        type Data = ();
+
+        fn successors_with_actions(&self) -> Vec<(i32, Self)> {
+            (1..=3).map(|step| (step, CostState { value: self.value + step })).collect()
+        }
+
+        fn h(&self) -> i32 {
+            0
+        }
+
+        //the longer steps cost more than the shorter ones put together
+        fn cost(&self, step: i32) -> u32 {
+            if step == 1 { 1 } else { 5 }
+        }
+
+        fn predecessors_with_actions(&self) -> Vec<(i32, Self)> {
+            (1..=3).map(|step| (step, CostState { value: self.value - step })).collect()
+        }
+    }
+
+    #[test]
+    fn test_uniform_cost_search() {
+        let initial = CostState { value: 0 };
+        let result = uniform_cost_search(&initial, |state| state.value == 4);
+        assert_eq!(result.plan_actions, Some(vec![1, 1, 1, 1]));
+        assert_eq!(result.solution_cost, Some(4));
+
+        //breadth first search takes the fewest actions, which cost more
+        let fewest = breadth_first_search(&initial, |state| state.value == 4);
+        assert_eq!(fewest.plan.unwrap().len(), 3);
+        assert_eq!(fewest.solution_cost, Some(6));
+    }
+
+    #[test]
+    fn test_mm_search() {
+        let (initial, goal) = (CostState { value: 0 }, CostState { value: 6 });
+        let result = mm_search(&initial, &goal, |_| 0, Limits::none());
+        assert_eq!(result.plan_actions, Some(vec![1; 6]));
+        assert_eq!(result.plan.unwrap().iter().map(|state| state.value).collect::<Vec<_>>(), (0..=6).collect::<Vec<_>>());
+        assert_eq!(result.solution_cost, Some(6));
+
+        //neither side goes past the middle of the plan
+        assert!(result.statistics.expanded < uniform_cost_search(&initial, |state| *state == goal).statistics.expanded);
+        //the embedded profile only counts the expansions
+        if cfg!(feature = "embedded") {
+            assert_eq!((result.statistics.created, result.statistics.queued), (0, 0));
+        } else {
+            assert!(result.statistics.created > 2 && result.statistics.queued > 2);
+        }
+
+        let same = mm_search(&initial, &initial, |_| 0, Limits::none());
+        assert_eq!(same.plan.unwrap().len(), 1);
+        assert_eq!(same.solution_cost, Some(0));
+
+        let limited = mm_search(&initial, &CostState { value: 60 }, |_| 0, Limits { max_expanded: Some(10), ..Limits::none() });
+        assert!(limited.limit_reached());
+        assert!(limited.plan.is_none());
+
+        //stopped after the searches met, with a plan that is not proven optimal
+        let full = mm_search(&initial, &CostState { value: 20 }, |_| 0, Limits::none());
+        let stopped: Vec<_> = (1..full.statistics.expanded)
+            .map(|max| mm_search(&initial, &CostState { value: 20 }, |_| 0, Limits { max_expanded: Some(max), ..Limits::none() }))
+            .filter(|result| result.plan.is_some())
+            .collect();
+        assert!(!stopped.is_empty());
+        assert!(stopped.iter().all(|result| result.stop_reason == Some(StopReason::MaxExpanded) && result.solution_cost >= full.solution_cost));
+        assert!(stopped.iter().any(|result| result.solution_cost > full.solution_cost));
+    }
+
     #[test]
     fn test_ehc_search() {
         let initial = TestState { value: 0 };
@@ -402,4 +1734,261 @@ mod tests {
         let goal = plan.get(plan.len() - 1).unwrap();
         assert_eq!(goal.value, GOAL);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_expansion_limit() {
+        let initial = TestState { value: 0 };
+        let limits = Limits { max_expanded: Some(10), ..Limits::none() };
+        let result = a_star_search_limited(&initial, |state| state.value == -1, limits);
+
+        assert!(result.plan.is_none());
+        assert!(result.limit_reached());
+        assert_eq!(result.stop_reason, Some(StopReason::MaxExpanded));
+        assert_eq!(result.statistics.expanded, 10);
+    }
+
+    #[test]
+    #[cfg(not(feature = "embedded"))]
+    fn test_timeout() {
+        let initial = TestState { value: 0 };
+        let limits = Limits { max_duration: Some(Duration::ZERO), ..Limits::none() };
+        let mut buffers = SearchBuffers::new();
+        let result = a_star_search_buffered(&initial, |state| state.value == -1, limits.clone(), &mut buffers);
+
+        assert_eq!(result.stop_reason, Some(StopReason::Timeout));
+        assert_eq!(result.statistics.seen, 1);
+        assert!(result.statistics.memory > 0);
+        //the buffers let go of the states of the search
+        assert!(buffers.seen.is_empty() && buffers.queue.is_empty());
+        assert!(buffers.capacity().seen > 0);
+        buffers.release();
+        assert_eq!(buffers.capacity(), Capacity { seen: 0, queue: 0 });
+
+        let path = ida_star_search(Counter { value: 0 }, |counter| counter.value == 3, limits);
+        assert_eq!((path.limit_reached(), path.stop_reason), (true, Some(StopReason::Timeout)));
+        assert_eq!(path.stop_reason.unwrap().to_string(), "timed out");
+    }
+
+    #[test]
+    #[cfg(feature = "embedded")]
+    fn test_timeout_without_clock() {
+        //the time limit has no effect, so the search returns once it reaches another limit or the goal
+        let limits = Limits { max_duration: Some(Duration::ZERO), max_expanded: Some(10), ..Limits::none() };
+        let result = a_star_search_limited(&TestState { value: 0 }, |state| state.value == -1, limits.clone());
+        assert_eq!(result.stop_reason, Some(StopReason::MaxExpanded));
+
+        let path = ida_star_search(Counter { value: 0 }, |counter| counter.value == 3, limits);
+        assert_eq!(path.stop_reason, None);
+        assert_eq!(path.actions, Some(vec![1, 1, 1]));
+    }
+
+    /// A number counting up towards 3
+    #[derive(Debug)]
+    struct Counter {
+        value: i32,
+    }
+
+    impl MutableState for Counter {
+        type Action = i32;
+
+        fn actions(&self) -> Vec<i32> {
+            vec![1]
+        }
+
+        fn apply(&mut self, action: i32) {
+            self.value += action;
+        }
+
+        fn undo(&mut self, action: i32) {
+            self.value -= action;
+        }
+
+        fn h(&self) -> i32 {
+            (3 - self.value).max(0)
+        }
+    }
+
+    #[test]
+    fn test_deepening_stop_reason() {
+        let result = iddfs_search(Counter { value: 0 }, |counter| counter.value == 3, Limits::none());
+        assert_eq!(result.actions, Some(vec![1, 1, 1]));
+        assert_eq!(result.stop_reason, None);
+
+        let limited = iddfs_search(Counter { value: 0 }, |counter| counter.value == 3, Limits { max_expanded: Some(2), ..Limits::none() });
+        assert_eq!(limited.stop_reason, Some(StopReason::MaxExpanded));
+        assert_eq!(limited.statistics.expanded, 2);
+    }
+
+    #[test]
+    fn test_dfbnb() {
+        let result = dfbnb_search(Counter { value: 0 }, |counter| counter.value == 3, 10, Limits::none());
+        assert_eq!(result.actions, Some(vec![1, 1, 1]));
+        assert_eq!(result.state.value, 3);
+        assert!(!result.limit_reached());
+
+        //no plan below the bound
+        let result = dfbnb_search(Counter { value: 0 }, |counter| counter.value == 3, 3, Limits::none());
+        assert_eq!(result.actions, None);
+        assert_eq!(result.state.value, 0);
+
+        let limited = dfbnb_search(Counter { value: 0 }, |counter| counter.value == 3, 10, Limits { max_expanded: Some(2), ..Limits::none() });
+        assert_eq!(limited.stop_reason, Some(StopReason::MaxExpanded));
+        assert_eq!(limited.actions, None);
+    }
+
+    #[test]
+    fn test_cancelled() {
+        let initial = TestState { value: 0 };
+        let cancel = CancellationToken::new();
+        let limits = Limits { cancel: Some(cancel.clone()), ..Limits::none() };
+
+        cancel.cancel();
+        let result = greedy_best_first_search_limited(&initial, |state| state.value == -1, limits);
+
+        assert!(result.limit_reached());
+        assert_eq!(result.stop_reason, Some(StopReason::Cancelled));
+        assert_eq!(result.statistics.expanded, 0);
+        assert_eq!(result.best, initial);
+    }
+
+    #[test]
+    fn test_progress() {
+        let initial = TestState { value: 0 };
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+        let progress = ProgressHook::new(Duration::ZERO, move |progress| recorded.lock().unwrap().push(progress.clone()));
+        let limits = Limits { max_expanded: Some(10), progress: Some(progress), ..Limits::none() };
+        a_star_search_limited(&initial, |state| state.value == -1, limits);
+
+        let events = events.lock().unwrap();
+        if cfg!(feature = "embedded") {
+            //without a clock there is nothing to report progress by
+            assert!(events.is_empty());
+        } else {
+            //without an interval, every expansion is reported
+            assert_eq!(events.len(), 10);
+            assert_eq!(events.iter().map(|progress| progress.expanded).collect::<Vec<i32>>(), (0..10).collect::<Vec<i32>>());
+            assert_eq!(events[0].open, 0);
+            assert!(events[1].open > 0);
+        }
+
+        let json = Progress { expanded: 3, created: 7, best_h: 2, open: 4, elapsed: Duration::from_millis(1500) }.to_json();
+        assert_eq!(json, "{\"event\":\"progress\",\"expanded\":3,\"created\":7,\"best_h\":2,\"open\":4,\"elapsed_ms\":1500}");
+    }
+
+    #[test]
+    fn test_best_state() {
+        let initial = TestState { value: 0 };
+        let limits = Limits { max_expanded: Some(1), ..Limits::none() };
+        let result = a_star_search_limited(&initial, |state| state.value == -1, limits);
+
+        assert_eq!(result.best, TestState { value: 3 });
+        assert_eq!(result.best_h, 2);
+        assert_eq!(result.best_path, vec![initial, TestState { value: 3 }]);
+    }
+
+    #[test]
+    fn test_solution_cost() {
+        let initial = TestState { value: 0 };
+        let result = a_star_search(&initial, |state| state.value == 5);
+
+        assert_eq!(result.initial_h, 5);
+        assert_eq!(result.solution_cost, Some(2));
+        assert_eq!(result.max_f, 5);
+
+        //the actions add up to the distance covered by the plan
+        let actions = result.plan_actions.unwrap();
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions.iter().sum::<i32>(), GOAL);
+
+        let limits = Limits { max_expanded: Some(1), ..Limits::none() };
+        let result = breadth_first_search_limited(&initial, |state| state.value == -1, limits);
+        assert_eq!(result.initial_h, 0);
+        assert_eq!(result.solution_cost, None);
+    }
+
+    #[test]
+    fn test_limit_not_reached() {
+        let initial = TestState { value: 0 };
+        let limits = Limits { max_expanded: Some(10), ..Limits::none() };
+        let result = breadth_first_search_limited(&initial, |state| state.value == 5, limits);
+
+        assert!(result.plan.is_some());
+        assert!(!result.limit_reached());
+        assert_eq!(result.stop_reason, None);
+    }
+
+    #[test]
+    fn test_buffers_reused() {
+        let initial = TestState { value: 0 };
+        let mut buffers = SearchBuffers::new();
+        assert_eq!(buffers.capacity(), Capacity { seen: 0, queue: 0 });
+
+        let first = a_star_search_buffered(&initial, |state| state.value == 5, Limits::none(), &mut buffers);
+        let capacity = buffers.capacity();
+        assert!(capacity.seen > 0 && capacity.queue > 0);
+
+        //the second search starts empty, but within the storage of the first
+        let second = a_star_search_buffered(&initial, |state| state.value == 5, Limits::none(), &mut buffers);
+        assert_eq!(second.plan, first.plan);
+        assert_eq!(second.statistics.expanded, first.statistics.expanded);
+        assert_eq!(second.statistics.seen, first.statistics.seen);
+        assert_eq!(buffers.capacity(), capacity);
+
+        let blind = breadth_first_search_buffered(&initial, |state| state.value == 5, Limits::none(), &mut buffers);
+        assert_eq!(blind.plan, breadth_first_search(&initial, |state| state.value == 5).plan);
+    }
+
+    #[test]
+    fn test_closed() {
+        let initial = TestState { value: 0 };
+        let mut buffers = SearchBuffers::new();
+        a_star_search_buffered(&initial, |state| state.value == 5, Limits::none(), &mut buffers);
+        assert_eq!(buffers.closed().count(), 0);
+
+        let mut buffers = SearchBuffers::retaining();
+        let result = a_star_search_buffered(&initial, |state| state.value == 5, Limits::none(), &mut buffers);
+        let closed: Vec<ClosedEntry<TestState>> = buffers.closed().collect();
+        //the embedded profile does not count the seen states
+        if !cfg!(feature = "embedded") {
+            assert_eq!(closed.len(), result.statistics.seen);
+        }
+        assert!(closed.contains(&ClosedEntry { state: initial, g: 0, h: 5, id: 0, parent: None }));
+        let goal = closed.iter().find(|entry| entry.state.value == 5).unwrap();
+        assert_eq!(goal.g, result.solution_cost.unwrap());
+        assert!(closed.iter().any(|entry| Some(entry.id) == goal.parent));
+
+        //the next search starts from no seen states
+        let second = breadth_first_search_buffered(&initial, |state| state.value == 1, Limits::none(), &mut buffers);
+        assert!(buffers.closed().count() < closed.len());
+        if !cfg!(feature = "embedded") {
+            assert_eq!(buffers.closed().count(), second.statistics.seen);
+        }
+    }
+
+    #[test]
+    fn test_effective_branching_factor() {
+        if cfg!(feature = "embedded") {
+            assert_eq!(effective_branching_factor(53, 5), None);
+            return;
+        }
+
+        //the textbook example, 52 nodes generated (besides the root) for a solution at depth 5
+        let b = effective_branching_factor(53, 5).unwrap();
+        assert!((b - 1.92).abs() < 0.01);
+
+        assert!((effective_branching_factor(1 + 2 + 4 + 8, 3).unwrap() - 2.0).abs() < 1e-9);
+        assert!((effective_branching_factor(4, 3).unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(effective_branching_factor(10, 0), None);
+    }
+
+    #[test]
+    fn test_statistics_branching_factor() {
+        let initial = TestState { value: 0 };
+        let result = breadth_first_search(&initial, |state| state.value == 5);
+
+        let depth = result.plan.unwrap().len() - 1;
+        let expected = effective_branching_factor(result.statistics.created as usize, depth);
+        assert_eq!(result.statistics.branching_factor, expected);
+    }
+}
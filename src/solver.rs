@@ -0,0 +1,816 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::board::{Board, MoveOrder, GOAL};
+use crate::cache::PlanCache;
+use crate::goal::GoalSet;
+use crate::heuristic::HeuristicKind;
+use crate::plan::Plan;
+use crate::queue::QueueKind;
+use crate::search::{Capacity, ClosedEntry, Limits, SearchBuffers, Statistics, StopReason};
+use crate::trace::Trace;
+use crate::BoardState;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Algorithm {
+    BreadthFirst,
+    Ehc,
+    EhcSteepest,
+    GreedyBestFirst,
+    AStar,
+}
+
+impl Algorithm {
+    pub fn all() -> [Algorithm; 5] {
+        [Algorithm::BreadthFirst, Algorithm::Ehc, Algorithm::EhcSteepest, Algorithm::GreedyBestFirst, Algorithm::AStar]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::BreadthFirst => "bfs",
+            Algorithm::Ehc => "ehc",
+            Algorithm::EhcSteepest => "ehc-steepest",
+            Algorithm::GreedyBestFirst => "greedy",
+            Algorithm::AStar => "astar",
+        }
+    }
+
+    /// Whether the algorithm makes use of a heuristic at all
+    pub fn is_informed(&self) -> bool {
+        *self != Algorithm::BreadthFirst
+    }
+
+    /// Whether the plans it finds have the fewest moves, the heuristics of `HeuristicKind` being
+    /// admissible
+    pub fn is_optimal(&self) -> bool {
+        matches!(self, Algorithm::BreadthFirst | Algorithm::AStar)
+    }
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = UnknownName;
+
+    fn from_str(name: &str) -> Result<Algorithm, UnknownName> {
+        Algorithm::all().iter()
+            .find(|algorithm| algorithm.name() == name)
+            .copied()
+            .ok_or_else(|| UnknownName(name.to_string()))
+    }
+}
+
+/// The name does not match any algorithm or heuristic
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnknownName(pub String);
+
+impl Display for UnknownName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown name: {}", self.0)
+    }
+}
+
+impl Error for UnknownName {}
+
+/// The outcome of solving a single 8-puzzle board with a `Solver`, whether a plan was found or not
+#[derive(Debug, Clone)]
+pub struct SolveResult {
+    pub board: Board,
+    pub plan: Option<Plan>,
+    pub statistics: Statistics,
+    /// The solver limit the search gave up on, if it did
+    pub stop_reason: Option<StopReason>,
+    /// The board with the lowest heuristic value found, which is the goal if a plan was found
+    pub best: Board,
+    pub best_h: i32,
+}
+
+impl SolveResult {
+    /// Whether the search gave up because it reached one of the solver limits
+    pub fn limit_reached(&self) -> bool {
+        self.stop_reason.is_some()
+    }
+
+    /// The plan from the goal back to the board that was solved, for restoring the scramble
+    pub fn undo_plan(&self) -> Option<Plan> {
+        self.plan.as_ref().map(|plan| plan.reverse())
+    }
+}
+
+/// Solves boards with a chosen search algorithm, and repairs plans when the board drifts from them.
+/// Only the 3×3 boards of the 8-puzzle, which the heuristics it can be given are built for: boards
+/// of other sizes are solved by the generic functions of the crate, such as `a_star_search` or
+/// `solve_auto`.
+#[derive(Clone)]
+pub struct Solver {
+    algorithm: Algorithm,
+    heuristic: HeuristicKind,
+    limits: Limits,
+    move_order: MoveOrder,
+    queue: QueueKind,
+    symmetry: bool,
+    goal: Board,
+    //the heuristic towards the goal, owned by the solver so that the states can borrow it for as
+    //long as the solver, and with them the buffers reused across its searches
+    estimate: Arc<dyn Fn(&Board) -> i32 + Send + Sync>,
+    cache: Option<Arc<Mutex<PlanCache>>>,
+    reuse_suffixes: bool,
+}
+
+impl Solver {
+    pub fn new(algorithm: Algorithm) -> Solver {
+        let heuristic = HeuristicKind::Manhattan;
+        Solver { algorithm, heuristic, limits: Limits::none(), move_order: MoveOrder::Default, queue: QueueKind::BinaryHeap, symmetry: false, goal: GOAL, estimate: estimate(heuristic, GOAL), cache: None, reuse_suffixes: false }
+    }
+
+    /// Guides the search with another heuristic instead of the manhattan distance.
+    /// Only applies to `solve`, `run`, `solve_all` and `replan`.
+    pub fn with_heuristic(self, heuristic: HeuristicKind) -> Solver {
+        Solver { heuristic, estimate: estimate(heuristic, self.goal), ..self }
+    }
+
+    /// Bounds every search of this solver, so that a hard instance cannot hold up the others
+    pub fn with_limits(self, limits: Limits) -> Solver {
+        Solver { limits, ..self }
+    }
+
+    /// Generates the successors of every board in the given order, which decides how ties are broken.
+    /// Only applies to `solve`, `run`, `solve_all` and `replan`.
+    pub fn with_move_order(self, move_order: MoveOrder) -> Solver {
+        Solver { move_order, ..self }
+    }
+
+    /// Orders the states of A* and greedy best first search with another kind of priority queue,
+    /// which changes how fast they run but not the plans they find.
+    /// Only applies to `solve`, `run`, `solve_all` and `replan`.
+    pub fn with_queue(self, queue: QueueKind) -> Solver {
+        Solver { queue, ..self }
+    }
+
+    /// Treats every board and its mirror image along the diagonal as the same board among the seen
+    /// states, which roughly halves the boards the searches go through. The goal needs its blank on
+    /// the diagonal, as `GOAL` and `BLANK_FIRST_GOAL` have, otherwise it has no effect.
+    /// Only applies to `solve`, `run`, `solve_all` and `replan`.
+    pub fn with_symmetry(self, symmetry: bool) -> Solver {
+        Solver { symmetry, ..self }
+    }
+
+    /// Solves towards another goal than `GOAL`, such as `BLANK_FIRST_GOAL`.
+    /// Only applies to `solve`, `run`, `solve_all` and `replan`.
+    pub fn with_goal(self, goal: Board) -> Solver {
+        Solver { goal, estimate: estimate(self.heuristic, goal), ..self }
+    }
+
+    /// Looks up every board in the cache before searching, and keeps the plans found in it, so that
+    /// boards solved before (or their mirror images) are answered without searching. The cache can
+    /// be shared by several solvers, and by the threads of `solve_all`, and the solvers of optimal
+    /// algorithms only take the plans proven optimal from it.
+    /// Only applies to `solve`, `run`, `run_reusing` and `solve_all`.
+    pub fn with_cache(self, cache: Arc<Mutex<PlanCache>>) -> Solver {
+        Solver { cache: Some(cache), ..self }
+    }
+
+    /// Ends the searches at the first board the cache has a plan for, rather than at the goal, and
+    /// follows that plan for the rest of the way. Batches of related boards are then solved much
+    /// faster, but the plans can be longer than the ones the search would find, even with A*.
+    /// Only applies with a cache, to `solve`, `run`, `run_reusing` and `solve_all`.
+    pub fn with_suffix_reuse(self, reuse_suffixes: bool) -> Solver {
+        Solver { reuse_suffixes, ..self }
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    pub fn heuristic(&self) -> HeuristicKind {
+        self.heuristic
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    pub fn move_order(&self) -> MoveOrder {
+        self.move_order
+    }
+
+    pub fn queue(&self) -> QueueKind {
+        self.queue
+    }
+
+    pub fn symmetry(&self) -> bool {
+        self.symmetry
+    }
+
+    pub fn goal(&self) -> &Board {
+        &self.goal
+    }
+
+    pub fn cache(&self) -> Option<&Arc<Mutex<PlanCache>>> {
+        self.cache.as_ref()
+    }
+
+    pub fn reuse_suffixes(&self) -> bool {
+        self.reuse_suffixes
+    }
+
+    pub fn solve(&self, board: Board) -> Option<Plan> {
+        self.run(board).plan
+    }
+
+    /// Solves the board, keeping the search statistics even when no plan is found.
+    /// Boards that cannot reach the goal are rejected without searching.
+    pub fn run(&self, board: Board) -> SolveResult {
+        self.run_cached(board, |board| self.run_reusing_suffixes(board, &mut SearchBuffers::new()))
+    }
+
+    /// Solves the board at the end of a known prefix plan (e.g. the opening moves found by another
+    /// solver), and returns the prefix followed by the plan found from its end, so that the plan
+    /// and its cost cover every move from the start of the prefix
+    pub fn run_from(&self, prefix: &Plan) -> SolveResult {
+        let mut result = self.run(*prefix.end());
+        let statistics = result.statistics.clone();
+        result.board = *prefix.start();
+        result.plan = result.plan.map(|plan| prefix.concat(&plan).unwrap().with_statistics(statistics));
+
+        result
+    }
+
+    /// Solves the board like `run`, but searches within the storage left by the previous searches
+    /// in the buffers, instead of allocating and growing new containers
+    pub fn run_reusing<'a>(&'a self, board: Board, buffers: &mut SolverBuffers<'a>) -> SolveResult {
+        self.run_cached(board, |board| self.run_reusing_suffixes(board, &mut buffers.buffers))
+    }
+
+    /// Solves the board, recording every board expanded by the search
+    pub fn run_traced(&self, board: Board) -> (SolveResult, Trace) {
+        let trace = RefCell::new(Trace::new());
+        let result = self.run_with(board, Some(&trace), &mut SearchBuffers::new());
+        (result, trace.into_inner())
+    }
+
+    /// Solves the board, also returning every board the search saw, with the cost of the cheapest
+    /// path to it found and its heuristic value, such as for coloring a map of the boards by g
+    pub fn run_retaining(&self, board: Board) -> (SolveResult, Vec<ClosedEntry<Board>>) {
+        let mut buffers = SearchBuffers::retaining();
+        let result = self.run_with(board, None, &mut buffers);
+        let closed = buffers.closed()
+            .map(|entry| ClosedEntry { state: entry.state.board, g: entry.g, h: entry.h, id: entry.id, parent: entry.parent })
+            .collect();
+
+        (result, closed)
+    }
+
+    /// Solves the board like `run_traced`, also recording every move along which the heuristic
+    /// dropped by more than one, which a consistent heuristic never does
+    pub fn run_checked(&self, board: Board) -> (SolveResult, Trace) {
+        let trace = RefCell::new(Trace::checking_consistency());
+        let result = self.run_with(board, Some(&trace), &mut SearchBuffers::new());
+        (result, trace.into_inner())
+    }
+
+    //answers from the cache when it has a plan for the board (an optimal one for the optimal
+    //algorithms), and otherwise searches and keeps the plan found, along with whether the search
+    //went all the way to the goal. The cache is not locked during the search.
+    fn run_cached<F: FnOnce(Board) -> (SolveResult, bool)>(&self, board: Board, search: F) -> SolveResult {
+        let Some(cache) = &self.cache else {
+            return search(board).0;
+        };
+
+        let optimal = self.algorithm.is_optimal();
+        if let Some(plan) = cache.lock().unwrap().get(&board, &self.goal, optimal) {
+            return SolveResult { board, plan: Some(plan), statistics: Statistics::default(), stop_reason: None, best: self.goal, best_h: 0 };
+        }
+
+        let (result, searched) = search(board);
+        if let Some(plan) = &result.plan {
+            cache.lock().unwrap().insert(plan, &self.goal, optimal && searched);
+        }
+
+        result
+    }
+
+    //searches up to the first board with a cached plan when reusing suffixes, and splices that plan,
+    //telling whether the plan was searched for all the way to the goal instead, as a spliced plan
+    //is not. The plan is checked again as it is taken from the cache, and searched for up to the
+    //goal instead if it is no longer there, with the statistics of both searches.
+    fn run_reusing_suffixes<'a>(&'a self, board: Board, buffers: &mut SearchBuffers<BoardState<'a>>) -> (SolveResult, bool) {
+        let cache = match &self.cache {
+            Some(cache) if self.reuse_suffixes => cache,
+            _ => return (self.run_with(board, None, buffers), true),
+        };
+
+        //a snapshot of the boards with a plan, so that the searches of other threads sharing the
+        //cache are not held up by this one
+        let optimal = self.algorithm.is_optimal();
+        let cached = cache.lock().unwrap().boards(&self.goal, optimal);
+        let mut result = self.search_with(board, |board: &Board| *board == self.goal || cached.contains(board), None, buffers);
+        let Some(plan) = result.plan.take() else {
+            return (result, true);
+        };
+        if *plan.end() == self.goal {
+            result.plan = Some(plan);
+            return (result, true);
+        }
+
+        let suffix = cache.lock().unwrap().get(plan.end(), &self.goal, optimal).filter(|suffix| suffix.end() == &self.goal);
+        match suffix.and_then(|suffix| plan.concat(&suffix).ok()) {
+            Some(spliced) => {
+                result.plan = Some(spliced.with_statistics(result.statistics.clone()));
+                result.best = self.goal;
+                result.best_h = 0;
+                (result, false)
+            }
+            None => {
+                let mut full = self.run_with(board, None, buffers);
+                full.statistics = result.statistics.combine(&full.statistics);
+                let statistics = full.statistics.clone();
+                full.plan = full.plan.map(|plan| plan.with_statistics(statistics));
+                (full, true)
+            }
+        }
+    }
+
+    fn run_with<'a>(&'a self, board: Board, trace: Option<&'a RefCell<Trace>>, buffers: &mut SearchBuffers<BoardState<'a>>) -> SolveResult {
+        self.search_with(board, |board: &Board| *board == self.goal, trace, buffers)
+    }
+
+    fn search_with<'a, G: Fn(&Board) -> bool>(&'a self, board: Board, goal: G, trace: Option<&'a RefCell<Trace>>, buffers: &mut SearchBuffers<BoardState<'a>>) -> SolveResult {
+        if !board.is_solvable_to(&self.goal) {
+            //the search would only give up after exhausting every board reachable from this one
+            return SolveResult { board, plan: None, statistics: Statistics::default(), stop_reason: None, best: board, best_h: (self.estimate)(&board) };
+        }
+
+        let mut initial_state = BoardState::new(board, &*self.estimate).ordered(self.move_order);
+        if self.heuristic == HeuristicKind::Manhattan && self.goal == GOAL {
+            initial_state = initial_state.incremental();
+        }
+        if self.symmetry {
+            initial_state = initial_state.symmetric(self.goal);
+        }
+        if let Some(trace) = trace {
+            initial_state = initial_state.traced(trace);
+        }
+        let result = crate::search_limited(&initial_state, goal, self.algorithm, self.limits.clone(), self.queue, buffers);
+        let statistics = result.statistics;
+        let plan = result.plan.zip(result.plan_actions).map(|(states, moves)| {
+            Plan::from_search_moves(states.iter().map(|state| state.board).collect(), moves, statistics.clone())
+        });
+
+        SolveResult {
+            board,
+            plan,
+            statistics,
+            stop_reason: result.stop_reason,
+            best: result.best.board,
+            best_h: result.best_h,
+        }
+    }
+
+    /// Solves independent 8-puzzle boards in parallel on the rayon thread pool, each within the
+    /// solver limits. The results are in the same order as the boards.
+    #[cfg(feature = "parallel")]
+    pub fn solve_all(&self, boards: &[Board]) -> Vec<SolveResult> {
+        boards.par_iter()
+            .map_init(SolverBuffers::new, |buffers, board| self.run_reusing(*board, buffers))
+            .collect()
+    }
+
+    /// Solves independent 8-puzzle boards one after the other, without the `parallel` feature, each
+    /// within the solver limits. The results are in the same order as the boards.
+    #[cfg(not(feature = "parallel"))]
+    pub fn solve_all(&self, boards: &[Board]) -> Vec<SolveResult> {
+        let mut buffers = SolverBuffers::new();
+        boards.iter().map(|board| self.run_reusing(*board, &mut buffers)).collect()
+    }
+
+    pub fn solve_goals(&self, board: Board, goals: &GoalSet) -> Option<Plan> {
+        match self.algorithm {
+            Algorithm::BreadthFirst => crate::breadth_first_search_goals(board, goals),
+            Algorithm::Ehc => crate::ehc_search_goals(board, goals),
+            Algorithm::EhcSteepest => crate::ehc_steepest_search_goals(board, goals),
+            Algorithm::GreedyBestFirst => crate::greedy_best_first_search_goals(board, goals),
+            Algorithm::AStar => crate::a_star_search_goals(board, goals),
+        }
+    }
+
+    /// Returns a plan from the actual board to the goal, given the plan that was being executed.
+    /// If the actual board is still on the previous plan, the rest of that plan is returned without
+    /// searching. Otherwise, the search of `run` goes back to any of the boards on the previous plan
+    /// (which is typically a few moves away after a failed move), within the solver limits, and the
+    /// rest of the previous plan is spliced. Boards that cannot reach the goal have no plan.
+    pub fn replan(&self, previous_plan: &Plan, actual: Board) -> Option<Plan> {
+        let previous_boards = previous_plan.boards();
+        if let Some(index) = previous_boards.iter().position(|board| *board == actual) {
+            return Plan::new(previous_boards[index..].to_vec()).ok();
+        }
+
+        if *previous_plan.end() != self.goal {
+            return self.solve(actual);
+        }
+
+        let on_plan: HashSet<Board> = previous_boards.iter().copied().collect();
+        let result = self.search_with(actual, |board: &Board| on_plan.contains(board), None, &mut SearchBuffers::new());
+        let (plan, statistics) = (result.plan?, result.statistics);
+
+        let index = previous_boards.iter().position(|board| board == plan.end()).unwrap();
+        let rest = Plan::new(previous_boards[index..].to_vec()).unwrap();
+
+        plan.concat(&rest).ok().map(|plan| plan.with_statistics(statistics))
+    }
+}
+
+impl Debug for Solver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Solver")
+            .field("algorithm", &self.algorithm)
+            .field("heuristic", &self.heuristic)
+            .field("limits", &self.limits)
+            .field("move_order", &self.move_order)
+            .field("queue", &self.queue)
+            .field("symmetry", &self.symmetry)
+            .field("goal", &self.goal)
+            .field("cache", &self.cache.is_some())
+            .field("reuse_suffixes", &self.reuse_suffixes)
+            .finish()
+    }
+}
+
+fn estimate(heuristic: HeuristicKind, goal: Board) -> Arc<dyn Fn(&Board) -> i32 + Send + Sync> {
+    Arc::new(move |board: &Board| heuristic.h_to(board, &goal))
+}
+
+/// The containers of the searches run by a solver, kept between them with `Solver::run_reusing`.
+/// Useful when solving many boards one after the other, where most of the time would otherwise be
+/// spent growing the containers of every search from empty.
+pub struct SolverBuffers<'a> {
+    buffers: SearchBuffers<BoardState<'a>>,
+}
+
+impl SolverBuffers<'_> {
+    pub fn new() -> Self {
+        SolverBuffers { buffers: SearchBuffers::new() }
+    }
+
+    /// Drops the contents, keeping the storage
+    pub fn clear(&mut self) {
+        self.buffers.clear();
+    }
+
+    /// Drops the contents and their storage
+    pub fn release(&mut self) {
+        self.buffers.release();
+    }
+
+    /// How many entries the containers can hold before they need to grow again
+    pub fn capacity(&self) -> Capacity {
+        self.buffers.capacity()
+    }
+}
+
+impl Default for SolverBuffers<'_> {
+    fn default() -> Self {
+        SolverBuffers::new()
+    }
+}
+
+/// The statistics of all the searches added up
+pub fn total_statistics(results: &[SolveResult]) -> Option<Statistics> {
+    results.iter()
+        .map(|result| result.statistics.clone())
+        .reduce(|total, statistics| total.combine(&statistics))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::BLANK_FIRST_GOAL;
+
+    use super::*;
+
+    #[test]
+    fn test_algorithm_names() {
+        for algorithm in Algorithm::all().iter() {
+            assert_eq!(algorithm.name().parse::<Algorithm>(), Ok(*algorithm));
+        }
+        assert!("dijkstra".parse::<Algorithm>().is_err());
+    }
+
+    #[test]
+    fn test_with_heuristic() {
+        let board = Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]);
+        let solver = Solver::new(Algorithm::AStar).with_heuristic(HeuristicKind::DisplacedTiles);
+        let result = solver.run(board);
+
+        //both heuristics are admissible, but the weaker one needs to expand more boards
+        let manhattan = Solver::new(Algorithm::AStar).run(board);
+        assert_eq!(result.plan.as_ref().unwrap().steps(), manhattan.plan.as_ref().unwrap().steps());
+        assert!(result.statistics.expanded > manhattan.statistics.expanded);
+    }
+
+    #[test]
+    fn test_move_order() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let default = Solver::new(Algorithm::GreedyBestFirst).run(board);
+        let reversed = Solver::new(Algorithm::GreedyBestFirst).with_move_order(MoveOrder::Reversed).run(board);
+
+        //ties are broken differently, but A* stays optimal whatever the order
+        assert_ne!(default.statistics.expanded, reversed.statistics.expanded);
+        for order in [MoveOrder::Reversed, MoveOrder::Random(3)].iter() {
+            let plan = Solver::new(Algorithm::AStar).with_move_order(*order).solve(board).unwrap();
+            assert_eq!(plan.steps(), 31);
+        }
+    }
+
+    #[test]
+    fn test_blank_first_goal() {
+        let board = Board::new([1, 2, 0, 3, 4, 5, 6, 7, 8]);
+        let solver = Solver::new(Algorithm::AStar).with_goal(BLANK_FIRST_GOAL);
+
+        let plan = solver.solve(board).unwrap();
+        assert_eq!(*plan.end(), BLANK_FIRST_GOAL);
+        assert_eq!(plan.steps(), 2);
+        assert!(Solver::new(Algorithm::AStar).solve(board).unwrap().steps() > 2);
+    }
+
+    #[test]
+    fn test_solve_all() {
+        let boards = [
+            Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]),
+            Board::new([6, 4, 7, 8, 5, 0, 3, 2, 1]),
+            Board::new([1, 2, 3, 0, 4, 6, 7, 5, 8]),
+        ];
+        let solver = Solver::new(Algorithm::AStar);
+        let results = solver.solve_all(&boards);
+
+        assert_eq!(results.len(), 3);
+        for (board, result) in boards.iter().zip(results.iter()) {
+            assert_eq!(result.board, *board);
+            assert_eq!(result.plan, crate::a_star_search(*board));
+        }
+
+        let total = total_statistics(&results).unwrap();
+        assert_eq!(total.expanded, results.iter().map(|result| result.statistics.expanded).sum::<i32>());
+    }
+
+    #[test]
+    fn test_run_from_prefix() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        //a first move chosen elsewhere, which the search continues from
+        let prefix = Plan::new(vec![board, board.successors()[0]]).unwrap();
+        let solver = Solver::new(Algorithm::AStar);
+        let result = solver.run_from(&prefix);
+
+        let plan = result.plan.unwrap();
+        let rest = solver.solve(*prefix.end()).unwrap();
+        assert_eq!(result.board, board);
+        assert_eq!(*plan.start(), board);
+        assert_eq!(*plan.end(), GOAL);
+        assert_eq!(plan.steps(), prefix.steps() + rest.steps());
+        assert_eq!(plan.cost(), prefix.cost() + rest.cost());
+        assert_eq!(&plan.boards()[..2], prefix.boards());
+        assert!(plan.statistics().is_some());
+    }
+
+    #[test]
+    fn test_undo_plan() {
+        let board = Board::new([1, 2, 3, 4, 0, 6, 7, 5, 8]);
+        let result = Solver::new(Algorithm::AStar).run(board);
+
+        let undo = result.undo_plan().unwrap();
+        assert_eq!(*undo.start(), GOAL);
+        assert_eq!(*undo.end(), board);
+        assert_eq!(undo.moves(), result.plan.unwrap().undo_moves().as_slice());
+        assert!(Solver::new(Algorithm::AStar).run(Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0])).undo_plan().is_none());
+    }
+
+    #[test]
+    fn test_queue_kinds_find_the_same_plans() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        for algorithm in [Algorithm::AStar, Algorithm::GreedyBestFirst].iter() {
+            let solver = Solver::new(*algorithm);
+            let expected = solver.run(board);
+
+            for queue in [QueueKind::DAryHeap(4), QueueKind::PairingHeap, QueueKind::Buckets].iter() {
+                let result = solver.clone().with_queue(*queue).run(board);
+                assert_eq!(result.plan, expected.plan, "{} with {}", algorithm, queue);
+                assert_eq!(result.statistics.expanded, expected.statistics.expanded, "{} with {}", algorithm, queue);
+            }
+        }
+    }
+
+    #[test]
+    fn test_symmetry() {
+        for board in [Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]), Board::new([1, 2, 3, 0, 4, 6, 7, 5, 8])].iter() {
+            for solver in [Solver::new(Algorithm::AStar), Solver::new(Algorithm::BreadthFirst), Solver::new(Algorithm::AStar).with_goal(BLANK_FIRST_GOAL)].iter() {
+                let expected = solver.run(*board);
+                let result = solver.clone().with_symmetry(true).run(*board);
+
+                let plan = result.plan.unwrap();
+                assert_eq!(plan.steps(), expected.plan.unwrap().steps());
+                assert_eq!(plan.end(), solver.goal());
+                assert!(Plan::new(plan.boards().to_vec()).is_ok());
+                assert!(result.statistics.seen <= expected.statistics.seen);
+            }
+        }
+
+        //breadth first search goes through every board closer than the goal, half of them mirrored
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let solver = Solver::new(Algorithm::BreadthFirst);
+        assert!(solver.clone().with_symmetry(true).run(board).statistics.expanded * 10 < solver.run(board).statistics.expanded * 6);
+    }
+
+    #[test]
+    fn test_unsolvable_board() {
+        let board = Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0]);
+        let result = Solver::new(Algorithm::BreadthFirst).run(board);
+
+        assert!(result.plan.is_none());
+        assert!(!result.limit_reached());
+        assert_eq!(result.statistics.expanded, 0);
+        assert_eq!(result.best, board);
+        assert_eq!(result.best_h, 2);
+
+        let solver = Solver::new(Algorithm::AStar).with_goal(BLANK_FIRST_GOAL);
+        assert!(solver.solve(board).is_none());
+        assert!(solver.solve(Board::new([1, 0, 2, 3, 4, 5, 6, 7, 8])).is_some());
+    }
+
+    #[test]
+    fn test_run_reusing() {
+        let solver = Solver::new(Algorithm::AStar);
+        let mut buffers = SolverBuffers::new();
+
+        for tiles in [[8, 6, 7, 2, 5, 4, 3, 0, 1], [1, 2, 3, 0, 4, 6, 7, 5, 8], [6, 4, 7, 8, 5, 0, 3, 2, 1]].iter() {
+            let board = Board::new(*tiles);
+            let reused = solver.run_reusing(board, &mut buffers);
+            let fresh = solver.run(board);
+
+            assert_eq!(reused.plan, fresh.plan);
+            assert_eq!(reused.statistics.expanded, fresh.statistics.expanded);
+        }
+
+        let capacity = buffers.capacity();
+        assert!(capacity.seen > 0);
+        buffers.clear();
+        assert_eq!(buffers.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_run_retaining() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let (result, closed) = Solver::new(Algorithm::AStar).run_retaining(board);
+
+        if !cfg!(feature = "embedded") {
+            assert_eq!(closed.len(), result.statistics.seen);
+        }
+        let goal = closed.iter().find(|entry| entry.state == GOAL).unwrap();
+        assert_eq!(goal.g, 31);
+        assert_eq!(goal.h, 0);
+        assert!(closed.iter().all(|entry| entry.h == entry.state.manhattan_dist()));
+    }
+
+    #[test]
+    fn test_solve_all_with_limits() {
+        let boards = [Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]), Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8])];
+        let solver = Solver::new(Algorithm::BreadthFirst).with_limits(Limits { max_expanded: Some(100), ..Limits::none() });
+        let results = solver.solve_all(&boards);
+
+        assert!(results[0].plan.is_none());
+        assert!(results[0].limit_reached());
+        assert_eq!(results[0].stop_reason, Some(StopReason::MaxExpanded));
+        assert_eq!(results[1].plan.as_ref().map(|plan| plan.steps()), Some(1));
+        assert!(!results[1].limit_reached());
+    }
+
+    #[test]
+    fn test_cache() {
+        let cache = Arc::new(Mutex::new(PlanCache::new(10)));
+        let solver = Solver::new(Algorithm::AStar).with_cache(cache.clone());
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+
+        let searched = solver.run(board);
+        assert!(searched.statistics.expanded > 0);
+        let cached = solver.run(board);
+        assert_eq!(cached.statistics.expanded, 0);
+        assert_eq!(cached.plan.unwrap().boards(), searched.plan.unwrap().boards());
+
+        //the mirror image is answered from the same entry, also by the threads of solve_all
+        let mirror = board.reflected_to(&GOAL).unwrap();
+        let results = solver.solve_all(&[mirror, board]);
+        assert!(results.iter().all(|result| result.statistics.expanded == 0 && result.plan.as_ref().unwrap().steps() == 31));
+        assert_eq!(*results[0].plan.as_ref().unwrap().start(), mirror);
+
+        //a solver towards another goal searches again
+        let other = solver.with_goal(BLANK_FIRST_GOAL);
+        assert!(other.run(board).statistics.expanded > 0);
+        assert_eq!(cache.lock().unwrap().len(), 2);
+        assert_eq!(cache.lock().unwrap().hits(), 3);
+    }
+
+    #[test]
+    fn test_cache_keeps_optimality() {
+        let cache = Arc::new(Mutex::new(PlanCache::new(10)));
+        let greedy = Solver::new(Algorithm::GreedyBestFirst).with_cache(cache.clone());
+        let a_star = Solver::new(Algorithm::AStar).with_cache(cache.clone());
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+
+        assert!(greedy.run(board).plan.unwrap().steps() > 31);
+        //the greedy plan does not do for A*, which searches and keeps its own plan instead
+        let searched = a_star.run(board);
+        assert!(searched.statistics.expanded > 0);
+        assert_eq!(searched.plan.unwrap().steps(), 31);
+        assert_eq!(a_star.run(board).statistics.expanded, 0);
+        assert_eq!(greedy.run(board).plan.unwrap().steps(), 31);
+    }
+
+    #[test]
+    fn test_suffix_reuse() {
+        let cache = Arc::new(Mutex::new(PlanCache::new(10)));
+        let solver = Solver::new(Algorithm::AStar).with_cache(cache.clone()).with_suffix_reuse(true);
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let full = solver.run(board);
+
+        //one move away from a cached board, off its plan
+        let plan = full.plan.unwrap();
+        let next = board.successors().into_iter().find(|successor| *successor != plan.boards()[1]).unwrap();
+        let result = solver.run(next);
+        let spliced = result.plan.unwrap();
+        assert_eq!((*spliced.start(), *spliced.end(), spliced.steps()), (next, GOAL, 32));
+        assert_eq!(&spliced.boards()[1..], plan.boards());
+        assert!(Plan::new(spliced.boards().to_vec()).is_ok());
+        assert!(result.statistics.expanded < full.statistics.expanded / 100);
+        assert_eq!(result.best, GOAL);
+
+        //without reuse the search goes all the way
+        let result = solver.clone().with_suffix_reuse(false).run(next.successors()[0]);
+        assert_eq!(*result.plan.unwrap().end(), GOAL);
+        assert!(result.statistics.expanded > 100);
+    }
+
+    #[test]
+    fn test_replan_on_plan() {
+        let solver = Solver::new(Algorithm::AStar);
+        let plan = solver.solve(Board::new([1, 2, 3, 4, 0, 6, 7, 5, 8])).unwrap();
+
+        let replanned = solver.replan(&plan, plan.boards()[1]).unwrap();
+        assert_eq!(replanned.boards(), &plan.boards()[1..]);
+    }
+
+    #[test]
+    fn test_replan_off_plan() {
+        let solver = Solver::new(Algorithm::AStar);
+        let plan = solver.solve(Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1])).unwrap();
+
+        //the robot moved the wrong tile on the first step
+        let actual = plan.start().successors().into_iter()
+            .find(|board| *board != plan.boards()[1])
+            .unwrap();
+
+        let replanned = solver.replan(&plan, actual).unwrap();
+        assert_eq!(*replanned.start(), actual);
+        assert_eq!(*replanned.end(), GOAL);
+        assert!(Plan::new(replanned.boards().to_vec()).is_ok());
+    }
+
+    #[test]
+    fn test_replan_with_solver_settings() {
+        let solver = Solver::new(Algorithm::AStar);
+        let plan = solver.solve(Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1])).unwrap();
+        let actual = plan.start().successors().into_iter()
+            .find(|board| *board != plan.boards()[1])
+            .unwrap();
+
+        //the search back to the plan stops on the limits of the solver
+        let limited = solver.clone().with_limits(Limits { max_expanded: Some(1), ..Limits::none() });
+        assert!(limited.replan(&plan, actual).is_none());
+
+        //the board back on the plan is one move away, rather than the whole way to the goal
+        let replanned = solver.clone().with_heuristic(HeuristicKind::DisplacedTiles).replan(&plan, actual).unwrap();
+        assert_eq!(replanned.boards()[1], *plan.start());
+        assert_eq!(*replanned.end(), GOAL);
+        assert!(replanned.statistics().unwrap().expanded <= 2);
+
+        //a board that cannot reach the goal is not searched
+        assert!(solver.replan(&plan, Board::new([2, 1, 3, 4, 5, 6, 7, 8, 0])).is_none());
+    }
+
+    #[test]
+    fn test_replan_previous_plan_not_to_goal() {
+        let solver = Solver::new(Algorithm::AStar);
+        let previous_plan = Plan::new(vec![Board::new([1, 2, 3, 4, 5, 0, 7, 8, 6])]).unwrap();
+        let replanned = solver.replan(&previous_plan, Board::new([1, 2, 3, 4, 5, 6, 7, 0, 8])).unwrap();
+
+        assert_eq!(replanned.steps(), 1);
+    }
+}
@@ -0,0 +1,298 @@
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::path::Path;
+
+use crate::board::Board;
+use crate::plan::Plan;
+
+/// A board expanded by the search, with its heuristic value and the boards generated from it
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Expansion<const R: usize = 3, const C: usize = R> {
+    pub board: Board<R, C>,
+    pub h: i32,
+    pub successors: Vec<Board<R, C>>,
+}
+
+/// A move along which the heuristic dropped by more than the cost of the move, so that it is not
+/// consistent, and A* may have to expand boards again after finding better paths to them
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Inconsistency<const R: usize = 3, const C: usize = R> {
+    pub from: Board<R, C>,
+    pub from_h: i32,
+    pub to: Board<R, C>,
+    pub to_h: i32,
+}
+
+/// The boards expanded by a search, in the order in which they were expanded
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Trace<const R: usize = 3, const C: usize = R> {
+    expansions: Vec<Expansion<R, C>>,
+    check_consistency: bool,
+    inconsistencies: Vec<Inconsistency<R, C>>,
+}
+
+impl<const R: usize, const C: usize> Trace<R, C> {
+    pub fn new() -> Trace<R, C> {
+        Trace { expansions: Vec::new(), check_consistency: false, inconsistencies: Vec::new() }
+    }
+
+    /// A trace that also checks the heuristic on every move generated by the search, for debugging
+    /// heuristics that are learned or combined from others
+    pub fn checking_consistency() -> Trace<R, C> {
+        Trace { check_consistency: true, ..Trace::new() }
+    }
+
+    pub(crate) fn record(&mut self, expansion: Expansion<R, C>) {
+        self.expansions.push(expansion);
+    }
+
+    pub(crate) fn checks_consistency(&self) -> bool {
+        self.check_consistency
+    }
+
+    pub(crate) fn record_inconsistency(&mut self, inconsistency: Inconsistency<R, C>) {
+        self.inconsistencies.push(inconsistency);
+    }
+
+    pub fn expansions(&self) -> &[Expansion<R, C>] {
+        &self.expansions
+    }
+
+    /// The moves that broke the consistency of the heuristic, when checking it
+    pub fn inconsistencies(&self) -> &[Inconsistency<R, C>] {
+        &self.inconsistencies
+    }
+
+    pub fn len(&self) -> usize {
+        self.expansions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expansions.is_empty()
+    }
+
+    /// Writes a self-contained HTML page that steps through the expansions on a rendered board,
+    /// followed by the moves of the plan (if one was found)
+    pub fn write_html<W: Write>(&self, writer: &mut W, plan: Option<&Plan<R, C>>) -> io::Result<()> {
+        let expansions: Vec<String> = self.expansions.iter()
+            .map(|expansion| {
+                let successors: Vec<String> = expansion.successors.iter().map(tiles_json).collect();
+                format!("{{\"board\":{},\"h\":{},\"successors\":[{}]}}", tiles_json(&expansion.board), expansion.h, successors.join(","))
+            })
+            .collect();
+        let plan: Vec<String> = plan.map_or(Vec::new(), |plan| plan.boards().iter().map(tiles_json).collect());
+
+        writer.write_all(HTML_HEAD.as_bytes())?;
+        writeln!(writer, "const trace = {{\"columns\":{},\"expansions\":[{}],\"plan\":[{}]}};", C, expansions.join(","), plan.join(","))?;
+        writer.write_all(HTML_TAIL.as_bytes())
+    }
+}
+
+const RANKS_HEADER: &str = "tiles-trace-ranks v1";
+
+impl Trace {
+    /// Saves the permutation rank and heuristic value of every expanded board, one expansion per
+    /// line, so that traces of different searches can be compared later on
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut contents = format!("{}\n", RANKS_HEADER);
+        for expansion in &self.expansions {
+            contents.push_str(&format!("{} {}\n", expansion.board.rank(), expansion.h));
+        }
+
+        fs::write(path, contents)
+    }
+
+    /// Loads a trace saved by `save`, with the successors of each board generated again
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Trace> {
+        let contents = fs::read_to_string(path)?;
+        let mut lines = contents.lines();
+        if lines.next() != Some(RANKS_HEADER) {
+            return Err(io::Error::new(ErrorKind::InvalidData, "Not a search trace file."));
+        }
+
+        let mut trace = Trace::new();
+        for line in lines {
+            let invalid = || io::Error::new(ErrorKind::InvalidData, format!("Invalid expansion: {}", line));
+            let (rank, h) = line.split_once(' ').ok_or_else(invalid)?;
+            let rank = rank.parse::<u32>().ok().filter(|rank| *rank < Board::RANKS).ok_or_else(invalid)?;
+            let h = h.parse::<i32>().map_err(|_| invalid())?;
+
+            let board = Board::unrank(rank);
+            trace.record(Expansion { board, h, successors: board.successors() });
+        }
+
+        Ok(trace)
+    }
+}
+
+fn tiles_json<const R: usize, const C: usize>(board: &Board<R, C>) -> String {
+    let tiles: Vec<String> = board.tiles().iter().map(|tile| tile.to_string()).collect();
+    format!("[{}]", tiles.join(","))
+}
+
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Sliding tiles search trace</title>
+<style>
+  body { font-family: sans-serif; margin: 2em; }
+  .board { display: grid; grid-template-columns: repeat(var(--columns, 3), 4em); gap: 0.3em; margin: 1em 0; }
+  .tile { height: 4em; display: flex; align-items: center; justify-content: center; font-size: 1.5em; background: #4a78b5; color: white; border-radius: 0.2em; }
+  .blank { background: #eee; }
+  .successors { display: flex; gap: 1.5em; }
+  .successors .board { grid-template-columns: repeat(var(--columns, 3), 2em); }
+  .successors .tile { height: 2em; font-size: 1em; }
+  #step { width: 30em; }
+</style>
+</head>
+<body>
+<h1>Search trace</h1>
+<div>
+  <button id="previous">&larr;</button>
+  <button id="play">Play</button>
+  <button id="next">&rarr;</button>
+  <input id="step" type="range" min="0" value="0">
+</div>
+<p id="caption"></p>
+<div id="board" class="board"></div>
+<div id="successors" class="successors"></div>
+<script>
+"#;
+
+const HTML_TAIL: &str = r#"
+const steps = trace.expansions.map((expansion, index) => ({
+  board: expansion.board,
+  successors: expansion.successors,
+  caption: "Expansion " + (index + 1) + " of " + trace.expansions.length + " (h = " + expansion.h + ")"
+})).concat(trace.plan.map((board, index) => ({
+  board: board,
+  successors: [],
+  caption: "Plan step " + index + " of " + (trace.plan.length - 1)
+})));
+
+const slider = document.getElementById("step");
+slider.max = Math.max(steps.length - 1, 0);
+let timer = null;
+
+function render(container, tiles) {
+  container.innerHTML = "";
+  container.style.setProperty("--columns", trace.columns);
+  for (const tile of tiles) {
+    const cell = document.createElement("div");
+    cell.className = tile === 0 ? "tile blank" : "tile";
+    cell.textContent = tile === 0 ? "" : tile;
+    container.appendChild(cell);
+  }
+}
+
+function show(index) {
+  if (steps.length === 0) {
+    document.getElementById("caption").textContent = "Nothing was expanded.";
+    return;
+  }
+  const step = steps[index];
+  slider.value = index;
+  document.getElementById("caption").textContent = step.caption;
+  render(document.getElementById("board"), step.board);
+
+  const successors = document.getElementById("successors");
+  successors.innerHTML = "";
+  for (const tiles of step.successors) {
+    const board = document.createElement("div");
+    board.className = "board";
+    render(board, tiles);
+    successors.appendChild(board);
+  }
+}
+
+function move(delta) {
+  const index = Math.min(Math.max(Number(slider.value) + delta, 0), steps.length - 1);
+  show(index);
+  return index;
+}
+
+document.getElementById("previous").onclick = () => move(-1);
+document.getElementById("next").onclick = () => move(1);
+slider.oninput = () => show(Number(slider.value));
+document.getElementById("play").onclick = (event) => {
+  if (timer) {
+    clearInterval(timer);
+    timer = null;
+    event.target.textContent = "Play";
+  } else {
+    event.target.textContent = "Pause";
+    timer = setInterval(() => {
+      if (move(1) === steps.length - 1) {
+        clearInterval(timer);
+        timer = null;
+        event.target.textContent = "Play";
+      }
+    }, 200);
+  }
+};
+
+show(0);
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use crate::solver::{Algorithm, Solver};
+
+    use super::*;
+
+    #[test]
+    fn test_trace_expansions() {
+        let board = Board::new([1, 2, 3, 4, 0, 6, 7, 5, 8]);
+        let (result, trace) = Solver::new(Algorithm::AStar).run_traced(board);
+
+        assert_eq!(trace.len() as i32, result.statistics.expanded);
+        assert_eq!(trace.expansions()[0].board, board);
+        assert_eq!(trace.expansions()[0].h, 2);
+        assert_eq!(trace.expansions()[0].successors, board.successors());
+    }
+
+    #[test]
+    fn test_write_html() {
+        let board = Board::new([1, 2, 3, 4, 0, 6, 7, 5, 8]);
+        let (result, trace) = Solver::new(Algorithm::AStar).run_traced(board);
+
+        let mut html = Vec::new();
+        trace.write_html(&mut html, result.plan.as_ref()).unwrap();
+        let html = String::from_utf8(html).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("\"columns\":3"));
+        assert!(html.contains("\"plan\":[[1,2,3,4,0,6,7,5,8],[1,2,3,4,5,6,7,0,8],[1,2,3,4,5,6,7,8,0]]"));
+        assert!(html.contains("{\"board\":[1,2,3,4,0,6,7,5,8],\"h\":2,"));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let (_, trace) = Solver::new(Algorithm::AStar).run_traced(board);
+
+        let path = std::env::temp_dir().join(format!("tiles-trace-{}.txt", std::process::id()));
+        trace.save(&path).unwrap();
+        let loaded = Trace::load(&path);
+        fs::write(&path, "tiles-trace-ranks v1\n362880 4\n").unwrap();
+        let invalid = Trace::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), trace);
+        assert_eq!(invalid.unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_consistent_heuristic() {
+        let board = Board::new([8, 6, 7, 2, 5, 4, 3, 0, 1]);
+        let (result, trace) = Solver::new(Algorithm::AStar).run_checked(board);
+
+        assert!(result.plan.is_some());
+        assert!(trace.inconsistencies().is_empty());
+        assert!(Trace::<3>::new().inconsistencies().is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+//! Golden traces: the cost of the plan and the number of boards expanded by every search on a fixed
+//! set of boards, checked against tests/golden.txt. A refactoring that is not meant to change what
+//! the searches do (node storage, board packing, queues) must leave them all as they are.
+//! When a change is meant to alter them, record the new values with
+//!
+//! ```text
+//! TILES_UPDATE_GOLDEN=1 cargo test --test golden
+//! ```
+//!
+//! and review the difference in tests/golden.txt along with the change.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tiles::anytime::{anytime_weighted_a_star, ara_star, continued_weighted_a_star, Stepwise};
+use tiles::beam::{beam_search, BeamWidth};
+use tiles::board::costed::CostedBoard;
+use tiles::board::torus::TorusBoard;
+use tiles::board::{Board, MoveOrder, Tile};
+use tiles::dataset::Sampling;
+use tiles::heuristic::HeuristicKind;
+use tiles::instances::InstanceSet;
+use tiles::pdb::{pattern_db_heuristic, Compression, DisjointPatternDatabases};
+use tiles::perimeter::{perimeter_search, Perimeter};
+use tiles::plan::Plan;
+use tiles::queue::QueueKind;
+use tiles::search::Limits;
+use tiles::solver::{Algorithm, Solver};
+
+const HEADER: &str = "tiles-golden v1";
+const SAMPLING: Sampling = Sampling::RandomWalk(40);
+const SEED: u64 = 1;
+const BOARDS: usize = 12;
+const UPDATE: &str = "TILES_UPDATE_GOLDEN";
+const ANYTIME_DEADLINE: Duration = Duration::from_secs(600);
+
+type Search = Box<dyn Fn(Board) -> Option<Plan>>;
+
+/// Every search with a name of its own, which is how it is found in the golden file.
+/// A new search or configuration belongs in this list, and its values are recorded with the others.
+fn searches() -> Vec<(String, Search)> {
+    let mut searches: Vec<(String, Search)> = Vec::new();
+    for algorithm in Algorithm::all() {
+        let heuristics = if algorithm.is_informed() { HeuristicKind::all().to_vec() } else { vec![HeuristicKind::Manhattan] };
+        for heuristic in heuristics {
+            let solver = Solver::new(algorithm).with_heuristic(heuristic);
+            let name = if algorithm.is_informed() { format!("{}-{}", algorithm, heuristic) } else { algorithm.to_string() };
+            searches.push((name, Box::new(move |board| solver.solve(board))));
+        }
+    }
+
+    for (name, queue) in [("dary4", QueueKind::DAryHeap(4)), ("pairing", QueueKind::PairingHeap), ("buckets", QueueKind::Buckets)] {
+        let solver = Solver::new(Algorithm::AStar).with_queue(queue);
+        searches.push((format!("astar-{}", name), Box::new(move |board| solver.solve(board))));
+    }
+    let solver = Solver::new(Algorithm::AStar).with_move_order(MoveOrder::Reversed);
+    searches.push(("astar-reversed".to_string(), Box::new(move |board| solver.solve(board))));
+
+    searches.push(("astar-packed".to_string(), Box::new(tiles::a_star_search_packed)));
+    searches.push(("mm".to_string(), Box::new(tiles::mm_search)));
+    searches.push(("idastar".to_string(), Box::new(tiles::ida_star_search)));
+    searches.push(("dfbnb".to_string(), Box::new(tiles::dfbnb_search)));
+    searches.push(("iddfs".to_string(), Box::new(tiles::iddfs_search)));
+
+    let databases = DisjointPatternDatabases::<3>::build(&[vec![1, 2, 3, 4], vec![5, 6, 7, 8]], Compression::None);
+    searches.push(("astar-pdb".to_string(), Box::new(move |board| tiles::a_star_search_with(board, pattern_db_heuristic(&databases)))));
+    let perimeter = Perimeter::<3>::build(4);
+    searches.push(("perimeter-4".to_string(), Box::new(move |board| perimeter_search(board, &perimeter, tiles::manhattan_heuristic))));
+    for width in [10, 100] {
+        searches.push((format!("beam-{}", width), Box::new(move |board| beam_search(board, tiles::manhattan_heuristic, BeamWidth::Fixed(width)).plan)));
+    }
+
+    //the anytime searches only stop at the deadline when it comes before they prove their plan
+    //optimal, which they do on these boards long before this one, so their traces do not depend
+    //on the speed of the machine, nor on the clock, which the embedded profile does not have.
+    searches.push(("anytime-stepwise".to_string(), Box::new(|board| {
+        anytime_weighted_a_star(board, tiles::manhattan_heuristic, &mut stepwise(), ANYTIME_DEADLINE).plan
+    })));
+    searches.push(("ara-stepwise".to_string(), Box::new(|board| {
+        ara_star(board, tiles::manhattan_heuristic, &mut stepwise(), ANYTIME_DEADLINE, |_, _| {}).plan
+    })));
+    searches.push(("continued-3".to_string(), Box::new(|board| {
+        continued_weighted_a_star(board, tiles::manhattan_heuristic, 3.0, &Limits::none(), |_, _| {}).plan
+    })));
+
+    //the plans of these count the cost of the moves, or the moves around the edges, so they are
+    //not expected to match the others
+    searches.push(("ucs-costed-tile".to_string(), Box::new(|board| tiles::uniform_cost_search_costed(CostedBoard::new(board, tile_cost)))));
+    searches.push(("astar-costed-tile".to_string(), Box::new(|board| tiles::a_star_search_costed(CostedBoard::new(board, tile_cost)))));
+    searches.push(("astar-torus".to_string(), Box::new(|board| tiles::a_star_search_torus(TorusBoard::new(board)))));
+    searches
+}
+
+fn stepwise() -> Stepwise {
+    Stepwise { first: 3.0, step: 1.0, last: 1.0 }
+}
+
+fn tile_cost(tile: Tile, _from: usize, _to: usize) -> u32 {
+    tile as u32
+}
+
+/// One line per search and board: the name of the search, the index of the board, the steps of
+/// the plan and the boards expanded, with `-` for a missing plan or statistics
+fn trace() -> Vec<String> {
+    let boards = InstanceSet::generate(SAMPLING, SEED, BOARDS).boards;
+    let mut lines = Vec::new();
+    for (name, search) in searches() {
+        for (index, board) in boards.iter().enumerate() {
+            let plan = search(*board);
+            let steps = plan.as_ref().map_or("-".to_string(), |plan| plan.steps().to_string());
+            let expanded = plan.as_ref().and_then(|plan| plan.statistics()).map_or("-".to_string(), |statistics| statistics.expanded.to_string());
+            lines.push(format!("{} {} {} {}", name, index, steps, expanded));
+        }
+    }
+    lines
+}
+
+fn golden_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden.txt")
+}
+
+#[test]
+fn test_golden_traces() {
+    let header = format!("{}\ninstances {} seed {} count {}", HEADER, SAMPLING, SEED, BOARDS);
+    let actual = trace();
+
+    if env::var_os(UPDATE).is_some() {
+        let contents = format!("{}\n{}\n", header, actual.join("\n"));
+        fs::write(golden_path(), contents).unwrap();
+        return;
+    }
+
+    let contents = fs::read_to_string(golden_path())
+        .unwrap_or_else(|e| panic!("Cannot read {}: {}, record it with {}=1", golden_path().display(), e, UPDATE));
+    let expected: Vec<&str> = contents.lines().collect();
+    let header_lines = header.lines().count();
+    assert_eq!(expected[..header_lines].join("\n"), header, "The golden traces were recorded on other boards, record them again with {}=1", UPDATE);
+
+    let expected = &expected[header_lines..];
+
+    //every difference at once rather than the first, as a change rarely moves a single value
+    let mut drifts: Vec<String> = actual.iter()
+        .filter(|line| !expected.contains(&line.as_str()))
+        .map(|line| format!("now:      {}", line))
+        .collect();
+    drifts.extend(expected.iter()
+        .filter(|line| !actual.iter().any(|actual| actual == *line))
+        .map(|line| format!("recorded: {}", line)));
+
+    assert!(drifts.is_empty(), "The searches no longer match their golden traces (search, board, steps, expanded):\n{}\nIf the change is intended, record them again with {}=1", drifts.join("\n"), UPDATE);
+}
@@ -0,0 +1,61 @@
+//! Cross-checks the optimal searches against each other, and against the exact distance of every
+//! board of the 8-puzzle. They take minutes, so they only run when asked for with
+//! `cargo test --release --test optimality -- --ignored`
+
+use rayon::prelude::*;
+
+use tiles::board::Board;
+use tiles::dataset::{DatasetGenerator, Sampling};
+use tiles::oracle::OracleTable;
+use tiles::pdb::{pattern_db_heuristic, Compression, DisjointPatternDatabases};
+
+/// The steps of the plan found by each of the optimal searches, which all have to agree.
+/// Any new optimal search (such as a bidirectional search) belongs in this list.
+fn optimal_steps(board: Board, databases: &DisjointPatternDatabases) -> Vec<(&'static str, Option<usize>)> {
+    vec![
+        ("bfs", tiles::breadth_first_search(board).map(|plan| plan.steps())),
+        ("astar-manhattan", tiles::a_star_search(board).map(|plan| plan.steps())),
+        ("astar-displaced", tiles::a_star_search_with(board, tiles::displaced_tiles_heuristic).map(|plan| plan.steps())),
+        ("astar-pdb", tiles::a_star_search_with(board, pattern_db_heuristic(databases)).map(|plan| plan.steps())),
+        ("idastar-manhattan", tiles::ida_star_search(board).map(|plan| plan.steps())),
+        ("mm-manhattan", tiles::mm_search(board).map(|plan| plan.steps())),
+        ("dfbnb-manhattan", tiles::dfbnb_search(board).map(|plan| plan.steps())),
+        ("iddfs", tiles::iddfs_search(board).map(|plan| plan.steps())),
+    ]
+}
+
+fn databases() -> DisjointPatternDatabases {
+    DisjointPatternDatabases::build(&[vec![1, 2, 3, 4], vec![5, 6, 7, 8]], Compression::None)
+}
+
+#[test]
+#[ignore]
+fn test_random_sample_agrees() {
+    let databases = databases();
+    let samples: Vec<_> = DatasetGenerator::new(Sampling::Uniform, 0).take(1000).collect();
+
+    samples.par_iter().for_each(|sample| {
+        for (search, steps) in optimal_steps(sample.board, &databases) {
+            assert_eq!(steps, Some(sample.cost as usize), "{} on {:?}", search, sample.board);
+        }
+    });
+}
+
+#[test]
+#[ignore]
+fn test_every_board_agrees_with_oracle() {
+    let oracle = OracleTable::build();
+    let databases = databases();
+    let heuristic = pattern_db_heuristic(&databases);
+
+    let (boards, unsolvable): (Vec<Board>, Vec<Board>) = (0..Board::RANKS)
+        .map(Board::unrank)
+        .partition(|board| board.is_solvable());
+    assert!(unsolvable.iter().all(|board| oracle.distance(board).is_none()));
+
+    boards.par_iter().for_each(|board| {
+        let distance = oracle.distance(board).map(|distance| distance as usize);
+        assert_eq!(tiles::a_star_search(*board).map(|plan| plan.steps()), distance, "astar-manhattan on {:?}", board);
+        assert_eq!(tiles::a_star_search_with(*board, &heuristic).map(|plan| plan.steps()), distance, "astar-pdb on {:?}", board);
+    });
+}